@@ -11,34 +11,541 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::checkpoint::Checkpoint;
+use crate::checkpoint::WorkItem;
+use crate::cli::ExecOutputFormat;
+use crate::cli::OutputFormat;
+use crate::cli::WarmupSeed;
+#[cfg(feature = "parquet")]
+use crate::columnar::ParquetSink;
 use crate::config::ResolvedConfig;
 use crate::config::ResolvedGenerator;
 use crate::config::ResolvedTask;
+use crate::config::ResourceLimits;
+use crate::config::RunOptions;
 use crate::error::BenchmarkError;
+use crate::manifest::ArgsDelivery;
+use crate::manifest::CommandArgs;
 use crate::manifest::ComponentType;
+#[cfg(feature = "msgpack")]
+use crate::msgpack::MsgpackSink;
+use crate::output;
+use crate::output::Sorted;
+use crate::output::StatusWriter;
+use crate::output::StdoutWriter;
+use crate::progress;
+use crate::progress::ProgressEvent;
+use crate::progress::ProgressSender;
+#[cfg(feature = "sqlite")]
+use crate::sqlite::SqliteSink;
+use crate::summary::Summary;
+use crate::summary::Throughput;
+use crate::summary::csv_field;
 use base64::Engine;
+use futures_util::Stream;
+use futures_util::StreamExt;
+use futures_util::TryStreamExt;
+use futures_util::stream;
+use rand::SeedableRng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::rngs::StdRng;
+use regex::Regex;
+use serde::Deserialize;
 use serde::Serialize;
-
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use std::num::NonZeroU32;
+use std::num::NonZeroUsize;
+use std::process::ExitStatus;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
+use tokio::process::ChildStdout;
 use tokio::process::Command;
 use tracing::Instrument;
 
-#[derive(Debug, Serialize)]
-struct BenchmarkMeta {
-  task_index: usize,
+/// What a result row was produced by: the task, repetition and case.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkMeta {
+  pub task_index: usize,
 
-  executor: String,
+  pub executor: String,
 
   #[serde(rename = "args", skip_serializing_if = "Vec::is_empty")]
-  task_args: Vec<String>,
+  pub task_args: Vec<String>,
+
+  pub rep_index: usize,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub case_id: Option<String>,
+
+  /// Input size the generator was asked for (`--sizes`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub size: Option<u64>,
+
+  /// Generator seed the pipeline's input was produced with. `None` without a
+  /// generator, or when the generator seeds itself (`--no-seed`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub seed: Option<u64>,
+
+  #[serde(
+    skip_serializing_if = "serde_json::Map::is_empty",
+    serialize_with = "output::sorted_map"
+  )]
+  pub attributes: serde_json::Map<String, serde_json::Value>,
+
+  /// Checksum of the generator input the executor consumed (`--checksum-input`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub input_checksum: Option<String>,
+
+  /// Executor wall time from spawn to exit, measured by the orchestrator
+  /// (`--orchestrator-duration`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub orchestrator_duration_ms: Option<f64>,
+}
+
+/// A parsed result row held in memory for output formats that are not streamed
+/// line by line, or until the pipeline's orchestrator duration is known.
+#[derive(Debug, Clone, Serialize)]
+pub struct BufferedRow {
+  pub data_token: String,
+
+  #[serde(
+    skip_serializing_if = "Option::is_none",
+    serialize_with = "output::sorted_option"
+  )]
+  pub gen_meta: Option<serde_json::Value>,
+
+  #[serde(
+    skip_serializing_if = "Option::is_none",
+    serialize_with = "output::sorted_option"
+  )]
+  pub exec_meta: Option<serde_json::Value>,
+
+  /// The metric exactly as the executor reported it. `None` on a row that
+  /// reports a failed executor.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub metric: Option<serde_json::Number>,
+
+  /// Additional named metrics reported alongside `metric`.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub metrics: BTreeMap<String, f64>,
+
+  pub status: ResultStatus,
+}
+
+impl BufferedRow {
+  /// A row reporting that the executor ended with `status` rather than
+  /// successfully.
+  pub(crate) fn failed(status: ResultStatus) -> Self {
+    BufferedRow {
+      data_token: String::new(),
+      gen_meta: None,
+      exec_meta: None,
+      metric: None,
+      metrics: BTreeMap::new(),
+      status,
+    }
+  }
+}
+
+/// How the executor of a result row ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultStatus {
+  /// The row was parsed from the output of an executor that exited
+  /// successfully, or is still running.
+  #[default]
+  Ok,
+  /// The executor was killed by a signal, e.g. on a segmentation fault.
+  Crashed,
+  /// The executor was killed by the orchestrator for exceeding `--timeout` or
+  /// `--line-timeout`.
+  Timeout,
+  /// The executor exited with a non-zero code.
+  NonzeroExit,
+}
+
+impl ResultStatus {
+  /// Classifies an executor's exit. A `None` status means it timed out.
+  fn of_exit(status: Option<ExitStatus>) -> Self {
+    match status {
+      None => ResultStatus::Timeout,
+      Some(status) if exit_signal(&status).is_some() => ResultStatus::Crashed,
+      Some(status) if !status.success() => ResultStatus::NonzeroExit,
+      Some(_) => ResultStatus::Ok,
+    }
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ResultStatus::Ok => "ok",
+      ResultStatus::Crashed => "crashed",
+      ResultStatus::Timeout => "timeout",
+      ResultStatus::NonzeroExit => "nonzero_exit",
+    }
+  }
+}
+
+/// The buffered result rows of one pipeline, together with their shared metadata.
+#[derive(Debug)]
+pub(crate) struct BufferedRows {
+  pub(crate) meta: BenchmarkMeta,
+  pub(crate) rows: Vec<BufferedRow>,
+}
+
+/// Run-level information gathered while executing the benchmark plan.
+#[derive(Debug, Default, Serialize)]
+pub struct RunMetadata {
+  /// Contents of generator metadata sidecar files, keyed by the generator seed.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub generator_metadata: BTreeMap<u64, serde_json::Value>,
+}
+
+/// The run context written to `--meta-file`, for reproducing a run.
+#[derive(Debug, Serialize)]
+struct RunContext<'a> {
+  impalab_version: &'static str,
+  started_at: String,
+  finished_at: String,
+  host: HostInfo,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  generator: Option<GeneratorContext<'a>>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  generator_mix: Vec<GeneratorContext<'a>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  case_ids: Option<&'a [String]>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  sizes: Option<&'a [u64]>,
+  tasks: Vec<TaskContext<'a>>,
+  #[serde(flatten)]
+  run_metadata: &'a RunMetadata,
+}
+
+#[derive(Debug, Serialize)]
+struct GeneratorContext<'a> {
+  name: &'a str,
+  seed: u64,
+  #[serde(skip_serializing_if = "<[String]>::is_empty")]
+  args: &'a [String],
+  #[serde(skip_serializing_if = "Option::is_none")]
+  weight: Option<NonZeroU32>,
+}
+
+impl<'a> GeneratorContext<'a> {
+  fn new(generator: &'a ResolvedGenerator, weight: Option<NonZeroU32>) -> Self {
+    GeneratorContext {
+      name: &generator.name,
+      seed: generator.seed,
+      args: &generator.command_args.args,
+      weight,
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+struct TaskContext<'a> {
+  executor: &'a str,
+  #[serde(skip_serializing_if = "<[String]>::is_empty")]
+  args: &'a [String],
+  reps: usize,
+  #[serde(
+    skip_serializing_if = "serde_json::Map::is_empty",
+    serialize_with = "output::sorted_map"
+  )]
+  attributes: &'a serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct HostInfo {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  hostname: Option<String>,
+  os: &'static str,
+  arch: &'static str,
+  cpus: usize,
+}
+
+impl HostInfo {
+  fn collect() -> Self {
+    HostInfo {
+      hostname: hostname(),
+      os: std::env::consts::OS,
+      arch: std::env::consts::ARCH,
+      cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+  }
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+  let mut buf = [0u8; 256];
+  // SAFETY: the buffer is valid for `buf.len()` bytes.
+  let ret = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+  if ret != 0 {
+    return None;
+  }
+  let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+  String::from_utf8(buf[..len].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+  std::env::var("COMPUTERNAME").ok()
+}
+
+/// What a single pipeline produced beyond the result rows it printed.
+#[derive(Debug, Default)]
+struct PipelineOutput {
+  metrics: Vec<f64>,
+  /// Total items and time of the rows, for `--report-throughput`.
+  throughput: Throughput,
+  rows: Option<BufferedRows>,
+  /// Number of result rows the executor emitted.
+  row_count: usize,
+  generator_metadata: Option<(u64, serde_json::Value)>,
+  /// Generator output buffered for replay to later repetitions.
+  captured_input: Option<Arc<Vec<u8>>>,
+  /// Checksum of the generator input, when the input is checksummed.
+  input_checksum: Option<String>,
+}
+
+/// A pipeline that failed, along with the rows it still reports.
+#[derive(Debug)]
+struct PipelineFailure {
+  error: BenchmarkError,
+  /// The rows printed before the failure and the row reporting it, when they
+  /// are left to the caller to write (see `PipelineOutput::rows`).
+  rows: Option<BufferedRows>,
+}
+
+impl From<BenchmarkError> for PipelineFailure {
+  fn from(error: BenchmarkError) -> Self {
+    Self { error, rows: None }
+  }
+}
+
+/// Identifies the generator output replayed to later repetitions: by task, or by
+/// `None` when all tasks share it (`--cache-generator`), then by case id, size
+/// and seed index.
+type CacheKey<'a> = (Option<usize>, Option<&'a str>, Option<u64>, usize);
 
+/// A pipeline of the current repetition, prepared to run.
+struct PendingPipeline<'a> {
+  task: (usize, Cow<'a, ResolvedTask>),
   rep_index: usize,
-  #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
-  attributes: serde_json::Map<String, serde_json::Value>,
+  case_id: Option<&'a str>,
+  size: Option<u64>,
+  case_generator: Option<ResolvedGenerator>,
+  cached_input: CachedInput,
+  cache_key: CacheKey<'a>,
+  work_item: WorkItem,
+  exec_span: tracing::Span,
+}
+
+/// What the run accumulates from the outcomes of its pipelines.
+#[derive(Default)]
+struct RunTotals {
+  summary: Summary,
+  run_metadata: RunMetadata,
+  /// Number of result rows the executors emitted.
+  rows: usize,
+  input_digests: InputDigests,
+}
+
+/// `(executor, checksum)` of every pipeline's input, by generator seed and size
+/// (`--verify-input-identical`).
+type InputDigests = BTreeMap<(u64, Option<u64>), Vec<(String, String)>>;
+
+/// The state shared by every batch of pipelines of a run.
+#[derive(Clone, Copy)]
+struct BatchContext<'a> {
+  options: &'a RunOptions,
+  stdout: &'a StdoutWriter,
+  status_writer: Option<&'a StatusWriter>,
+  row_sink: &'a Mutex<Option<Box<dyn RowSink>>>,
+  input_cache: &'a Mutex<HashMap<CacheKey<'a>, Arc<Vec<u8>>>>,
+  rep_concurrency: usize,
+}
+
+/// How a pipeline delivers its result rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowDelivery {
+  /// Written by the pipeline itself, as they are printed or once it exits.
+  /// Rows bound for an `--output-format` file are returned to the caller.
+  Write,
+  /// Returned to the caller when the pipeline succeeds, and dropped when it
+  /// fails with an error that will be retried.
+  Hold,
+  /// Returned to the caller, which writes them in order (`--ordered-per-key`).
+  Return,
+}
+
+/// Where one pipeline reads its replayed input from and writes its output to.
+struct PipelineContext<'a> {
+  cached_input: &'a CachedInput,
+  /// Collects the executor's last stderr lines, for `--retry-on`.
+  stderr_tail: &'a StderrTail,
+  stdout: &'a StdoutWriter,
+  status_writer: Option<&'a StatusWriter>,
+  delivery: RowDelivery,
+}
+
+/// Holds back the rows of one task and case id until those of all its earlier
+/// repetitions have been written (`--ordered-per-key`).
+#[derive(Default)]
+struct RepOrder {
+  /// Number of pipelines not yet finished, per repetition.
+  running: BTreeMap<usize, usize>,
+  held: BTreeMap<usize, Vec<BufferedRows>>,
+}
+
+impl RepOrder {
+  fn expect(&mut self, rep_index: usize) {
+    *self.running.entry(rep_index).or_default() += 1;
+  }
+
+  /// Records a finished pipeline of `rep_index` and returns the rows that can
+  /// now be written, in `rep_index` order.
+  fn finish(&mut self, rep_index: usize, rows: Option<BufferedRows>) -> Vec<BufferedRows> {
+    if let Some(rows) = rows {
+      self.held.entry(rep_index).or_default().push(rows);
+    }
+    if let Some(running) = self.running.get_mut(&rep_index) {
+      *running -= 1;
+    }
+    let mut ready = Vec::new();
+    while let Some(entry) = self.running.first_entry() {
+      if *entry.get() > 0 {
+        break;
+      }
+      let rep_index = entry.remove_entry().0;
+      ready.extend(self.held.remove(&rep_index).unwrap_or_default());
+    }
+    ready
+  }
+}
+
+/// Prefix of executor output lines that report progress, e.g. `#progress=0.5`.
+const PROGRESS_PREFIX: &str = "#progress=";
+
+/// Prefix of executor output lines that set the item count of the rows that
+/// follow, e.g. `#count=1000`.
+const COUNT_PREFIX: &str = "#count=";
+
+/// Name of the metric `--report-throughput` adds to each result row.
+pub const ITEMS_PER_SEC: &str = "items_per_sec";
+
+/// Maximum length of an executor output line unless `--max-line-length` is given.
+pub const DEFAULT_MAX_LINE_LENGTH: u64 = 16 << 20;
+
+/// Controls how executor result rows are post-processed.
+#[derive(Debug, Clone)]
+struct OutputOptions {
+  collect_metrics: bool,
+  /// Whether result rows are written out at all (see `--only-summary`).
+  emit_rows: bool,
+  buffer_rows: bool,
+  min_metric: Option<f64>,
+  report_throughput: bool,
+  item_count: Option<u64>,
+  exec_output_format: ExecOutputFormat,
+  /// Encoding of rows written to stdout.
+  output_format: OutputFormat,
+  max_line_length: u64,
+  /// Maximum wait for each line; `stalled` is notified when it elapses.
+  line_timeout: Option<Duration>,
+  stalled: Arc<tokio::sync::Notify>,
+  progress: Option<ProgressSender>,
+  /// Receives rows in place of stdout (see `RunOptions::results`).
+  results: Option<ResultSender>,
+  stdout: StdoutWriter,
+}
+
+/// Runs the benchmark like [`run_benchmarks`], but returns the result rows
+/// instead of printing them, in the order `impa run` would print them.
+///
+/// Every row is buffered in memory until the run ends, so prefer
+/// [`run_benchmarks_stream`] for very long runs. Rows are not printed, whatever
+/// the `output_format`; summaries still are.
+pub async fn run_benchmarks_collect(
+  config: ResolvedConfig,
+) -> Result<Vec<BenchmarkResult>, BenchmarkError> {
+  run_benchmarks_collect_with(config, |_| true).await
+}
+
+/// Runs the benchmark like [`run_benchmarks_collect`], passing each result row
+/// through `hook` as it arrives. The hook may modify the row, e.g. to add
+/// derived metrics, and keeps it only by returning `true`.
+pub async fn run_benchmarks_collect_with(
+  config: ResolvedConfig,
+  mut hook: impl FnMut(&mut BenchmarkResult) -> bool,
+) -> Result<Vec<BenchmarkResult>, BenchmarkError> {
+  run_benchmarks_stream(config)
+    .try_filter_map(|mut result| std::future::ready(Ok(hook(&mut result).then_some(result))))
+    .try_collect()
+    .await
+}
+
+/// Runs the benchmark like [`run_benchmarks`], yielding each result row as
+/// soon as its executor prints it. The run only progresses while the stream is
+/// polled.
+///
+/// Rows arrive in the order `impa run` would print them, so with `--jobs` the
+/// rows of concurrent pipelines interleave. A failed run yields its error last.
+/// Dropping the stream cancels the run and kills its running processes.
+pub fn run_benchmarks_stream(
+  mut config: ResolvedConfig,
+) -> impl Stream<Item = Result<BenchmarkResult, BenchmarkError>> {
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  config.options.results = Some(sender);
+  // Keeps a JSON array or CSV header off stdout.
+  config.options.output_format = OutputFormat::Jsonl;
+  let run = Box::pin(run_benchmarks(config));
+
+  stream::unfold(Some((receiver, Some(run), None)), |state| async move {
+    let (mut receiver, mut run, mut error) = state?;
+    loop {
+      match run.as_mut() {
+        Some(pending) => tokio::select! {
+          biased;
+          Some(result) = receiver.recv() => {
+            return Some((Ok(result), Some((receiver, run, error))));
+          }
+          outcome = pending => {
+            run = None;
+            error = outcome.err();
+          }
+        },
+        // Rows sent just before the run ended are still yielded.
+        None => {
+          return match receiver.try_recv() {
+            Ok(result) => Some((Ok(result), Some((receiver, None, error)))),
+            Err(_) => error.map(|e| (Err(e), None)),
+          };
+        }
+      }
+    }
+  })
 }
 
 /// Main benchmark runner.
@@ -46,12 +553,34 @@ struct BenchmarkMeta {
 /// Takes a fully resolved `Config` and executes the benchmark plan.
 /// It handles spawning the generator (if any) and all executor processes (tasks),
 /// piping data, and logging results.
+///
+/// Returns the [`RunMetadata`] collected over the whole run.
 pub async fn run_benchmarks(
   ResolvedConfig {
     generator: gen_cmd_args,
+    generator_mix,
     tasks,
+    options,
   }: ResolvedConfig,
-) -> Result<(), BenchmarkError> {
+) -> Result<RunMetadata, BenchmarkError> {
+  // Members of a generator mix share one seed.
+  let seed = gen_cmd_args
+    .as_ref()
+    .or(generator_mix.first().map(|(g, _)| g))
+    .map(|g| g.seed);
+  if options.echo_seed
+    && let Some(seed) = seed
+  {
+    eprintln!("IMPA_SEED={}", seed);
+  }
+  let seed_count = options.seed_count.map_or(1, NonZeroUsize::get);
+  if options.seed_count.is_some()
+    && let Some(seed) = seed
+  {
+    let seeds: Vec<u64> = (0..seed_count).map(|i| sub_seed(seed, i)).collect();
+    tracing::info!(?seeds, "Running every pipeline with each generator seed");
+  }
+
   let gen_info = if let Some(ResolvedGenerator {
     seed,
     command_args: gen_cmd,
@@ -65,72 +594,714 @@ pub async fn run_benchmarks(
       gen_cmd.command.display(),
       gen_cmd.args
     )
+  } else if !generator_mix.is_empty() {
+    format!(
+      "generator mix = {:?}",
+      generator_mix
+        .iter()
+        .map(|(g, weight)| format!("{}:{}", g.name, weight))
+        .collect::<Vec<_>>()
+    )
   } else {
     "generator = none".to_string()
   };
+  // The generator of the repetition (or warmup) with the given index.
+  let generator_for = |index: usize| {
+    if generator_mix.is_empty() {
+      gen_cmd_args.clone()
+    } else {
+      pick_generator(&generator_mix, index).cloned()
+    }
+  };
 
   let max_reps = tasks.iter().map(|t| t.effective_reps).max().unwrap_or(1);
+  // Repetitions that replay the first one's generator output cannot overlap it.
+  let shares_input = gen_cmd_args.is_some()
+    && !options.regenerate_per_rep
+    && options.input_files.is_none()
+    && (max_reps > 1 || options.cache_generator);
+  let rep_concurrency = match options.rep_concurrency {
+    Some(_) if shares_input => {
+      tracing::warn!(
+        "--rep-concurrency has no effect when repetitions share generator output. Ignoring"
+      );
+      1
+    }
+    Some(n) => n.get(),
+    None => 1,
+  };
 
   let span = tracing::info_span!(
     "run_benchmarks",
     %gen_info
   );
 
-  async {
+  let started_at = SystemTime::now();
+  let total_timeout = options.total_timeout;
+  let stdout = StdoutWriter::new(
+    options.output_format,
+    options.line_ending,
+    options.emit_rate,
+  );
+  // Shared with the SIGTERM handling below, which finishes the sink when the
+  // run is cut short.
+  let row_sink: Mutex<Option<Box<dyn RowSink>>> = Mutex::new(None);
+  let run = async {
     tracing::info!("--- Starting Benchmark Pipeline ---");
-    for rep_index in 0..max_reps {
+    let mut totals = RunTotals::default();
+    let case_ids: Vec<Option<&str>> = match (&options.seed_ids, &options.input_files) {
+      (Some(ids), _) | (None, Some(ids)) => ids.iter().map(|id| Some(id.as_str())).collect(),
+      (None, None) => vec![None],
+    };
+    // Every case is run once per `--sizes` entry.
+    let sizes: Vec<Option<u64>> = match &options.sizes {
+      Some(sizes) => sizes.iter().copied().map(Some).collect(),
+      None => vec![None],
+    };
+    let cases: Vec<(Option<&str>, Option<u64>)> = case_ids
+      .iter()
+      .flat_map(|&case_id| sizes.iter().map(move |&size| (case_id, size)))
+      .collect();
+    // `--input-files -` reads the data piped to `impa` once, for every pipeline.
+    let stdin_input = match &options.input_files {
+      Some(files) if files.iter().any(|f| f == "-") => {
+        let mut buf = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut buf).map_err(|e| BenchmarkError::ReadInputFile {
+            path: PathBuf::from("-"),
+            source: e,
+          })?;
+        Some(Arc::new(buf))
+      }
+      _ => None,
+    };
+    // With `--input-files`, each case id is the path of the file fed to the executor.
+    let file_input = |case_id: Option<&str>| match (&options.input_files, case_id, &stdin_input) {
+      (Some(_), Some("-"), Some(buf)) => Ok(CachedInput::Replay(buf.clone())),
+      (Some(_), Some(path), _) => std::fs::read(path)
+        .map(|buf| CachedInput::Replay(Arc::new(buf)))
+        .map_err(|e| BenchmarkError::ReadInputFile {
+          path: PathBuf::from(path),
+          source: e,
+        }),
+      _ => Ok(CachedInput::Disabled),
+    };
+    let input_cache: Mutex<HashMap<CacheKey, Arc<Vec<u8>>>> = Mutex::default();
+    let mut checkpoint = options
+      .resume
+      .as_deref()
+      .map(Checkpoint::open)
+      .transpose()?;
+    *row_sink.lock().unwrap() = open_row_sink(&options)?;
+    let status_writer = options
+      .emit_process_status
+      .then(|| StatusWriter::open(options.process_status_file.as_deref()))
+      .transpose()?;
+    let batch = BatchContext {
+      options: &options,
+      stdout: &stdout,
+      status_writer: status_writer.as_ref(),
+      row_sink: &row_sink,
+      input_cache: &input_cache,
+      rep_concurrency,
+    };
+
+    progress::emit(
+      options.progress.as_ref(),
+      ProgressEvent::RunStarted {
+        tasks: tasks
+          .iter()
+          .map(|t| {
+            std::iter::once(t.executor.as_str())
+              .chain(t.args.iter().map(String::as_str))
+              .collect::<Vec<_>>()
+              .join(" ")
+          })
+          .collect(),
+        pipelines: tasks
+          .iter()
+          .map(|t| t.effective_reps * cases.len() * seed_count)
+          .collect(),
+      },
+    );
+
+    // Setup commands run once per executor, before its warmup and measured pipelines.
+    let mut set_up = HashSet::new();
+    for task in &tasks {
+      if let Some(setup) = &task.setup
+        && set_up.insert(task.executor.as_str())
+      {
+        run_hook(Hook::Setup, &task.executor, setup, &task.run_env, &options).await?;
+      }
+    }
+
+    if options.warmup > 0 {
+      let discard = StdoutWriter::discard();
       for task in tasks.iter().enumerate() {
-        let reps = task.1.effective_reps;
-        if rep_index >= reps {
-          continue;
+        for &(case_id, size) in &cases {
+          for warmup_index in 0..options.warmup {
+            let generator = generator_for(warmup_index).map(|g| ResolvedGenerator {
+              seed: match options.warmup_seed {
+                Some(WarmupSeed::Fixed(seed)) => seed,
+                Some(WarmupSeed::Random) => rand::random(),
+                None => case_id.map_or(g.seed, seed_from_id),
+              },
+              ..g
+            });
+            let exec_span =
+              tracing::info_span!("warmup", executor = %task.1.executor, case_id, size, warmup_index);
+            tracing::info!(
+              parent: &exec_span,
+              seed = ?generator.as_ref().map(|g| g.seed),
+              "Running warmup pipeline"
+            );
+            let result = run_pipeline(
+              generator.as_ref(),
+              task,
+              warmup_index,
+              case_id,
+              size,
+              &options,
+              PipelineContext {
+                cached_input: &file_input(case_id)?,
+                stderr_tail: &StderrTail::default(),
+                stdout: &discard,
+                status_writer: None,
+                delivery: RowDelivery::Write,
+              },
+            )
+            .instrument(exec_span.clone())
+            .await
+            .map_err(|failure| failure.error);
+            // Warmup output is still validated, but as it is never measured, a
+            // malformed line only warrants a warning.
+            match result {
+              Err(
+                e @ (BenchmarkError::MalformedExecOutput { .. }
+                | BenchmarkError::TruncatedFinalLine { .. }),
+              ) => {
+                tracing::warn!(parent: &exec_span, error = %e, "Malformed output from warmup pipeline");
+              }
+              result => {
+                result?;
+              }
+            }
+          }
         }
+      }
+    }
 
-        let executor = task.1.executor.clone();
-        let exec_span = tracing::info_span!("run_executor", executor = %executor);
-
-        let result = async {
-          tracing::info!(
-            "Running natively for: {} (rep_index={} out of {} reps)...",
-            executor,
-            rep_index,
-            reps
-          );
+    for first_rep in (0..max_reps).step_by(rep_concurrency) {
+      let mut pending = Vec::new();
+      for rep_index in first_rep..max_reps.min(first_rep + rep_concurrency) {
+        for task in tasks.iter().enumerate() {
+          let reps = task.1.effective_reps;
+          if rep_index >= reps {
+            continue;
+          }
 
-          match run_pipeline(gen_cmd_args.as_ref(), task, rep_index).await {
-            Ok(_) => {
+          let seeded_cases = cases.iter().flat_map(|&(case_id, size)| {
+            (0..seed_count).map(move |seed_index| (case_id, size, seed_index))
+          });
+          for (case_id, size, seed_index) in seeded_cases {
+            let executor = task.1.executor.clone();
+            let exec_span = tracing::info_span!("run_executor", executor = %executor, case_id, size);
+
+            // In per-id mode, each case gets its own generator seed derived from the
+            // id. With `--seed-count`, it is derived from the run's seed instead.
+            let case_generator = generator_for(rep_index).map(|g| match case_id {
+              Some(id) => ResolvedGenerator {
+                seed: seed_from_id(id),
+                ..g
+              },
+              None => ResolvedGenerator {
+                seed: sub_seed(g.seed, seed_index),
+                ..g
+              },
+            });
+            // Fresh generators per repetition advance the seed so each repetition
+            // sees different input.
+            let case_generator = case_generator.map(|g| {
+              if options.regenerate_per_rep {
+                ResolvedGenerator {
+                  seed: g.seed.wrapping_add(rep_index as u64),
+                  ..g
+                }
+              } else {
+                g
+              }
+            });
+
+            // Otherwise the first repetition's generator output is replayed to the
+            // rest, and with `--cache-generator` to every other task as well.
+            let cache_key = (
+              (!options.cache_generator).then_some(task.0),
+              case_id,
+              size,
+              seed_index,
+            );
+            let cached_input = if options.input_files.is_some() {
+              file_input(case_id)?
+            } else if case_generator.is_none()
+              || options.regenerate_per_rep
+              || !generator_mix.is_empty()
+              || (reps < 2 && !options.cache_generator)
+            {
+              CachedInput::Disabled
+            } else {
+              // Resolved to a replay when the pipeline starts, if the output has
+              // been captured by then.
+              CachedInput::Capture
+            };
+
+            let work_item = WorkItem {
+              executor: executor.clone(),
+              args: task.1.args.clone(),
+              case_id: case_id.map(str::to_owned),
+              size,
+              seed: case_generator.as_ref().map(|g| g.seed),
+              rep_index,
+            };
+            if checkpoint
+              .as_ref()
+              .is_some_and(|c| c.is_completed(&work_item))
+            {
               tracing::info!(
-                "Finished running pipeline: {} (rep_index {})",
+                parent: &exec_span,
+                "Skipping pipeline already recorded in checkpoint: {} (rep_index {})",
                 executor,
                 rep_index
               );
-              Ok(())
-            }
-            Err(e) => {
-              tracing::error!(
-                error = %e,
-                "Pipeline failed for executor: {} (rep_index {})",
-                executor,
-                rep_index
+              progress::emit(
+                options.progress.as_ref(),
+                ProgressEvent::PipelineFinished {
+                  task_index: task.0,
+                  rep_index,
+                },
               );
-              Err(e)
+              continue;
             }
+
+            // A mixed run records which generator each pipeline used.
+            let task = match &case_generator {
+              Some(generator) if !generator_mix.is_empty() => {
+                let mut attributes = task.1.effective_attributes.clone();
+                attributes.insert(
+                  "generator".to_string(),
+                  serde_json::Value::String(generator.name.clone()),
+                );
+                (
+                  task.0,
+                  Cow::Owned(ResolvedTask {
+                    effective_attributes: attributes,
+                    ..task.1.clone()
+                  }),
+                )
+              }
+              _ => (task.0, Cow::Borrowed(task.1)),
+            };
+
+            pending.push(PendingPipeline {
+              task,
+              rep_index,
+              case_id,
+              size,
+              case_generator,
+              cached_input,
+              cache_key,
+              work_item,
+              exec_span,
+            });
           }
         }
-        .instrument(exec_span)
-        .await;
+      }
+
+      run_batch(batch, pending, checkpoint.as_mut(), &mut totals).await?;
+    }
+    let RunTotals {
+      summary,
+      run_metadata,
+      rows: total_rows,
+      input_digests,
+    } = totals;
+
+    let sink = row_sink.lock().unwrap().take();
+    if let Some(sink) = sink {
+      sink.finish()?;
+    }
+
+    if options.only_summary {
+      for record in summary.records(&tasks) {
+        stdout
+          .write(&serde_json::to_string(&record).map_err(BenchmarkError::SerializeResult)?)
+          .await;
+      }
+    }
 
-        result?
+    for ((seed, _), entries) in input_digests {
+      if entries.iter().any(|(_, digest)| *digest != entries[0].1) {
+        let (executors, digests) = entries.into_iter().unzip();
+        tracing::error!(seed, "Executors received different generator input");
+        return Err(BenchmarkError::InputMismatch {
+          seed,
+          executors,
+          digests,
+        });
       }
     }
+
+    if options.fail_if_empty && total_rows == 0 {
+      tracing::error!("No result rows were produced");
+      return Err(BenchmarkError::NoResults);
+    }
+
+    if let Some(path) = &options.summary_csv {
+      std::fs::write(path, summary.to_csv(&tasks, options.line_ending)).map_err(|e| {
+        BenchmarkError::WriteSummary {
+          path: path.clone(),
+          source: e,
+        }
+      })?;
+      tracing::info!("Summary written to {}", path.display());
+    }
+    if let Some(path) = &options.summary_json {
+      let records = summary.records(&tasks).collect::<Vec<_>>();
+      let json = serde_json::to_string_pretty(&records).map_err(BenchmarkError::SerializeResult)?;
+      std::fs::write(path, json + "\n").map_err(|e| {
+        BenchmarkError::WriteSummary {
+          path: path.clone(),
+          source: e,
+        }
+      })?;
+      tracing::info!("Summary written to {}", path.display());
+    }
+    if options.print_summary {
+      eprint!("{}", summary.to_table(&tasks));
+    }
+
+    if let Some(path) = &options.meta_file {
+      let context = RunContext {
+        impalab_version: env!("CARGO_PKG_VERSION"),
+        started_at: humantime::format_rfc3339_millis(started_at).to_string(),
+        finished_at: humantime::format_rfc3339_millis(SystemTime::now()).to_string(),
+        host: HostInfo::collect(),
+        generator: gen_cmd_args
+          .as_ref()
+          .map(|g| GeneratorContext::new(g, None)),
+        generator_mix: generator_mix
+          .iter()
+          .map(|(g, weight)| GeneratorContext::new(g, Some(*weight)))
+          .collect(),
+        case_ids: options.seed_ids.as_deref().or(options.input_files.as_deref()),
+        sizes: options.sizes.as_deref(),
+        tasks: tasks
+          .iter()
+          .map(|t| TaskContext {
+            executor: &t.executor,
+            args: &t.args,
+            reps: t.effective_reps,
+            attributes: &t.effective_attributes,
+          })
+          .collect(),
+        run_metadata: &run_metadata,
+      };
+      let json = serde_json::to_string_pretty(&context).map_err(BenchmarkError::SerializeResult)?;
+      std::fs::write(path, json + "\n").map_err(|e| BenchmarkError::WriteMetaFile {
+        path: path.clone(),
+        source: e,
+      })?;
+      tracing::info!("Run context written to {}", path.display());
+    }
+
+    if !run_metadata.generator_metadata.is_empty() {
+      tracing::info!(
+        metadata = %serde_json::to_string(&run_metadata).map_err(BenchmarkError::SerializeResult)?,
+        "Run metadata"
+      );
+    }
+
     tracing::info!("--- Benchmark run complete ---");
+    Ok(run_metadata)
+  }
+  .instrument(span);
+
+  let run = async {
+    match total_timeout {
+      // Dropping the run future kills any in-flight children (`kill_on_drop`).
+      Some(limit) => tokio::time::timeout(limit, run)
+        .await
+        .map_err(|_| BenchmarkError::TotalTimeout { timeout: limit })
+        .and_then(|r| r),
+      None => run.await,
+    }
+  };
+  let result = if options.output_flush_on_signal {
+    // As with the total timeout, the interrupted run future is dropped along
+    // with its children; only the rows it already handed over are kept.
+    tokio::select! {
+      result = run => result,
+      signal = sigterm() => match signal {
+        Ok(()) => {
+          tracing::warn!("Received SIGTERM. Flushing the results collected so far");
+          let sink = row_sink.lock().unwrap().take();
+          sink
+            .map_or(Ok(()), |sink| sink.finish())
+            .and(Err(BenchmarkError::Terminated))
+        }
+        Err(e) => Err(e),
+      },
+    }
+  } else {
+    run.await
+  };
+  // Teardowns run even when the run failed; a run error takes precedence over a
+  // (strict) teardown error.
+  let teardown = run_teardowns(&tasks, &options).await;
+  let result = result.and_then(|metadata| teardown.map(|_| metadata));
+  // Close a streamed JSON array even when the run failed, so the rows written
+  // so far remain parseable.
+  stdout.finish();
+  result
+}
+
+/// Runs a batch of pipelines, of one or (with `--rep-concurrency`) several
+/// repetitions, and records their outcomes.
+async fn run_batch<'a>(
+  BatchContext {
+    options,
+    stdout,
+    status_writer,
+    row_sink,
+    input_cache,
+    rep_concurrency,
+  }: BatchContext<'a>,
+  pending: Vec<PendingPipeline<'a>>,
+  mut checkpoint: Option<&mut Checkpoint>,
+  totals: &mut RunTotals,
+) -> Result<(), BenchmarkError> {
+  // Rows of concurrent repetitions come back to be written in `rep_index`
+  // order, per task and case id.
+  let mut rep_order = (options.ordered_per_key && rep_concurrency > 1).then(|| {
+    let mut order = HashMap::<_, RepOrder>::new();
+    for pipeline in &pending {
+      order
+        .entry((pipeline.task.0, pipeline.case_id))
+        .or_default()
+        .expect(pipeline.rep_index);
+    }
+    order
+  });
+  let return_rows = rep_order.is_some();
+
+  // Up to `--jobs` pipelines of each repetition of the batch run at once.
+  // Once one fails, no new pipelines start, but those already running finish
+  // and keep their results.
+  let failed = AtomicBool::new(false);
+  let mut outcomes = stream::iter(pending)
+    .map(|pipeline| {
+      let span = pipeline.exec_span.clone();
+      let failed = &failed;
+      async move {
+        let rep_index = pipeline.rep_index;
+        if failed.load(Ordering::Relaxed) {
+          return (pipeline, None);
+        }
+        let cached_input = match &pipeline.cached_input {
+          CachedInput::Capture => match input_cache.lock().unwrap().get(&pipeline.cache_key) {
+            Some(buf) => CachedInput::Replay(Arc::clone(buf)),
+            None => CachedInput::Capture,
+          },
+          other => other.clone(),
+        };
+        let (task_index, task) = (pipeline.task.0, &*pipeline.task.1);
+        tracing::info!(
+          "Running natively for: {} (rep_index={} out of {} reps)...",
+          task.executor,
+          rep_index,
+          task.effective_reps
+        );
+
+        progress::emit(
+          options.progress.as_ref(),
+          ProgressEvent::PipelineStarted {
+            task_index,
+            rep_index,
+          },
+        );
+
+        let mut attempt = 0;
+        let outcome = loop {
+          let stderr_tail = StderrTail::default();
+          let context = PipelineContext {
+            cached_input: &cached_input,
+            stderr_tail: &stderr_tail,
+            stdout,
+            status_writer,
+            // Rows of an attempt that may still be retried are only written
+            // once it succeeds, so that no repetition is reported twice.
+            delivery: if attempt < options.retries {
+              RowDelivery::Hold
+            } else if return_rows {
+              RowDelivery::Return
+            } else {
+              RowDelivery::Write
+            },
+          };
+          match run_pipeline(
+            pipeline.case_generator.as_ref(),
+            (task_index, task),
+            rep_index,
+            pipeline.case_id,
+            pipeline.size,
+            options,
+            context,
+          )
+          .await
+          {
+            Err(failure) if attempt < options.retries && is_retryable(options, &stderr_tail) => {
+              attempt += 1;
+              tracing::warn!(
+                error = %failure.error,
+                attempt,
+                retries = options.retries,
+                "Pipeline failed with a retryable error. Retrying"
+              );
+            }
+            outcome => break outcome,
+          }
+        };
+        if let Err(failure) = &outcome {
+          failed.store(true, Ordering::Relaxed);
+          tracing::error!(
+            error = %failure.error,
+            "Pipeline failed for executor: {} (rep_index {})",
+            task.executor,
+            rep_index
+          );
+        }
+        (pipeline, Some(outcome))
+      }
+      .instrument(span)
+    })
+    .buffer_unordered(options.jobs.map_or(1, NonZeroUsize::get) * rep_concurrency);
+
+  let mut first_error = None;
+  while let Some((pipeline, outcome)) = outcomes.next().await {
+    let _entered = pipeline.exec_span.enter();
+    let (task_index, rep_index) = (pipeline.task.0, pipeline.rep_index);
+    // A failed pipeline's rows are written like those of a successful one,
+    // and a pipeline without rows no longer holds back later repetitions.
+    let (rows, outcome) = match outcome {
+      None => (None, None),
+      Some(Ok(mut output)) => (output.rows.take(), Some(Ok(output))),
+      Some(Err(PipelineFailure { error, rows })) => (rows, Some(Err(error))),
+    };
+    let rows = match rep_order.as_mut() {
+      Some(order) => order
+        .get_mut(&(task_index, pipeline.case_id))
+        .unwrap()
+        .finish(rep_index, rows),
+      None => rows.into_iter().collect(),
+    };
+    for rows in rows {
+      write_pipeline_rows(options, stdout, row_sink, rows).await?;
+    }
+    match outcome {
+      // Not started because another pipeline failed.
+      None => {}
+      Some(Ok(PipelineOutput {
+        metrics,
+        throughput,
+        rows: _,
+        row_count,
+        generator_metadata,
+        captured_input,
+        input_checksum,
+      })) => {
+        totals.rows += row_count;
+        if let (true, Some(seed), Some(digest)) = (
+          options.verify_input_identical,
+          pipeline.work_item.seed,
+          input_checksum,
+        ) {
+          totals
+            .input_digests
+            .entry((seed, pipeline.size))
+            .or_default()
+            .push((pipeline.task.1.executor.clone(), digest));
+        }
+        if let Some(buf) = captured_input {
+          if let (Some(dir), Some(seed)) = (&options.cache_generator_path, pipeline.work_item.seed)
+          {
+            write_cached_input(dir, seed, pipeline.size, &buf)?;
+          }
+          input_cache.lock().unwrap().insert(pipeline.cache_key, buf);
+        }
+        progress::emit(
+          options.progress.as_ref(),
+          ProgressEvent::PipelineFinished {
+            task_index,
+            rep_index,
+          },
+        );
+        tracing::info!(
+          "Finished running pipeline: {} (rep_index {})",
+          pipeline.task.1.executor,
+          rep_index
+        );
+        totals.summary.record(task_index, metrics);
+        if options.report_throughput {
+          totals.summary.record_throughput(task_index, throughput);
+        }
+        if let Some((seed, contents)) = generator_metadata {
+          totals
+            .run_metadata
+            .generator_metadata
+            .insert(seed, contents);
+        }
+        if let Some(checkpoint) = checkpoint.as_deref_mut() {
+          checkpoint.record(pipeline.work_item)?;
+        }
+      }
+      Some(Err(e)) => {
+        first_error.get_or_insert(e);
+      }
+    }
+  }
+  if let Some(e) = first_error {
+    // Like an interrupted run, a failed one still completes the file-based
+    // output with the rows written so far.
+    let sink = row_sink.lock().unwrap().take();
+    if let Some(sink) = sink {
+      sink.finish()?;
+    }
+    return Err(e);
+  }
+  Ok(())
+}
+
+/// Writes the rows returned by a pipeline to the `--output-format` file, if
+/// any, or else like [`write_buffered_rows`].
+async fn write_pipeline_rows(
+  options: &RunOptions,
+  stdout: &StdoutWriter,
+  row_sink: &Mutex<Option<Box<dyn RowSink>>>,
+  rows: BufferedRows,
+) -> Result<(), BenchmarkError> {
+  if options.output_format.writes_to_file() {
+    if let Some(sink) = row_sink.lock().unwrap().as_mut() {
+      sink.write(&rows)?;
+    }
     Ok(())
+  } else {
+    write_buffered_rows(options, stdout, rows).await
   }
-  .instrument(span)
-  .await
 }
 
 /// Spawns and manages the generator -> executor pipeline for one language.
 /// Handles both pipelined and self-contained (no generator) runs.
+///
+/// Returns the metrics emitted by the executor when a summary is requested, along
+/// with the generator's metadata sidecar contents when it declares one.
 async fn run_pipeline(
   generator_cfg: Option<&ResolvedGenerator>,
   (
@@ -139,19 +1310,123 @@ async fn run_pipeline(
       executor: executor_name,
       args: task_args,
       command_args,
+      args_delivery,
+      config_template,
+      run_env,
       effective_attributes,
       effective_reps,
+      setup: _,
+      teardown: _,
+      stdin_prelude,
     },
   ): (usize, &ResolvedTask),
   rep_index: usize,
-) -> Result<(), BenchmarkError> {
+  case_id: Option<&str>,
+  size: Option<u64>,
+  options: &RunOptions,
+  PipelineContext {
+    cached_input,
+    stderr_tail,
+    stdout,
+    status_writer,
+    delivery,
+  }: PipelineContext<'_>,
+) -> Result<PipelineOutput, PipelineFailure> {
+  let env_passthrough = options.env_passthrough.as_deref();
   let mut gen_child_handle: Option<Child> = None;
   let mut gen_stderr_handle: Option<tokio::task::JoinHandle<Result<(), BenchmarkError>>> = None;
+  // Set when the generator reports a failed allocation on stderr.
+  let gen_allocation_failed = Arc::new(AtomicBool::new(false));
+  let mut gen_metadata_file: Option<(u64, PathBuf)> = None;
+  // Input that must be forwarded by the orchestrator instead of being handed
+  // directly to the executor (see `ArgsDelivery::StdinHeader`,
+  // `RunOptions::batch_records` and `CachedInput`).
+  let mut stdin_source: Option<StdinSource> = None;
+  let replay = match cached_input {
+    CachedInput::Replay(buf) => Some(buf.clone()),
+    _ => None,
+  };
+  let capture = matches!(cached_input, CachedInput::Capture) && generator_cfg.is_some();
+  let builtin = generator_cfg.and_then(|g| g.builtin.as_ref().map(|b| (g.seed, b)));
+  // Checksumming needs the complete input before the executor starts, so the
+  // generator stream is buffered by the orchestrator.
+  let checksum_input = (options.checksum_input || options.verify_input_identical)
+    && (generator_cfg.is_some() || replay.is_some());
+  let mut input_checksum = None;
+  // Validating generator output against a schema buffers it the same way.
+  #[cfg(feature = "jsonschema")]
+  let validate_input = options.validate_input_schema.is_some() && generator_cfg.is_some();
+  #[cfg(not(feature = "jsonschema"))]
+  let validate_input = false;
+
+  let stdin_header = match args_delivery {
+    ArgsDelivery::Argv => None,
+    ArgsDelivery::StdinHeader => {
+      Some(serde_json::to_string(task_args).map_err(BenchmarkError::SerializeResult)?)
+    }
+  };
+  let batch_records = options
+    .batch_records
+    .filter(|_| generator_cfg.is_some() || replay.is_some());
+  let relay_stdin = stdin_header.is_some()
+    || stdin_prelude.is_some()
+    || batch_records.is_some()
+    || replay.is_some()
+    || capture
+    || builtin.is_some()
+    || checksum_input
+    || validate_input;
+
+  // --- Render per-run config file (if declared) ---
+  // The file is removed when `config_file` is dropped at the end of the pipeline.
+  let config_file = match config_template {
+    Some(template) => {
+      let template_path = match &command_args.working_dir {
+        Some(dir) => dir.join(template),
+        None => template.clone(),
+      };
+      let vars = [
+        ("executor", executor_name.clone()),
+        ("task_index", task_index.to_string()),
+        ("rep_index", rep_index.to_string()),
+        (
+          "seed",
+          generator_cfg
+            .map(|g| g.seed.to_string())
+            .unwrap_or_default(),
+        ),
+        (
+          "args",
+          serde_json::to_string(task_args).map_err(BenchmarkError::SerializeResult)?,
+        ),
+        (
+          "attributes",
+          serde_json::to_string(effective_attributes).map_err(BenchmarkError::SerializeResult)?,
+        ),
+      ];
+      Some(write_config_file(&template_path, &vars)?)
+    }
+    None => None,
+  };
 
   // --- Configure Executor Command ---
-  let mut exec_cmd = Command::new(&command_args.command);
+  let exec_command_args = match &config_file {
+    Some(file) => {
+      let config_path = file.path().display().to_string();
+      CommandArgs {
+        args: command_args
+          .args
+          .iter()
+          .map(|a| a.replace("{config_path}", &config_path))
+          .collect(),
+        ..command_args.clone()
+      }
+    }
+    None => command_args.clone(), // Base args from manifest/override
+  };
+  let mut exec_cmd = Command::new(&exec_command_args.command);
+  exec_cmd.args(&exec_command_args.args);
   exec_cmd
-    .args(&command_args.args) // Add base args from manifest/override
     .stdout(Stdio::piped())
     .stderr(Stdio::piped())
     .kill_on_drop(true);
@@ -159,6 +1434,8 @@ async fn run_pipeline(
   if let Some(dir) = &command_args.working_dir {
     exec_cmd.current_dir(dir);
   }
+  apply_env_passthrough(&mut exec_cmd, env_passthrough);
+  exec_cmd.envs(run_env);
 
   exec_cmd
     .env("IMPALAB_COMPONENT_NAME", executor_name)
@@ -171,10 +1448,34 @@ async fn run_pipeline(
     );
 
   // --- Configure Generator (if provided) ---
-  if let Some(ResolvedGenerator {
+  if let Some(buf) = replay {
+    // --- Replay Mode ---
+    tracing::debug!(
+      bytes = buf.len(),
+      "Replaying generator output from an earlier repetition, or an input file"
+    );
+    exec_cmd.stdin(Stdio::piped());
+    if checksum_input {
+      input_checksum = Some(checksum(&buf));
+    }
+    stdin_source = Some(StdinSource::Cached(buf));
+  } else if let Some((seed, builtin)) = builtin {
+    // --- Builtin Mode ---
+    let builtin = builtin.clone().sized(size);
+    tracing::debug!(seed, ?builtin, "Generating input in-process");
+    exec_cmd.stdin(Stdio::piped());
+    let buf = builtin.generate(seed);
+    if checksum_input {
+      input_checksum = Some(checksum(&buf));
+    }
+    stdin_source = Some(StdinSource::Cached(Arc::new(buf)));
+  } else if let Some(ResolvedGenerator {
     name: generator_name,
     seed,
     command_args: gen_command_args,
+    metadata_file,
+    run_env: gen_run_env,
+    builtin: _,
   }) = generator_cfg
   {
     // --- Pipelined Mode ---
@@ -188,18 +1489,53 @@ async fn run_pipeline(
     if let Some(dir) = &gen_command_args.working_dir {
       gen_cmd.current_dir(dir);
     }
+    apply_env_passthrough(&mut gen_cmd, env_passthrough);
+    gen_cmd.envs(gen_run_env);
 
-    gen_cmd
-      .env("IMPALAB_COMPONENT_NAME", generator_name)
-      .env("IMPALAB_SEED", seed.to_string())
-      .env(
-        "IMPALAB_ATTRIBUTES",
-        serde_json::to_string(&effective_attributes).unwrap(),
-      );
+    if let Some(template) = metadata_file {
+      let mut path = PathBuf::from(template.replace("{seed}", &seed.to_string()));
+      if let Some(dir) = &gen_command_args.working_dir {
+        path = dir.join(path);
+      }
+      // Remove any stale sidecar so that a generator that fails to write one is not
+      // credited with a previous run's metadata.
+      if let Err(e) = std::fs::remove_file(&path)
+        && e.kind() != std::io::ErrorKind::NotFound
+      {
+        tracing::warn!(path = %path.display(), error = %e, "Failed to remove stale generator metadata file");
+      }
+      gen_metadata_file = Some((*seed, path));
+    }
+
+    if let Some(id) = case_id {
+      gen_cmd.env("IMPALAB_CASE_ID", id);
+    }
+    if let Some(size) = size {
+      gen_cmd.env("IMPALAB_SIZE", size.to_string());
+    }
+    if !options.no_seed {
+      gen_cmd.env("IMPALAB_SEED", seed.to_string());
+    }
+    gen_cmd.env("IMPALAB_COMPONENT_NAME", generator_name).env(
+      "IMPALAB_ATTRIBUTES",
+      serde_json::to_string(&effective_attributes).unwrap(),
+    );
+
+    apply_resource_limits(&mut gen_cmd, options.generator_limits);
 
     tracing::debug!(gen_dir = ?gen_command_args.working_dir, "Generator directory");
-    tracing::debug!(cmd = ?gen_cmd, "Spawning generator");
-    let mut gen_child = gen_cmd.spawn().map_err(BenchmarkError::SpawnGenerator)?;
+    tracing::debug!(cmd = %gen_command_args.to_shell_string(), "Spawning generator");
+    if options.dump_effective_env {
+      dump_effective_env(&gen_cmd, generator_name, env_passthrough);
+    }
+    let mut gen_child = gen_cmd.spawn().map_err(|e| {
+      classify_spawn_error(
+        generator_name,
+        &gen_command_args.command,
+        e,
+        BenchmarkError::SpawnGenerator,
+      )
+    })?;
 
     // Take pipes from generator
     let gen_stdout = gen_child
@@ -211,97 +1547,1184 @@ async fn run_pipeline(
       .take()
       .ok_or(BenchmarkError::PipeGenStderr)?;
 
-    // Pipe generator's stdout into executor's stdin
-    let gen_stdout_try: Stdio = gen_stdout
-      .try_into()
-      .map_err(BenchmarkError::ConvertGenStdout)?;
-    exec_cmd.stdin(gen_stdout_try);
-
     // Spawn task to log generator's stderr
     gen_stderr_handle = Some(tokio::spawn(
-      read_and_log_stderr(gen_stderr, generator_name.clone()).instrument(
+      read_and_log_stderr(
+        gen_stderr,
+        generator_name.clone(),
+        stderr_tail.clone(),
+        Some(gen_allocation_failed.clone()),
+      )
+      .instrument(
         tracing::info_span!("stderr_handler", component_type = ?ComponentType::Generator),
       ),
     ));
 
+    if checksum_input || validate_input {
+      exec_cmd.stdin(Stdio::piped());
+      let buf = buffer_generator_output(gen_stdout, options.generator_timeout).await?;
+      #[cfg(feature = "jsonschema")]
+      if let Some(schema) = &options.validate_input_schema {
+        validate_generator_output(&buf, schema)?;
+      }
+      if checksum_input {
+        input_checksum = Some(checksum(&buf));
+      }
+      stdin_source = Some(StdinSource::Cached(Arc::new(buf)));
+    } else if relay_stdin {
+      // The header must precede the data and batches must be framed, so the
+      // stream is relayed by the orchestrator.
+      exec_cmd.stdin(Stdio::piped());
+      stdin_source = Some(StdinSource::Generator(gen_stdout));
+    } else {
+      // Pipe generator's stdout into executor's stdin
+      let gen_stdout_try: Stdio = gen_stdout
+        .try_into()
+        .map_err(BenchmarkError::ConvertGenStdout)?;
+      exec_cmd.stdin(gen_stdout_try);
+    }
+
     gen_child_handle = Some(gen_child);
   } else {
     // --- Self-Contained Mode ---
     tracing::debug!("Running executor in self-contained mode (no generator)");
-    exec_cmd.stdin(Stdio::null());
+    if relay_stdin {
+      exec_cmd.stdin(Stdio::piped());
+    } else {
+      exec_cmd.stdin(Stdio::null());
+    }
+  }
+
+  // --- Spawn Executor Process ---
+  tracing::debug!(
+    cmd = %exec_command_args.to_shell_string(),
+    dir = ?exec_command_args.working_dir,
+    "Spawning executor component"
+  );
+  if options.dump_effective_env {
+    dump_effective_env(&exec_cmd, executor_name, env_passthrough);
+  }
+  let exec_started = Instant::now();
+  let mut exec_child = exec_cmd.spawn().map_err(|e| {
+    classify_spawn_error(
+      executor_name,
+      &exec_command_args.command,
+      e,
+      BenchmarkError::SpawnExecutor,
+    )
+  })?;
+
+  let exec_stdout = exec_child
+    .stdout
+    .take()
+    .ok_or(BenchmarkError::PipeExecStdout)?;
+  let exec_stderr = exec_child
+    .stderr
+    .take()
+    .ok_or(BenchmarkError::PipeExecStderr)?;
+
+  let stdin_task = if relay_stdin {
+    let exec_stdin = exec_child
+      .stdin
+      .take()
+      .ok_or(BenchmarkError::PipeExecStdin)?;
+    Some(tokio::spawn(
+      write_exec_stdin(
+        exec_stdin,
+        executor_name.clone(),
+        stdin_header,
+        stdin_prelude.clone(),
+        stdin_source,
+        batch_records,
+        capture,
+      )
+      .instrument(tracing::info_span!("stdin_handler", executor = %executor_name)),
+    ))
+  } else {
+    None
+  };
+
+  // --- Concurrently process all IO ---
+  let meta = BenchmarkMeta {
+    task_index,
+    executor: executor_name.clone(),
+    task_args: task_args.clone(),
+    rep_index,
+    case_id: case_id.map(str::to_owned),
+    size,
+    // Generators run with `--no-seed` choose their own seed.
+    seed: generator_cfg
+      .filter(|g| !options.no_seed || g.builtin.is_some())
+      .map(|g| g.seed),
+    attributes: effective_attributes.clone(),
+    input_checksum: input_checksum.clone().filter(|_| options.checksum_input),
+    orchestrator_duration_ms: None,
+  };
+  let failure_meta = meta.clone();
+  let exec_stalled = Arc::new(tokio::sync::Notify::new());
+  let output_opts = OutputOptions {
+    collect_metrics: options.summary_csv.is_some()
+      || options.summary_json.is_some()
+      || options.print_summary
+      || options.only_summary,
+    emit_rows: !options.only_summary,
+    // Rows wait for the executor's exit when they carry its measured duration,
+    // and for the pipeline's success when a failure would be retried.
+    buffer_rows: !options.only_summary
+      && (options.output_format.writes_to_file()
+        || options.orchestrator_duration
+        || delivery != RowDelivery::Write),
+    min_metric: options.min_metric,
+    report_throughput: options.report_throughput,
+    item_count: options.item_count,
+    exec_output_format: options.exec_output_format,
+    output_format: options.output_format,
+    max_line_length: options.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH),
+    line_timeout: options.line_timeout,
+    stalled: exec_stalled.clone(),
+    // Discarded rows are not reported as progress either.
+    progress: options.progress.clone().filter(|_| !stdout.is_discarding()),
+    results: options.results.clone().filter(|_| !stdout.is_discarding()),
+    stdout: stdout.clone(),
+  };
+  let stdout_task = tokio::spawn(
+    async move {
+      let buffer_rows = output_opts.buffer_rows;
+      let ExecutorStdout {
+        metrics,
+        throughput,
+        rows,
+        row_count,
+        unterminated_error,
+      } = process_executor_stdout(exec_stdout, &meta, output_opts).await?;
+      Ok::<_, BenchmarkError>((
+        metrics,
+        throughput,
+        buffer_rows.then_some(BufferedRows { meta, rows }),
+        row_count,
+        unterminated_error,
+      ))
+    }
+    .instrument(tracing::info_span!("stdout_handler", executor = %executor_name)),
+  );
+
+  let exec_stderr_task = tokio::spawn(
+    read_and_log_stderr(
+      exec_stderr,
+      executor_name.clone(),
+      stderr_tail.clone(),
+      None,
+    )
+    .instrument(tracing::info_span!("stderr_handler", component_type = ?ComponentType::Executor)),
+  );
+
+  // --- Wait for processes to exit ---
+  // A `None` status means the process exceeded its timeout and was killed.
+  let exec_timed_out = tokio::sync::Notify::new();
+  let exec_wait = async {
+    let waited = tokio::select! {
+      status = wait_with_timeout(&mut exec_child, options.timeout, options.kill_grace) => Some(status?),
+      // The stdout reader gave up waiting for the executor's next line.
+      () = exec_stalled.notified() => None,
+    };
+    let status = match waited {
+      Some(status) => status,
+      None => {
+        terminate(&mut exec_child, options.kill_grace).await?;
+        None
+      }
+    };
+    if status.is_none() {
+      exec_timed_out.notify_one();
+    }
+    Ok((status, exec_started.elapsed()))
+  };
+  let (gen_status, (exec_status, exec_elapsed)) = if let Some(mut gen_child) = gen_child_handle {
+    // Pipelined mode: Wait on both
+    let gen_wait = async {
+      tokio::select! {
+        status = wait_with_timeout(&mut gen_child, options.generator_timeout, options.kill_grace) => {
+          return status;
+        }
+        () = exec_timed_out.notified() => {}
+      }
+      // Don't leave the generator of a timed-out executor running.
+      terminate(&mut gen_child, options.kill_grace).await?;
+      gen_child.wait().await.map(Some)
+    };
+
+    let (gen_res, exec_res) =
+      tokio::try_join!(gen_wait, exec_wait).map_err(BenchmarkError::WaitChild)?;
+    (Some(gen_res), exec_res)
+  } else {
+    // Self-contained mode: Wait only on executor
+    let exec_res = exec_wait.await.map_err(BenchmarkError::WaitExec)?;
+    (None, exec_res)
+  };
+
+  // --- Wait for IO tasks to finish ---
+  let captured_input = match stdin_task {
+    Some(handle) => handle.await.map_err(BenchmarkError::StdinTask)??,
+    None => None,
+  };
+  if let Some(handle) = gen_stderr_handle {
+    handle.await.map_err(BenchmarkError::GenStderrTask)??;
+  }
+
+  let (metrics, throughput, rows, row_count, unterminated_error) =
+    stdout_task.await.map_err(BenchmarkError::StdoutTask)??;
+  exec_stderr_task
+    .await
+    .map_err(BenchmarkError::ExecStderrTask)??;
+
+  // An executor that did not exit successfully is reported by a row of its
+  // own, after any rows it printed, unless the attempt is about to be retried.
+  let exec_outcome = ResultStatus::of_exit(exec_status);
+  let failure_rows = (exec_outcome != ResultStatus::Ok
+    && !options.only_summary
+    && !(delivery == RowDelivery::Hold && is_retryable(options, stderr_tail)))
+  .then(|| BufferedRows {
+    meta: failure_meta,
+    rows: vec![BufferedRow::failed(exec_outcome)],
+  });
+
+  let checked = 'checks: {
+    // An unparseable, unterminated final line from an executor that was killed
+    // is a truncated write rather than malformed output.
+    if let Some(err) = unterminated_error {
+      let killed = match exec_status {
+        None => Some(format!(
+          "timed out after {:?}",
+          options.timeout.unwrap_or_default()
+        )),
+        Some(status) => exit_signal(&status).map(|signal| format!("killed by signal {}", signal)),
+      };
+      break 'checks Err(match (killed, err) {
+        (Some(cause), BenchmarkError::MalformedExecOutput { line, .. }) => {
+          tracing::error!(?line, %cause, "Executor was killed mid-write. Final output line is truncated");
+          BenchmarkError::TruncatedFinalLine { line, cause }
+        }
+        (_, err) => {
+          tracing::error!(error = %err, "Error: Malformed output line from executor");
+          err
+        }
+      });
+    }
+
+    if let Some(status_writer) = status_writer {
+      /// Exit codes of one pipeline's processes. A code is `null` when there was
+      /// no such process or it was terminated by a signal or timeout.
+      #[derive(Serialize)]
+      struct ProcessStatus<'a> {
+        #[serde(rename = "type")]
+        kind: &'static str,
+        task_index: usize,
+        executor: &'a str,
+        rep_index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        case_id: Option<&'a str>,
+        exec_code: Option<i32>,
+        gen_code: Option<i32>,
+      }
+
+      let status = ProcessStatus {
+        kind: "process_status",
+        task_index,
+        executor: executor_name,
+        rep_index,
+        case_id,
+        exec_code: exec_status.and_then(|s| s.code()),
+        gen_code: gen_status.flatten().and_then(|s| s.code()),
+      };
+      status_writer
+        .write(&serde_json::to_string(&status).map_err(BenchmarkError::SerializeResult)?)?;
+    }
+
+    // --- Check exit statuses ---
+    let Some(exec_status) = exec_status else {
+      tracing::error!(timeout = ?options.timeout, "Executor process timed out");
+      break 'checks Err(BenchmarkError::ExecutorTimedOut {
+        timeout: options.timeout.unwrap_or_default(),
+      });
+    };
+    let gen_status = match gen_status {
+      Some(None) => {
+        tracing::error!(timeout = ?options.generator_timeout, "Generator process timed out");
+        break 'checks Err(BenchmarkError::GeneratorTimedOut {
+          timeout: options.generator_timeout.unwrap_or_default(),
+        });
+      }
+      Some(Some(status)) => Some(status),
+      None => None,
+    };
+    if let Some(gen_status) = gen_status
+      && !gen_status.success()
+    {
+      if hit_resource_limit(
+        options.generator_limits,
+        &gen_status,
+        gen_allocation_failed.load(Ordering::Relaxed),
+      ) {
+        let signal = exit_signal(&gen_status);
+        tracing::error!(code = ?gen_status.code(), ?signal, limits = %options.generator_limits, "Generator process failed under resource limits");
+        if !options.no_fail_on_error {
+          break 'checks Err(BenchmarkError::GeneratorResourceLimit {
+            limits: options.generator_limits.to_string(),
+            code: gen_status.code(),
+            signal,
+          });
+        }
+      } else {
+        tracing::error!(code = ?gen_status.code(), "Generator process failed");
+      }
+      if !options.no_fail_on_error {
+        break 'checks Err(BenchmarkError::GeneratorProcessFailed {
+          code: gen_status.code(),
+        });
+      }
+    }
+    if !exec_status.success() {
+      tracing::error!(code = ?exec_status.code(), "Executor process failed");
+      if !options.no_fail_on_error {
+        break 'checks Err(BenchmarkError::ExecutorProcessFailed {
+          code: exec_status.code(),
+        });
+      }
+    }
+    Ok(())
+  };
+  let rows = match (rows, failure_rows) {
+    (Some(mut rows), Some(failure)) => {
+      rows.rows.extend(failure.rows);
+      Some(rows)
+    }
+    (rows, failure) => rows.or(failure),
+  };
+  let rows = rows.map(|mut rows| {
+    if options.orchestrator_duration {
+      rows.meta.orchestrator_duration_ms = Some(exec_elapsed.as_secs_f64() * 1000.0);
+    }
+    rows
+  });
+  if let Err(error) = checked {
+    // The rows of a failed pipeline are left to the caller, unless the attempt
+    // is about to be retried.
+    return Err(PipelineFailure {
+      error,
+      rows: rows.filter(|_| !(delivery == RowDelivery::Hold && is_retryable(options, stderr_tail))),
+    });
+  }
+
+  let rows = match rows {
+    Some(rows) if !options.output_format.writes_to_file() && delivery == RowDelivery::Write => {
+      write_buffered_rows(options, stdout, rows).await?;
+      None
+    }
+    rows => rows,
+  };
+
+  let generator_metadata =
+    gen_metadata_file.and_then(|(seed, path)| read_generator_metadata(&path).map(|v| (seed, v)));
+
+  Ok(PipelineOutput {
+    metrics,
+    throughput,
+    rows,
+    row_count,
+    generator_metadata,
+    captured_input,
+    input_checksum,
+  })
+}
+
+/// Writes the buffered rows of a pipeline to stdout, or sends them to the
+/// library caller collecting results.
+async fn write_buffered_rows(
+  options: &RunOptions,
+  stdout: &StdoutWriter,
+  rows: BufferedRows,
+) -> Result<(), BenchmarkError> {
+  if let Some(results) = options.results.as_ref().filter(|_| !stdout.is_discarding()) {
+    for row in rows.rows {
+      // A dropped receiver only means nobody is interested in the results.
+      let _ = results.send(BenchmarkResult {
+        meta: rows.meta.clone(),
+        row,
+      });
+    }
+    return Ok(());
+  }
+  for row in &rows.rows {
+    let line = ResultLine {
+      meta: &rows.meta,
+      row,
+    }
+    .to_line(options.output_format)?;
+    tracing::debug!(parse_native_line = line, "Enriched Output");
+    stdout.write(&line).await;
+  }
+  Ok(())
+}
+
+/// A destination for result rows buffered by file-based output formats.
+trait RowSink {
+  fn write(&mut self, rows: &BufferedRows) -> Result<(), BenchmarkError>;
+
+  fn finish(self: Box<Self>) -> Result<(), BenchmarkError>;
+}
+
+#[cfg(feature = "parquet")]
+impl RowSink for ParquetSink {
+  fn write(&mut self, rows: &BufferedRows) -> Result<(), BenchmarkError> {
+    ParquetSink::write(self, rows)
+  }
+
+  fn finish(self: Box<Self>) -> Result<(), BenchmarkError> {
+    ParquetSink::finish(*self)
+  }
+}
+
+#[cfg(feature = "msgpack")]
+impl RowSink for MsgpackSink {
+  fn write(&mut self, rows: &BufferedRows) -> Result<(), BenchmarkError> {
+    MsgpackSink::write(self, rows)
+  }
+
+  fn finish(self: Box<Self>) -> Result<(), BenchmarkError> {
+    MsgpackSink::finish(*self)
+  }
+}
+
+#[cfg(feature = "sqlite")]
+impl RowSink for SqliteSink {
+  fn write(&mut self, rows: &BufferedRows) -> Result<(), BenchmarkError> {
+    SqliteSink::write(self, rows)
+  }
+
+  fn finish(self: Box<Self>) -> Result<(), BenchmarkError> {
+    SqliteSink::finish(*self)
+  }
+}
+
+/// Writes a cached generator output to `<dir>/<seed>.bin`, or
+/// `<dir>/<seed>_<size>.bin` in a `--sizes` run.
+fn write_cached_input(
+  dir: &Path,
+  seed: u64,
+  size: Option<u64>,
+  buf: &[u8],
+) -> Result<(), BenchmarkError> {
+  let path = match size {
+    Some(size) => dir.join(format!("{}_{}.bin", seed, size)),
+    None => dir.join(format!("{}.bin", seed)),
+  };
+  std::fs::create_dir_all(dir)
+    .and_then(|()| std::fs::write(&path, buf))
+    .map_err(|e| BenchmarkError::WriteCachedInput {
+      path: path.clone(),
+      source: e,
+    })?;
+  tracing::debug!(path = %path.display(), "Cached generator output written");
+  Ok(())
+}
+
+/// Opens the row sink for the selected output format. JSON Lines output is
+/// streamed to stdout and needs none.
+fn open_row_sink(options: &RunOptions) -> Result<Option<Box<dyn RowSink>>, BenchmarkError> {
+  match (options.output_format, &options.output_file) {
+    #[cfg(feature = "parquet")]
+    (OutputFormat::Parquet, Some(path)) => Ok(Some(Box::new(ParquetSink::create(path)?))),
+    #[cfg(feature = "msgpack")]
+    (OutputFormat::Msgpack, Some(path)) => Ok(Some(Box::new(MsgpackSink::create(path)?))),
+    #[cfg(feature = "sqlite")]
+    (OutputFormat::Sqlite, Some(path)) => Ok(Some(Box::new(SqliteSink::create(path)?))),
+    _ => Ok(None),
+  }
+}
+
+/// Resolves once the process receives SIGTERM.
+#[cfg(unix)]
+async fn sigterm() -> Result<(), BenchmarkError> {
+  use tokio::signal::unix::SignalKind;
+
+  tokio::signal::unix::signal(SignalKind::terminate())
+    .map_err(BenchmarkError::InstallSignalHandler)?
+    .recv()
+    .await;
+  Ok(())
+}
+
+/// SIGTERM is not delivered on this platform, so the run is never interrupted.
+#[cfg(not(unix))]
+async fn sigterm() -> Result<(), BenchmarkError> {
+  std::future::pending().await
+}
+
+/// Applies `setrlimit` resource limits to `cmd` just before it execs.
+///
+/// The CPU hard limit is one second above the soft limit, so the process first
+/// receives `SIGXCPU` and is only `SIGKILL`ed if it ignores it.
+#[cfg(unix)]
+fn apply_resource_limits(cmd: &mut Command, limits: ResourceLimits) {
+  if limits.is_empty() {
+    return;
+  }
+
+  // SAFETY: the closure runs in the forked child before `exec` and only calls
+  // `setrlimit`, which is async-signal-safe.
+  unsafe {
+    cmd.pre_exec(move || {
+      if let Some(bytes) = limits.max_mem {
+        let lim = libc::rlimit {
+          rlim_cur: bytes as libc::rlim_t,
+          rlim_max: bytes as libc::rlim_t,
+        };
+        if libc::setrlimit(libc::RLIMIT_AS, &lim) != 0 {
+          return Err(std::io::Error::last_os_error());
+        }
+      }
+      if let Some(secs) = limits.max_cpu_secs {
+        let lim = libc::rlimit {
+          rlim_cur: secs as libc::rlim_t,
+          rlim_max: secs.saturating_add(1) as libc::rlim_t,
+        };
+        if libc::setrlimit(libc::RLIMIT_CPU, &lim) != 0 {
+          return Err(std::io::Error::last_os_error());
+        }
+      }
+      Ok(())
+    });
+  }
+}
+
+#[cfg(not(unix))]
+fn apply_resource_limits(_cmd: &mut Command, limits: ResourceLimits) {
+  if !limits.is_empty() {
+    tracing::warn!("Generator resource limits are only supported on Unix. Ignoring");
+  }
+}
+
+/// Whether a failed process ran into one of its `limits`: `SIGXCPU` (or the
+/// `SIGKILL` at the hard limit) for the CPU limit, and a failed allocation or
+/// `SIGSEGV` for the memory limit. Any other failure is an ordinary one.
+#[cfg(unix)]
+fn hit_resource_limit(
+  limits: ResourceLimits,
+  status: &ExitStatus,
+  allocation_failed: bool,
+) -> bool {
+  let signal = exit_signal(status);
+  let cpu = limits.max_cpu_secs.is_some() && matches!(signal, Some(libc::SIGXCPU | libc::SIGKILL));
+  let mem = limits.max_mem.is_some() && (allocation_failed || signal == Some(libc::SIGSEGV));
+  cpu || mem
+}
+
+#[cfg(not(unix))]
+fn hit_resource_limit(
+  _limits: ResourceLimits,
+  _status: &ExitStatus,
+  _allocation_failed: bool,
+) -> bool {
+  false
+}
+
+/// Whether a stderr line reports a failed allocation, as printed by common
+/// runtimes (Python, Rust, C++, Go, the JVM and libc's `strerror`).
+fn is_allocation_failure(line: &str) -> bool {
+  const PATTERNS: &[&str] = &[
+    "memoryerror",
+    "memory allocation of",
+    "bad_alloc",
+    "out of memory",
+    "outofmemoryerror",
+    "cannot allocate memory",
+  ];
+  let line = line.to_ascii_lowercase();
+  PATTERNS.iter().any(|pattern| line.contains(pattern))
+}
+
+/// One-shot commands run around an executor's pipelines.
+#[derive(Debug, Clone, Copy)]
+enum Hook {
+  Setup,
+  Teardown,
+}
+
+impl Hook {
+  fn name(self) -> &'static str {
+    match self {
+      Hook::Setup => "setup",
+      Hook::Teardown => "teardown",
+    }
+  }
+
+  fn spawn_error(self) -> fn(std::io::Error) -> BenchmarkError {
+    match self {
+      Hook::Setup => BenchmarkError::SpawnSetup,
+      Hook::Teardown => BenchmarkError::SpawnTeardown,
+    }
+  }
+
+  fn failed(self, executor: &str, code: Option<i32>) -> BenchmarkError {
+    let executor = executor.to_owned();
+    match self {
+      Hook::Setup => BenchmarkError::SetupFailed { executor, code },
+      Hook::Teardown => BenchmarkError::TeardownFailed { executor, code },
+    }
+  }
+}
+
+/// Runs an executor's setup or teardown command to completion, logging its output.
+async fn run_hook(
+  hook: Hook,
+  executor: &str,
+  command_args: &CommandArgs,
+  run_env: &BTreeMap<String, String>,
+  options: &RunOptions,
+) -> Result<(), BenchmarkError> {
+  let mut cmd = Command::new(&command_args.command);
+  cmd
+    .args(&command_args.args)
+    .stdin(Stdio::null())
+    .kill_on_drop(true);
+  if let Some(dir) = &command_args.working_dir {
+    cmd.current_dir(dir);
+  }
+  apply_env_passthrough(&mut cmd, options.env_passthrough.as_deref());
+  cmd.envs(run_env).env("IMPALAB_COMPONENT_NAME", executor);
+
+  tracing::info!(cmd = %command_args.to_shell_string(), "Running {} for executor: {}", hook.name(), executor);
+  let output = cmd
+    .output()
+    .await
+    .map_err(|e| classify_spawn_error(executor, &command_args.command, e, hook.spawn_error()))?;
+  for line in String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .chain(String::from_utf8_lossy(&output.stderr).lines())
+  {
+    tracing::info!(component = %executor, "[{}] {}", hook.name(), line);
+  }
+
+  if !output.status.success() {
+    return Err(hook.failed(executor, output.status.code()));
+  }
+  Ok(())
+}
+
+/// Runs every executor's teardown command once. Failures are logged and skipped,
+/// unless `options.strict` is set, in which case the first one is returned after
+/// the remaining teardowns have still run.
+async fn run_teardowns(tasks: &[ResolvedTask], options: &RunOptions) -> Result<(), BenchmarkError> {
+  let mut first_error = None;
+  let mut torn_down = HashSet::new();
+  for task in tasks {
+    if let Some(teardown) = &task.teardown
+      && torn_down.insert(task.executor.as_str())
+      && let Err(e) = run_hook(
+        Hook::Teardown,
+        &task.executor,
+        teardown,
+        &task.run_env,
+        options,
+      )
+      .await
+    {
+      tracing::warn!(error = %e, "Teardown failed for executor: {}", task.executor);
+      first_error.get_or_insert(e);
+    }
+  }
+  match first_error {
+    Some(e) if options.strict => Err(e),
+    _ => Ok(()),
+  }
+}
+
+/// Maps a failure to spawn `program` onto an actionable error, falling back to
+/// `other` for errors that are not about the program itself.
+fn classify_spawn_error(
+  component: &str,
+  program: &Path,
+  error: std::io::Error,
+  other: fn(std::io::Error) -> BenchmarkError,
+) -> BenchmarkError {
+  let component = component.to_owned();
+  let program = program.to_owned();
+  #[cfg(unix)]
+  if error.raw_os_error() == Some(libc::ENOEXEC) {
+    return BenchmarkError::NotExecutable { component, program };
+  }
+  match error.kind() {
+    std::io::ErrorKind::NotFound => BenchmarkError::ProgramNotFound { component, program },
+    std::io::ErrorKind::PermissionDenied => BenchmarkError::PermissionDenied { component, program },
+    _ => other(error),
+  }
+}
+
+/// The signal that terminated the process, if any.
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+  std::os::unix::process::ExitStatusExt::signal(status)
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+  None
+}
+
+/// Replaces every `{name}` placeholder in `template` with its value.
+fn render_template(template: &str, vars: &[(&str, String)]) -> String {
+  vars.iter().fold(template.to_owned(), |acc, (name, value)| {
+    acc.replace(&format!("{{{}}}", name), value)
+  })
+}
+
+/// Renders a config template into a fresh temporary file.
+fn write_config_file(
+  template_path: &std::path::Path,
+  vars: &[(&str, String)],
+) -> Result<tempfile::NamedTempFile, BenchmarkError> {
+  let template =
+    std::fs::read_to_string(template_path).map_err(|e| BenchmarkError::ReadConfigTemplate {
+      path: template_path.to_owned(),
+      source: e,
+    })?;
+
+  let mut file = tempfile::Builder::new()
+    .prefix("impa-config-")
+    .tempfile()
+    .map_err(BenchmarkError::WriteConfigFile)?;
+  std::io::Write::write_all(&mut file, render_template(&template, vars).as_bytes())
+    .map_err(BenchmarkError::WriteConfigFile)?;
+
+  tracing::debug!(path = %file.path().display(), "Rendered executor config file");
+  Ok(file)
+}
+
+/// Waits for `child` to exit, terminating it if `timeout` elapses first.
+///
+/// Returns `Ok(None)` when the process was killed for exceeding its timeout.
+async fn wait_with_timeout(
+  child: &mut Child,
+  timeout: Option<Duration>,
+  grace: Option<Duration>,
+) -> std::io::Result<Option<ExitStatus>> {
+  let Some(timeout) = timeout else {
+    return child.wait().await.map(Some);
+  };
+
+  match tokio::time::timeout(timeout, child.wait()).await {
+    Ok(status) => status.map(Some),
+    Err(_) => {
+      terminate(child, grace).await?;
+      Ok(None)
+    }
+  }
+}
+
+/// Stops `child`. With a `grace` period it is first sent `SIGTERM` and only
+/// `SIGKILL`ed if it is still running once the period elapses.
+async fn terminate(child: &mut Child, grace: Option<Duration>) -> std::io::Result<()> {
+  #[cfg(unix)]
+  if let Some(grace) = grace
+    && let Some(pid) = child.id()
+  {
+    // SAFETY: `pid` belongs to a child that has not been reaped yet.
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0
+      && tokio::time::timeout(grace, child.wait()).await.is_ok()
+    {
+      return Ok(());
+    }
+    tracing::warn!(
+      ?grace,
+      "Process did not exit within its grace period. Sending SIGKILL"
+    );
+  }
+  #[cfg(not(unix))]
+  let _ = grace;
+
+  child.kill().await
+}
+
+/// Input relayed to an executor's stdin by the orchestrator.
+#[derive(Debug)]
+enum StdinSource {
+  /// The live stdout of a generator spawned for this pipeline.
+  Generator(ChildStdout),
+  /// Generator output buffered by an earlier repetition.
+  Cached(Arc<Vec<u8>>),
+}
+
+/// How generator output is shared between the repetitions of a case.
+#[derive(Debug, Clone, Default)]
+enum CachedInput {
+  /// Every pipeline streams from its own generator.
+  #[default]
+  Disabled,
+  /// Buffer the generator's output so later repetitions can replay it.
+  Capture,
+  /// Feed this buffered output (or input file) instead of spawning the generator.
+  Replay(Arc<Vec<u8>>),
+}
+
+/// Writes the task-argument header and the task's stdin prelude to the
+/// executor's stdin, then relays the input (if any) before closing the pipe.
+///
+/// With `capture`, a generator's output is read to completion before any of it
+/// is written, and the buffer is returned so that later repetitions can be fed
+/// the identical bytes. An executor that exits without draining its stdin is not
+/// treated as an error, but the rest of the input is drained so the generator
+/// can finish, and a warning is logged when the executor took less than half of
+/// it.
+async fn write_exec_stdin(
+  mut exec_stdin: tokio::process::ChildStdin,
+  executor: String,
+  header: Option<String>,
+  prelude: Option<String>,
+  source: Option<StdinSource>,
+  batch_records: Option<NonZeroUsize>,
+  capture: bool,
+) -> Result<Option<Arc<Vec<u8>>>, BenchmarkError> {
+  let mut source = match source {
+    Some(StdinSource::Generator(mut gen_stdout)) if capture => {
+      let mut buf = Vec::new();
+      gen_stdout
+        .read_to_end(&mut buf)
+        .await
+        .map_err(BenchmarkError::BufferGeneratorOutput)?;
+      Some(StdinSource::Cached(Arc::new(buf)))
+    }
+    other => other,
+  };
+  let captured = match &source {
+    Some(StdinSource::Cached(buf)) if capture => Some(buf.clone()),
+    _ => None,
+  };
+
+  let mut sink = CountingWriter {
+    inner: &mut exec_stdin,
+    written: 0,
+  };
+  let result = async {
+    if let Some(header) = header {
+      sink.inner.write_all(header.as_bytes()).await?;
+      sink.inner.write_all(b"\n").await?;
+    }
+    if let Some(prelude) = prelude {
+      sink.inner.write_all(prelude.as_bytes()).await?;
+    }
+    match &mut source {
+      Some(StdinSource::Generator(gen_stdout)) => {
+        forward_input(gen_stdout, &mut sink, batch_records).await?
+      }
+      Some(StdinSource::Cached(buf)) => {
+        forward_input(buf.as_slice(), &mut sink, batch_records).await?
+      }
+      None => {}
+    }
+    sink.shutdown().await
+  }
+  .await;
+  let written = sink.written;
+
+  match result {
+    Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+      tracing::debug!("Executor closed stdin before all input was written");
+      let unconsumed = match source {
+        Some(StdinSource::Generator(mut gen_stdout)) => {
+          tokio::io::copy(&mut gen_stdout, &mut tokio::io::sink())
+            .await
+            .map_err(BenchmarkError::DrainGeneratorOutput)?
+        }
+        Some(StdinSource::Cached(buf)) => (buf.len() as u64).saturating_sub(written),
+        None => 0,
+      };
+      if unconsumed > written {
+        let e = BenchmarkError::AlgorithmIgnoredInput {
+          executor,
+          bytes_unconsumed: unconsumed,
+        };
+        tracing::warn!(error = %e, written, "Executor ignored most of its input");
+      }
+      Ok(captured)
+    }
+    other => other
+      .map(|_| captured)
+      .map_err(BenchmarkError::WriteExecStdin),
+  }
+}
+
+/// Counts the bytes written through to `inner`.
+struct CountingWriter<'a, W> {
+  inner: &'a mut W,
+  written: u64,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for CountingWriter<'_, W> {
+  fn poll_write(
+    mut self: std::pin::Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &[u8],
+  ) -> std::task::Poll<std::io::Result<usize>> {
+    let poll = std::pin::Pin::new(&mut *self.inner).poll_write(cx, buf);
+    if let std::task::Poll::Ready(Ok(n)) = poll {
+      self.written += n as u64;
+    }
+    poll
+  }
+
+  fn poll_flush(
+    mut self: std::pin::Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<std::io::Result<()>> {
+    std::pin::Pin::new(&mut *self.inner).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    mut self: std::pin::Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<std::io::Result<()>> {
+    std::pin::Pin::new(&mut *self.inner).poll_shutdown(cx)
+  }
+}
+
+/// Copies `source` into `sink`, framed into batches when `batch_records` is set.
+async fn forward_input<R, W>(
+  mut source: R,
+  sink: &mut W,
+  batch_records: Option<NonZeroUsize>,
+) -> std::io::Result<()>
+where
+  R: AsyncRead + Unpin,
+  W: AsyncWrite + Unpin,
+{
+  match batch_records {
+    Some(batch_size) => relay_batches(source, sink, batch_size).await,
+    None => tokio::io::copy(&mut source, sink).await.map(|_| ()),
+  }
+}
+
+/// Relays newline-delimited records from `source` to `sink` in batches of
+/// `batch_size`, terminating every batch (including a final partial one) with an
+/// empty line.
+///
+/// Each batch is flushed before the next is read, so the time a batch takes to
+/// be written reflects how quickly the executor consumes it.
+async fn relay_batches<R, W>(
+  source: R,
+  sink: &mut W,
+  batch_size: NonZeroUsize,
+) -> std::io::Result<()>
+where
+  R: AsyncRead + Unpin,
+  W: AsyncWrite + Unpin,
+{
+  let mut reader = BufReader::new(source);
+  let mut record = Vec::new();
+  let mut batch = Vec::new();
+  let mut batch_len = 0usize;
+  let mut batches = 0usize;
+  let mut records = 0usize;
+  let started = Instant::now();
+
+  loop {
+    record.clear();
+    let eof = reader.read_until(b'\n', &mut record).await? == 0;
+    if !eof {
+      if !record.ends_with(b"\n") {
+        record.push(b'\n');
+      }
+      batch.extend_from_slice(&record);
+      batch_len += 1;
+    }
+
+    if batch_len > 0 && (batch_len == batch_size.get() || eof) {
+      let batch_started = Instant::now();
+      batch.push(b'\n');
+      sink.write_all(&batch).await?;
+      sink.flush().await?;
+      let elapsed = batch_started.elapsed();
+      tracing::debug!(
+        batch = batches,
+        records = batch_len,
+        ?elapsed,
+        records_per_sec = batch_len as f64 / elapsed.as_secs_f64(),
+        "Relayed batch"
+      );
+      batches += 1;
+      records += batch_len;
+      batch.clear();
+      batch_len = 0;
+    }
+
+    if eof {
+      break;
+    }
+  }
+
+  tracing::info!(
+    batches,
+    records,
+    records_per_sec = records as f64 / started.elapsed().as_secs_f64(),
+    "Relayed generator output in batches"
+  );
+  Ok(())
+}
+
+/// Reads a generator's metadata sidecar after it has exited.
+///
+/// A missing or unreadable file is logged and skipped rather than failing the run.
+/// Contents that are not valid JSON are kept verbatim as a JSON string.
+fn read_generator_metadata(path: &std::path::Path) -> Option<serde_json::Value> {
+  match std::fs::read_to_string(path) {
+    Ok(contents) => Some(
+      serde_json::from_str(&contents).unwrap_or_else(|e| {
+        tracing::warn!(path = %path.display(), error = %e, "Generator metadata file is not valid JSON. Keeping raw contents");
+        serde_json::Value::String(contents)
+      }),
+    ),
+    Err(e) => {
+      tracing::warn!(path = %path.display(), error = %e, "Generator metadata file could not be read. Skipping");
+      None
+    }
   }
+}
 
-  // --- Spawn Executor Process ---
-  tracing::debug!(cmd = ?exec_cmd, "Spawning executor component");
-  let mut exec_child = exec_cmd.spawn().map_err(BenchmarkError::SpawnExecutor)?;
+/// Picks a generator from a weighted mix. The pick depends only on the mix's
+/// seed and `index`, so a fixed seed reproduces the sequence of picks.
+fn pick_generator(
+  mix: &[(ResolvedGenerator, NonZeroU32)],
+  index: usize,
+) -> Option<&ResolvedGenerator> {
+  let (first, _) = mix.first()?;
+  // Weights are positive, so building the distribution cannot fail.
+  let weights = WeightedIndex::new(mix.iter().map(|(_, weight)| weight.get())).ok()?;
+  let mut rng = StdRng::seed_from_u64(first.seed.wrapping_add(index as u64));
+  Some(&mix[weights.sample(&mut rng)].0)
+}
 
-  let exec_stdout = exec_child
-    .stdout
-    .take()
-    .ok_or(BenchmarkError::PipeExecStdout)?;
-  let exec_stderr = exec_child
-    .stderr
-    .take()
-    .ok_or(BenchmarkError::PipeExecStderr)?;
+/// Derives a stable generator seed from a case id.
+fn seed_from_id(id: &str) -> u64 {
+  fnv1a(id.as_bytes())
+}
 
-  // --- Concurrently process all IO ---
-  let meta = BenchmarkMeta {
-    task_index,
-    executor: executor_name.clone(),
-    task_args: task_args.clone(),
-    rep_index,
-    attributes: effective_attributes.clone(),
-  };
-  let stdout_task = tokio::spawn(
-    async move { process_executor_stdout(exec_stdout, &meta).await }
-      .instrument(tracing::info_span!("stdout_handler", executor = %executor_name)),
-  );
+/// The generator seed of the `index`th pipeline of a `--seed-count` run: `seed`
+/// itself, then successive outputs of a SplitMix64 generator seeded with it.
+fn sub_seed(seed: u64, index: usize) -> u64 {
+  if index == 0 {
+    return seed;
+  }
+  let mut z = seed.wrapping_add((index as u64).wrapping_mul(0x9e3779b97f4a7c15));
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+  z ^ (z >> 31)
+}
 
-  let exec_stderr_task = tokio::spawn(
-    read_and_log_stderr(exec_stderr, executor_name.clone())
-      .instrument(tracing::info_span!("stderr_handler", component_type = ?ComponentType::Executor)),
-  );
+/// Checksum of a generator's complete output, as a 16-digit hex string.
+fn checksum(input: &[u8]) -> String {
+  format!("{:016x}", fnv1a(input))
+}
 
-  // --- Wait for processes to exit ---
-  let (gen_status, exec_status) = if let Some(mut gen_child) = gen_child_handle {
-    // Pipelined mode: Wait on both
+/// 64-bit FNV-1a hash.
+///
+/// The std `DefaultHasher` is not guaranteed stable across Rust releases, so the
+/// hash is spelled out to keep seeds and checksums reproducible between
+/// orchestrator builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+  const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+  const FNV_PRIME: u64 = 0x100000001b3;
+
+  bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+    (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+  })
+}
 
-    let (gen_res, exec_res) =
-      tokio::try_join!(gen_child.wait(), exec_child.wait()).map_err(BenchmarkError::WaitChild)?;
-    (Some(gen_res), exec_res)
-  } else {
-    // Self-contained mode: Wait only on executor
-    let exec_res = exec_child.wait().await.map_err(BenchmarkError::WaitExec)?;
-    (None, exec_res)
+/// Reads a generator's stdout to the end, bounded by the generator timeout.
+async fn buffer_generator_output(
+  mut gen_stdout: ChildStdout,
+  timeout: Option<Duration>,
+) -> Result<Vec<u8>, BenchmarkError> {
+  let mut buf = Vec::new();
+  let read = gen_stdout.read_to_end(&mut buf);
+  let result = match timeout {
+    Some(limit) => tokio::time::timeout(limit, read)
+      .await
+      .map_err(|_| BenchmarkError::GeneratorTimedOut { timeout: limit })?,
+    None => read.await,
   };
+  result.map_err(BenchmarkError::BufferGeneratorOutput)?;
+  Ok(buf)
+}
 
-  // --- Wait for IO tasks to finish ---
-  if let Some(handle) = gen_stderr_handle {
-    handle.await.map_err(BenchmarkError::GenStderrTask)??;
+/// Checks each line of a generator's output against `--validate-input-schema`,
+/// failing on the first one that is not JSON or does not match. Blank lines are
+/// skipped.
+#[cfg(feature = "jsonschema")]
+fn validate_generator_output(
+  buf: &[u8],
+  schema: &jsonschema::Validator,
+) -> Result<(), BenchmarkError> {
+  /// Longest part of an offending line quoted in the error.
+  const MAX_RECORD_CHARS: usize = 200;
+
+  for (idx, line) in buf.split(|&b| b == b'\n').enumerate() {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    if line.iter().all(u8::is_ascii_whitespace) {
+      continue;
+    }
+    let violation = |reason: String| {
+      let record = String::from_utf8_lossy(line);
+      BenchmarkError::InputSchemaViolation {
+        line: idx + 1,
+        record: match record.char_indices().nth(MAX_RECORD_CHARS) {
+          Some((end, _)) => format!("{}...", &record[..end]),
+          None => record.into_owned(),
+        },
+        reason,
+      }
+    };
+    let value: serde_json::Value =
+      serde_json::from_slice(line).map_err(|e| violation(format!("not valid JSON ({})", e)))?;
+    schema
+      .validate(&value)
+      .map_err(|e| violation(e.to_string()))?;
   }
+  Ok(())
+}
 
-  stdout_task.await.map_err(BenchmarkError::StdoutTask)??;
-  exec_stderr_task
-    .await
-    .map_err(BenchmarkError::ExecStderrTask)??;
+/// Clears the inherited environment of `cmd`, re-adding only the allowlisted
+/// variables that are present in the orchestrator's own environment.
+fn apply_env_passthrough(cmd: &mut Command, allowlist: Option<&[String]>) {
+  let Some(allowlist) = allowlist else {
+    return;
+  };
 
-  // --- Check exit statuses ---
-  if let Some(gen_status) = gen_status
-    && !gen_status.success()
-  {
-    tracing::error!(code = ?gen_status.code(), "Generator process failed");
-    return Err(BenchmarkError::GeneratorProcessFailed {
-      code: gen_status.code(),
-    });
-  }
-  if !exec_status.success() {
-    tracing::error!(code = ?exec_status.code(), "Executor process failed");
-    return Err(BenchmarkError::ExecutorProcessFailed {
-      code: exec_status.code(),
-    });
+  cmd.env_clear();
+  for key in allowlist {
+    if let Some(val) = std::env::var_os(key) {
+      cmd.env(key, val);
+    }
   }
+}
 
-  Ok(())
+/// Logs the environment `cmd` will be spawned with: the inherited variables,
+/// or only the allowlisted ones, with the command's own variables applied.
+fn dump_effective_env(cmd: &Command, component: &str, allowlist: Option<&[String]>) {
+  let mut env: BTreeMap<OsString, OsString> = match allowlist {
+    // `apply_env_passthrough` already set the allowlisted variables on `cmd`.
+    Some(_) => BTreeMap::new(),
+    None => std::env::vars_os().collect(),
+  };
+  for (key, value) in cmd.as_std().get_envs() {
+    match value {
+      Some(value) => env.insert(key.to_owned(), value.to_owned()),
+      None => env.remove(key),
+    };
+  }
+  let env: Vec<String> = env
+    .iter()
+    .map(|(key, value)| format!("{}={}", key.to_string_lossy(), value.to_string_lossy()))
+    .collect();
+  tracing::debug!(component, ?env, "Effective environment");
 }
 
 fn extract_gen_meta(token: &str) -> Result<Option<serde_json::Value>, BenchmarkError> {
@@ -323,75 +2746,353 @@ fn extract_gen_meta(token: &str) -> Result<Option<serde_json::Value>, BenchmarkE
   }
 }
 
-/// Reads lines from the executor's stdout, parses them, and prints them as JSON.
-async fn process_executor_stdout<R: AsyncRead + Unpin>(
-  stream: R,
-  meta: &BenchmarkMeta,
-) -> Result<(), BenchmarkError> {
-  /// The structure of a single benchmark result, used for JSON serialization.
-  #[derive(Debug, Serialize)]
-  struct BenchmarkResult<'a> {
-    #[serde(flatten)]
-    meta: &'a BenchmarkMeta,
+/// A single benchmark result, as returned by [`run_benchmarks_collect`]. It
+/// serializes to the same JSON object `impa run` prints.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+  #[serde(flatten)]
+  pub meta: BenchmarkMeta,
+
+  #[serde(flatten)]
+  pub row: BufferedRow,
+}
 
-    data_token: String,
+/// Receives the results of a run (`RunOptions::results`).
+pub type ResultSender = tokio::sync::mpsc::UnboundedSender<BenchmarkResult>;
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    gen_meta: Option<serde_json::Value>,
+/// A borrowed view of a single benchmark result, used for serialization.
+///
+/// Fields are emitted in declaration order (`task_index`, `executor`, `args`,
+/// `rep_index`, `case_id`, `size`, `seed`, `attributes`, `input_checksum`,
+/// `orchestrator_duration_ms`, `data_token`, `gen_meta`, `exec_meta`,
+/// `metric`, `metrics`, `status`), and object keys inside attributes and
+/// metadata are sorted.
+#[derive(Debug, Serialize)]
+struct ResultLine<'a> {
+  #[serde(flatten)]
+  meta: &'a BenchmarkMeta,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    exec_meta: Option<serde_json::Value>,
+  #[serde(flatten)]
+  row: &'a BufferedRow,
+}
 
-    metric: serde_json::Number,
+impl ResultLine<'_> {
+  /// Serializes the row as one stdout line of `format`.
+  fn to_line(&self, format: OutputFormat) -> Result<String, BenchmarkError> {
+    match format {
+      OutputFormat::Csv => self.to_csv(),
+      OutputFormat::Pretty => {
+        serde_json::to_string_pretty(self).map_err(BenchmarkError::SerializeResult)
+      }
+      _ => serde_json::to_string(self).map_err(BenchmarkError::SerializeResult),
+    }
   }
 
-  let mut reader = BufReader::new(stream).lines();
-  while let Some(line) = reader
-    .next_line()
-    .await
-    .map_err(BenchmarkError::ReadExecStdout)?
-  {
+  /// Serializes the row as a CSV record with the columns of
+  /// [`output::CSV_HEADER`]. Objects are encoded as JSON and absent values
+  /// are left empty.
+  fn to_csv(&self) -> Result<String, BenchmarkError> {
+    let json = |value: &serde_json::Value| {
+      serde_json::to_string(&Sorted(value)).map_err(BenchmarkError::SerializeResult)
+    };
+    let BenchmarkMeta {
+      task_index,
+      executor,
+      task_args,
+      rep_index,
+      case_id,
+      size,
+      seed,
+      attributes,
+      input_checksum,
+      orchestrator_duration_ms,
+    } = self.meta;
+    let BufferedRow {
+      data_token,
+      gen_meta,
+      exec_meta,
+      metric,
+      metrics,
+      status,
+    } = self.row;
+    let attributes = if attributes.is_empty() {
+      String::new()
+    } else {
+      json(&attributes.clone().into())?
+    };
+    let metrics = if metrics.is_empty() {
+      String::new()
+    } else {
+      serde_json::to_string(metrics).map_err(BenchmarkError::SerializeResult)?
+    };
+    let fields = [
+      task_index.to_string(),
+      executor.clone(),
+      task_args.join(" "),
+      rep_index.to_string(),
+      case_id.clone().unwrap_or_default(),
+      size.map(|size| size.to_string()).unwrap_or_default(),
+      seed.map(|seed| seed.to_string()).unwrap_or_default(),
+      attributes,
+      input_checksum.clone().unwrap_or_default(),
+      orchestrator_duration_ms
+        .map(|ms| ms.to_string())
+        .unwrap_or_default(),
+      data_token.clone(),
+      gen_meta.as_ref().map(json).transpose()?.unwrap_or_default(),
+      exec_meta
+        .as_ref()
+        .map(json)
+        .transpose()?
+        .unwrap_or_default(),
+      metric
+        .as_ref()
+        .map(|metric| metric.to_string())
+        .unwrap_or_default(),
+      metrics,
+      status.as_str().to_owned(),
+    ];
+    Ok(
+      fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(","),
+    )
+  }
+}
+
+/// What was read from an executor's stdout.
+#[derive(Debug, Default)]
+struct ExecutorStdout {
+  metrics: Vec<f64>,
+  throughput: Throughput,
+  rows: Vec<BufferedRow>,
+  /// Number of result rows kept after `min_metric` filtering.
+  row_count: usize,
+  /// The parse error of a final line that was not newline-terminated. It is
+  /// classified once the executor's exit status is known, because a process
+  /// killed mid-write leaves exactly such a line behind.
+  unterminated_error: Option<BenchmarkError>,
+}
+
+/// Reads lines from the executor's stdout, parses them, and prints them as JSON.
+///
+/// When `collect_metrics` is set, the parsed metrics are also returned for aggregation.
+/// When `buffer_rows` is set, rows are returned instead of printed.
+/// Rows whose metric falls below `min_metric` are dropped before being written.
+/// If no line arrives within `line_timeout`, `stalled` is notified so that the
+/// executor is killed.
+/// With `report_throughput`, rows gain an [`ITEMS_PER_SEC`] metric computed
+/// from the current item count.
+async fn process_executor_stdout<R: AsyncRead + Unpin>(
+  stream: R,
+  meta: &BenchmarkMeta,
+  OutputOptions {
+    collect_metrics,
+    emit_rows,
+    buffer_rows,
+    min_metric,
+    report_throughput,
+    mut item_count,
+    exec_output_format,
+    output_format,
+    max_line_length,
+    line_timeout,
+    stalled,
+    progress,
+    results,
+    stdout,
+  }: OutputOptions,
+) -> Result<ExecutorStdout, BenchmarkError> {
+  let mut metrics = Vec::new();
+  let mut buffered = Vec::new();
+  let mut dropped = 0usize;
+  let mut row_count = 0usize;
+  let mut throughput = Throughput::default();
+  let mut uncounted = 0usize;
+  let mut reader = BufReader::new(stream);
+  let mut bytes = Vec::new();
+  loop {
+    bytes.clear();
+    // Bounded, so that a runaway line cannot exhaust memory. Returning early
+    // closes the pipe, which stops the executor on its next write.
+    let mut limited = (&mut reader).take(max_line_length.saturating_add(1));
+    let read = limited.read_until(b'\n', &mut bytes);
+    let read = match line_timeout {
+      Some(limit) => tokio::time::timeout(limit, read).await.map_err(|_| {
+        tracing::error!(timeout = ?limit, "Executor stalled between output lines");
+        stalled.notify_one();
+        BenchmarkError::LineTimedOut { timeout: limit }
+      })?,
+      None => read.await,
+    };
+    if read.map_err(BenchmarkError::ReadExecStdout)? == 0 {
+      break;
+    }
+    if bytes.len() as u64 > max_line_length && bytes.last() != Some(&b'\n') {
+      tracing::error!(max_line_length, "Executor output line is too long");
+      return Err(BenchmarkError::LineTooLong {
+        limit: max_line_length,
+      });
+    }
+    // One stray byte should not discard the rest of the executor's results.
+    let buf = String::from_utf8_lossy(&bytes);
+    if matches!(buf, Cow::Owned(_)) {
+      tracing::warn!(
+        line = %buf.trim_end(),
+        "Executor output line is not valid UTF-8. Invalid bytes were replaced with U+FFFD"
+      );
+    }
+    let terminated = buf.ends_with('\n');
+    let line = buf.strip_suffix('\n').unwrap_or(&buf);
+    let line = line.strip_suffix('\r').unwrap_or(line).to_owned();
     if line.is_empty() {
       continue;
     }
+    // Progress reports drive the progress view and are not result rows.
+    if let Some(value) = line.strip_prefix(PROGRESS_PREFIX) {
+      match value.trim().parse::<f64>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => progress::emit(
+          progress.as_ref(),
+          ProgressEvent::PipelineProgress {
+            task_index: meta.task_index,
+            rep_index: meta.rep_index,
+            fraction,
+          },
+        ),
+        _ => tracing::warn!(
+          ?line,
+          "Ignoring progress line without a fraction between 0 and 1"
+        ),
+      }
+      continue;
+    }
+    if let Some(value) = line.strip_prefix(COUNT_PREFIX) {
+      match value.trim().parse::<u64>() {
+        Ok(count) => item_count = Some(count),
+        Err(_) => tracing::warn!(?line, "Ignoring count line without a non-negative integer"),
+      }
+      continue;
+    }
 
-    match parse_native_line(&line) {
-      Ok((metric, data_token, exec_meta)) => {
+    let parsed = match exec_output_format {
+      ExecOutputFormat::Native => parse_native_line(&line),
+      ExecOutputFormat::Kv => parse_kv_line(&line),
+    };
+    match parsed {
+      Ok((metric, data_token, exec_meta, mut extra_metrics)) => {
         let gen_meta =
           extract_gen_meta(&data_token).map_err(|e| BenchmarkError::MalformedExecOutput {
             line: line.clone(),
             source: Box::new(e),
           })?;
 
-        let result = BenchmarkResult {
-          meta,
+        if let Some(min) = min_metric
+          && metric.as_f64().is_some_and(|m| m < min)
+        {
+          dropped += 1;
+          continue;
+        }
+        row_count += 1;
+
+        if collect_metrics && let Some(m) = metric.as_f64() {
+          metrics.push(m);
+        }
+
+        if report_throughput {
+          match item_count.zip(metric.as_f64()) {
+            // A zero or negative duration has no meaningful throughput.
+            Some((items, m)) => {
+              if let Some(row) = Throughput::from_row(items, m)
+                && let Some(rate) = row.items_per_sec()
+              {
+                throughput += row;
+                extra_metrics.insert(ITEMS_PER_SEC.to_owned(), rate);
+              }
+            }
+            None => uncounted += 1,
+          }
+        }
+
+        let row = BufferedRow {
+          data_token,
           gen_meta,
           exec_meta,
-          data_token,
-          metric,
+          metric: Some(metric),
+          metrics: extra_metrics,
+          status: ResultStatus::Ok,
         };
-        let json_result =
-          serde_json::to_string(&result).map_err(BenchmarkError::SerializeResult)?;
-        tracing::debug!(parse_native_line = json_result, "Enriched Output");
-        println!("{}", json_result);
+        if buffer_rows {
+          buffered.push(row);
+        } else if let (true, Some(results)) = (emit_rows, &results) {
+          // A dropped receiver stops the run (see `run_benchmarks_stream`).
+          let _ = results.send(BenchmarkResult {
+            meta: meta.clone(),
+            row,
+          });
+        } else if emit_rows {
+          let line = ResultLine { meta, row: &row }.to_line(output_format)?;
+          tracing::debug!(parse_native_line = line, "Enriched Output");
+          stdout.write(&line).await;
+        }
+        progress::emit(
+          progress.as_ref(),
+          ProgressEvent::ResultRow {
+            task_index: meta.task_index,
+          },
+        );
       }
       Err(e) => {
         let wrapped_err = BenchmarkError::MalformedExecOutput {
           line: line.clone(),
           source: Box::new(e),
         };
+        if !terminated {
+          tracing::debug!(?line, error = %wrapped_err, "Final executor output line is unterminated and malformed");
+          return Ok(ExecutorStdout {
+            metrics,
+            throughput,
+            rows: buffered,
+            row_count,
+            unterminated_error: Some(wrapped_err),
+          });
+        }
         tracing::error!(?line, error = %wrapped_err, "Error: Malformed output line from executor");
         return Err(wrapped_err);
       }
     }
   }
-  Ok(())
+
+  if dropped > 0 {
+    tracing::info!(
+      dropped,
+      min_metric,
+      "Dropped result rows with a metric below the minimum"
+    );
+  }
+  if uncounted > 0 {
+    tracing::warn!(
+      uncounted,
+      "No item count for result rows; pass --item-count or print a #count= line"
+    );
+  }
+  Ok(ExecutorStdout {
+    metrics,
+    throughput,
+    rows: buffered,
+    row_count,
+    unterminated_error: None,
+  })
 }
 
-/// Reads lines from a process's stderr and logs them.
+/// Reads lines from a process's stderr, logs them and keeps the most recent in `tail`.
+/// `allocation_failed` is set if a line reports a failed allocation.
 async fn read_and_log_stderr<R: AsyncRead + Unpin>(
   stream: R,
   component_name: String,
+  tail: StderrTail,
+  allocation_failed: Option<Arc<AtomicBool>>,
 ) -> Result<(), BenchmarkError> {
   let mut reader = BufReader::new(stream).lines();
 
@@ -404,14 +3105,60 @@ async fn read_and_log_stderr<R: AsyncRead + Unpin>(
     })?
   {
     tracing::info!(component = %component_name, "{}", line);
+    if let Some(flag) = &allocation_failed
+      && is_allocation_failure(&line)
+    {
+      flag.store(true, Ordering::Relaxed);
+    }
+    tail.push(line);
   }
   Ok(())
 }
 
-/// Parses a single line of `metric|data_token[|exec_meta]` pipe-delimited format.
-fn parse_native_line(
-  line: &str,
-) -> Result<(serde_json::Number, String, Option<serde_json::Value>), BenchmarkError> {
+/// The most recent stderr lines of a pipeline's processes, shared with the
+/// stderr reader tasks so the retry decision can inspect them.
+#[derive(Debug, Clone, Default)]
+struct StderrTail(Arc<Mutex<VecDeque<String>>>);
+
+impl StderrTail {
+  /// Number of lines retained per pipeline.
+  const CAPACITY: usize = 64;
+
+  fn push(&self, line: String) {
+    let mut lines = self.0.lock().unwrap_or_else(|e| e.into_inner());
+    if lines.len() == Self::CAPACITY {
+      lines.pop_front();
+    }
+    lines.push_back(line);
+  }
+
+  fn matches(&self, pattern: &Regex) -> bool {
+    let lines = self.0.lock().unwrap_or_else(|e| e.into_inner());
+    lines.iter().any(|line| pattern.is_match(line))
+  }
+}
+
+/// Whether a failed pipeline may be retried. Without a stderr pattern every
+/// failure is retryable; with one, only failures whose stderr matches it are.
+fn is_retryable(options: &RunOptions, stderr_tail: &StderrTail) -> bool {
+  match &options.retry_if_stderr_matches {
+    Some(pattern) => stderr_tail.matches(pattern),
+    None => true,
+  }
+}
+
+/// An executor result line: its metric, data token, `exec_meta` and extra metrics.
+type ParsedLine = (
+  serde_json::Number,
+  String,
+  Option<serde_json::Value>,
+  BTreeMap<String, f64>,
+);
+
+/// Parses a single line of `metric|data_token[|key=value...][|exec_meta]`
+/// pipe-delimited format. A trailing `\r` from a CRLF line ending is ignored.
+fn parse_native_line(line: &str) -> Result<ParsedLine, BenchmarkError> {
+  let line = line.strip_suffix('\r').unwrap_or(line);
   let parts: Vec<&str> = line.splitn(3, '|').collect();
 
   if parts.len() < 2 {
@@ -429,19 +3176,121 @@ fn parse_native_line(
     }
   })?;
 
-  let exec_meta = if parts.len() == 3 {
-    Some(
-      serde_json::from_str(parts[2]).map_err(|e| BenchmarkError::MalformedJSON {
-        context: "exec_meta".to_string(),
-        raw_segment: parts[2].to_string(),
-        source: e,
-      })?,
-    )
-  } else {
-    None
+  // Segments that start like `key=` are extra metrics. No JSON value starts
+  // that way, so whatever follows them is `exec_meta`.
+  let mut metrics = BTreeMap::new();
+  let mut rest = parts.get(2).copied();
+  while let Some(segment) = rest.filter(|s| is_metric_pair(s)) {
+    let (pair, tail) = match segment.split_once('|') {
+      Some((pair, tail)) => (pair, Some(tail)),
+      None => (segment, None),
+    };
+    let (key, value) = pair.split_once('=').unwrap_or_default();
+    match value.parse::<f64>() {
+      Ok(value) if value.is_finite() => metrics.insert(key.to_string(), value),
+      _ => {
+        return Err(BenchmarkError::MalformedMetric {
+          pair: pair.to_string(),
+        });
+      }
+    };
+    rest = tail;
+  }
+
+  let exec_meta = match rest {
+    Some(segment) => {
+      Some(
+        serde_json::from_str(segment).map_err(|e| BenchmarkError::MalformedJSON {
+          context: "exec_meta".to_string(),
+          raw_segment: segment.to_string(),
+          source: e,
+        })?,
+      )
+    }
+    None => None,
   };
 
-  Ok((metric, data_token, exec_meta))
+  Ok((metric, data_token, exec_meta, metrics))
+}
+
+/// Whether a pipe-delimited segment starts with a `key=` extra metric name.
+fn is_metric_pair(segment: &str) -> bool {
+  let key = segment.split('|').next().unwrap_or_default();
+  key.split_once('=').is_some_and(|(key, _)| {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+      && key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+  })
+}
+
+/// Parses a single line of space-separated `key=value` pairs.
+///
+/// `metric` and `data_token` are required; any other pairs are collected into
+/// an `exec_meta` object of strings. Values may be double-quoted to contain
+/// whitespace, with `\"` and `\\` escapes.
+fn parse_kv_line(line: &str) -> Result<ParsedLine, BenchmarkError> {
+  let mut metric = None;
+  let mut data_token = None;
+  let mut extras = serde_json::Map::new();
+  let mut chars = line.chars().peekable();
+
+  loop {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if chars.peek().is_none() {
+      break;
+    }
+
+    let mut key = String::new();
+    while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+      key.push(c);
+    }
+    if key.is_empty() || chars.next_if_eq(&'=').is_none() {
+      while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+        key.push(c);
+      }
+      return Err(BenchmarkError::KvPair { token: key });
+    }
+
+    let mut value = String::new();
+    if chars.next_if_eq(&'"').is_some() {
+      loop {
+        match chars.next() {
+          Some('"') => break,
+          Some('\\') if chars.peek().is_some() => value.extend(chars.next()),
+          Some(c) => value.push(c),
+          None => return Err(BenchmarkError::UnterminatedKvQuote { key }),
+        }
+      }
+      if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+        let mut token = format!("{}=\"{}\"", key, value);
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+          token.push(c);
+        }
+        return Err(BenchmarkError::KvPair { token });
+      }
+    } else {
+      while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+        value.push(c);
+      }
+    }
+
+    match key.as_str() {
+      "metric" => metric = Some(value),
+      "data_token" => data_token = Some(value),
+      _ => {
+        extras.insert(key, serde_json::Value::String(value));
+      }
+    }
+  }
+
+  let metric = metric.ok_or(BenchmarkError::MissingKvKey { key: "metric" })?;
+  let data_token = data_token.ok_or(BenchmarkError::MissingKvKey { key: "data_token" })?;
+  let metric = serde_json::from_str::<serde_json::Number>(&metric)
+    .map_err(|e| BenchmarkError::ParseMetric { metric, source: e })?;
+  let exec_meta = (!extras.is_empty()).then_some(serde_json::Value::Object(extras));
+
+  Ok((metric, data_token, exec_meta, BTreeMap::new()))
 }
 
 #[cfg(test)]
@@ -450,15 +3299,39 @@ mod tests {
 
   #[test]
   fn test_parse_native_line_valid() {
-    let (metric, id, meta) = parse_native_line("45000|run_123").unwrap();
+    let (metric, id, meta, _) = parse_native_line("45000|run_123").unwrap();
     assert_eq!(id, "run_123");
     assert_eq!(metric, serde_json::Number::from(45000));
     assert!(meta.is_none());
   }
 
+  #[test]
+  fn test_is_allocation_failure() {
+    assert!(is_allocation_failure("MemoryError"));
+    assert!(is_allocation_failure(
+      "memory allocation of 1073741824 bytes failed"
+    ));
+    assert!(is_allocation_failure(
+      "terminate called after throwing an instance of 'std::bad_alloc'"
+    ));
+    assert!(is_allocation_failure("fatal error: runtime: out of memory"));
+    assert!(!is_allocation_failure("Traceback (most recent call last):"));
+  }
+
+  #[test]
+  fn test_parse_native_line_crlf() {
+    let (metric, id, meta, _) = parse_native_line("1234|case1\r").unwrap();
+    assert_eq!(id, "case1");
+    assert_eq!(metric, serde_json::Number::from(1234));
+    assert!(meta.is_none());
+
+    let (_, _, meta, _) = parse_native_line("1234|case1|{\"sorted\":true}\r").unwrap();
+    assert_eq!(meta, Some(serde_json::json!({"sorted": true})));
+  }
+
   #[test]
   fn test_parse_native_line_valid_float() {
-    let (metric, id, meta) = parse_native_line("45.52|run_123").unwrap();
+    let (metric, id, meta, _) = parse_native_line("45.52|run_123").unwrap();
     assert_eq!(id, "run_123");
     assert_eq!(metric, serde_json::Number::from_f64(45.52).unwrap());
     assert!(meta.is_none());
@@ -466,7 +3339,7 @@ mod tests {
 
   #[test]
   fn test_parse_native_line_with_meta() {
-    let (metric, id, meta) =
+    let (metric, id, meta, _) =
       parse_native_line(r#"450|run_1|{"converged":true,"iters":10}"#).unwrap();
     assert_eq!(id, "run_1");
     assert_eq!(metric, serde_json::Number::from(450));
@@ -501,7 +3374,7 @@ mod tests {
 
   #[test]
   fn test_parse_native_line_nested_array() {
-    let (metric, id, meta) = parse_native_line(r#"450|run_1|[1, 2, {"a": "b"}]"#).unwrap();
+    let (metric, id, meta, _) = parse_native_line(r#"450|run_1|[1, 2, {"a": "b"}]"#).unwrap();
     assert_eq!(id, "run_1");
     assert_eq!(metric, serde_json::Number::from(450));
     assert!(meta.unwrap().is_array());
@@ -509,12 +3382,87 @@ mod tests {
 
   #[test]
   fn test_parse_native_line_with_nested_pipes_in_meta() {
-    let (metric, id, meta) = parse_native_line(r#"450|run_1|{"msg":"foo|bar"}"#).unwrap();
+    let (metric, id, meta, _) = parse_native_line(r#"450|run_1|{"msg":"foo|bar"}"#).unwrap();
     assert_eq!(id, "run_1");
     assert_eq!(metric, serde_json::Number::from(450));
     assert_eq!(meta.unwrap()["msg"], "foo|bar");
   }
 
+  #[test]
+  fn test_parse_native_line_with_extra_metrics() {
+    let (metric, id, meta, metrics) =
+      parse_native_line(r#"1234|case1|comparisons=5012|bytes=4096|{"sorted":true}"#).unwrap();
+    assert_eq!(id, "case1");
+    assert_eq!(metric, serde_json::Number::from(1234));
+    assert_eq!(meta.unwrap()["sorted"], true);
+    assert_eq!(metrics["comparisons"], 5012.0);
+    assert_eq!(metrics["bytes"], 4096.0);
+
+    let (_, _, meta, metrics) = parse_native_line("1234|case1|ratio=0.5").unwrap();
+    assert!(meta.is_none());
+    assert_eq!(metrics["ratio"], 0.5);
+
+    let (_, _, _, metrics) = parse_native_line("1234|case1").unwrap();
+    assert!(metrics.is_empty());
+  }
+
+  #[test]
+  fn test_parse_native_line_with_malformed_extra_metric() {
+    assert!(matches!(
+      parse_native_line("1234|case1|comparisons=many"),
+      Err(BenchmarkError::MalformedMetric { pair }) if pair == "comparisons=many"
+    ));
+    assert!(matches!(
+      parse_native_line("1234|case1|bytes=inf|{}"),
+      Err(BenchmarkError::MalformedMetric { .. })
+    ));
+  }
+
+  #[test]
+  fn test_parse_kv_line_valid() {
+    let (metric, id, meta, _) =
+      parse_kv_line(r#"data_token=run_1 func=sort metric=1234 note="two words \"quoted\"""#)
+        .unwrap();
+    assert_eq!(id, "run_1");
+    assert_eq!(metric, serde_json::Number::from(1234));
+    let meta = meta.unwrap();
+    assert_eq!(meta["func"], "sort");
+    assert_eq!(meta["note"], r#"two words "quoted""#);
+
+    let (_, _, meta, _) = parse_kv_line("metric=1.5 data_token=x").unwrap();
+    assert!(meta.is_none());
+  }
+
+  #[test]
+  fn test_parse_kv_line_malformed() {
+    assert!(matches!(
+      parse_kv_line("metric=1 func=sort"),
+      Err(BenchmarkError::MissingKvKey { key: "data_token" })
+    ));
+    assert!(matches!(
+      parse_kv_line("metric=1 data_token=x stray"),
+      Err(BenchmarkError::KvPair { token }) if token == "stray"
+    ));
+    assert!(matches!(
+      parse_kv_line(r#"metric=1 data_token="x"#),
+      Err(BenchmarkError::UnterminatedKvQuote { key }) if key == "data_token"
+    ));
+    assert!(matches!(
+      parse_kv_line("metric=fast data_token=x"),
+      Err(BenchmarkError::ParseMetric { .. })
+    ));
+  }
+
+  #[test]
+  fn test_sub_seed() {
+    assert_eq!(sub_seed(42, 0), 42);
+    // The first SplitMix64 output for a state of 0.
+    assert_eq!(sub_seed(0, 1), 0xe220a8397b1dcdaf);
+    let seeds: HashSet<u64> = (0..100).map(|i| sub_seed(42, i)).collect();
+    assert_eq!(seeds.len(), 100);
+    assert_ne!(sub_seed(42, 1), sub_seed(43, 1));
+  }
+
   #[test]
   fn test_extract_gen_meta() {
     // Valid JSON
@@ -539,6 +3487,226 @@ mod tests {
     assert!(matches!(res, Err(BenchmarkError::MalformedJSON { .. })));
   }
 
+  fn bash_component(script: &str, working_dir: &std::path::Path) -> crate::manifest::CommandArgs {
+    crate::manifest::CommandArgs {
+      command: "bash".into(),
+      args: vec!["-c".to_string(), script.to_string()],
+      working_dir: Some(working_dir.to_owned()),
+    }
+  }
+
+  fn sidecar_config(gen_script: &str, working_dir: &std::path::Path) -> ResolvedConfig {
+    ResolvedConfig {
+      generator: Some(ResolvedGenerator {
+        name: "sidecar-gen".to_string(),
+        seed: 7,
+        command_args: bash_component(gen_script, working_dir),
+        metadata_file: Some("meta_{seed}.json".to_string()),
+        run_env: BTreeMap::new(),
+        builtin: None,
+      }),
+      generator_mix: Vec::new(),
+      tasks: vec![ResolvedTask {
+        executor: "cat-exec".to_string(),
+        args: vec![],
+        command_args: bash_component("while read -r l; do echo \"1|$l\"; done", working_dir),
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        stdin_prelude: None,
+        effective_reps: 1,
+        effective_attributes: serde_json::Map::new(),
+      }],
+      options: RunOptions::default(),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_run_benchmarks_reads_generator_metadata_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let config = sidecar_config(
+      r#"echo '{"rows":1}' > "meta_$IMPALAB_SEED.json"; echo case_1"#,
+      temp.path(),
+    );
+
+    let metadata = run_benchmarks(config).await.unwrap();
+    assert_eq!(
+      metadata.generator_metadata[&7],
+      serde_json::json!({"rows": 1})
+    );
+  }
+
+  #[tokio::test]
+  async fn test_run_benchmarks_missing_generator_metadata_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let config = sidecar_config("echo case_1", temp.path());
+
+    let metadata = run_benchmarks(config).await.unwrap();
+    assert!(metadata.generator_metadata.is_empty());
+  }
+
+  #[test]
+  fn test_rep_order_releases_rows_in_rep_index_order() {
+    let rows = |rep_index| BufferedRows {
+      meta: BenchmarkMeta {
+        task_index: 0,
+        executor: "exec".to_string(),
+        task_args: Vec::new(),
+        rep_index,
+        case_id: None,
+        size: None,
+        seed: None,
+        attributes: serde_json::Map::new(),
+        input_checksum: None,
+        orchestrator_duration_ms: None,
+      },
+      rows: vec![BufferedRow::failed(ResultStatus::Ok)],
+    };
+    let reps =
+      |ready: Vec<BufferedRows>| ready.iter().map(|r| r.meta.rep_index).collect::<Vec<_>>();
+
+    let mut order = RepOrder::default();
+    for rep_index in 0..4 {
+      order.expect(rep_index);
+    }
+    assert_eq!(reps(order.finish(2, Some(rows(2)))), Vec::<usize>::new());
+    // A repetition without rows still lets the later ones through.
+    assert_eq!(reps(order.finish(1, None)), Vec::<usize>::new());
+    assert_eq!(reps(order.finish(0, Some(rows(0)))), [0, 2]);
+    assert_eq!(reps(order.finish(3, Some(rows(3)))), [3]);
+  }
+
+  #[tokio::test]
+  async fn test_run_benchmarks_collect() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut config = sidecar_config("echo case_1; echo case_2", temp.path());
+    config.tasks[0].effective_reps = 2;
+
+    let results = run_benchmarks_collect(config).await.unwrap();
+    let rows: Vec<(usize, &str)> = results
+      .iter()
+      .map(|r| (r.meta.rep_index, r.row.data_token.as_str()))
+      .collect();
+    assert_eq!(
+      rows,
+      [(0, "case_1"), (0, "case_2"), (1, "case_1"), (1, "case_2")]
+    );
+    assert_eq!(
+      serde_json::to_value(&results[0]).unwrap(),
+      serde_json::json!({
+        "task_index": 0,
+        "executor": "cat-exec",
+        "rep_index": 0,
+        "seed": 7,
+        "data_token": "case_1",
+        "metric": 1,
+        "status": "ok"
+      })
+    );
+  }
+
+  #[tokio::test]
+  async fn test_run_benchmarks_collect_with_hook() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut config = sidecar_config("echo case_1; echo case_5; echo case_9", temp.path());
+    // Reports the number in each case as its metric.
+    config.tasks[0].command_args = bash_component(
+      "while read -r l; do echo \"${l#case_}|$l\"; done",
+      temp.path(),
+    );
+
+    let results = run_benchmarks_collect_with(config, |result| {
+      let metric = result.row.metric.as_ref().unwrap().as_f64().unwrap();
+      result
+        .row
+        .metrics
+        .insert("doubled".to_string(), metric * 2.0);
+      metric >= 3.0
+    })
+    .await
+    .unwrap();
+    let rows: Vec<(&str, f64)> = results
+      .iter()
+      .map(|r| (r.row.data_token.as_str(), r.row.metrics["doubled"]))
+      .collect();
+    assert_eq!(rows, [("case_5", 10.0), ("case_9", 18.0)]);
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_run_benchmarks_stream_yields_live_and_cancels_on_drop() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut config = sidecar_config("echo case_1", temp.path());
+    // The executor reports a row, then keeps running under the same pid.
+    config.tasks[0].command_args = bash_component(
+      "read -r line; echo $$ > exec.pid; echo \"1|$line\"; exec sleep 30",
+      temp.path(),
+    );
+
+    let mut results = Box::pin(run_benchmarks_stream(config));
+    let first = tokio::time::timeout(Duration::from_secs(10), results.next())
+      .await
+      .expect("row arrives while the executor is still running")
+      .unwrap()
+      .unwrap();
+    assert_eq!(first.row.data_token, "case_1");
+
+    let pid: libc::pid_t = std::fs::read_to_string(temp.path().join("exec.pid"))
+      .unwrap()
+      .trim()
+      .parse()
+      .unwrap();
+    drop(results);
+    let killed = async {
+      // Signal 0 only checks that the process still exists.
+      while unsafe { libc::kill(pid, 0) } == 0 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+      }
+    };
+    tokio::time::timeout(Duration::from_secs(5), killed)
+      .await
+      .expect("dropping the stream kills the executor");
+  }
+
+  #[test]
+  fn test_render_template() {
+    let vars = [("seed", "42".to_string()), ("args", r#"["a"]"#.to_string())];
+    assert_eq!(
+      render_template("seed={seed} args={args} {unknown}", &vars),
+      r#"seed=42 args=["a"] {unknown}"#
+    );
+  }
+
+  #[tokio::test]
+  async fn test_relay_batches_groups_records() {
+    let input: &[u8] = b"r1\nr2\nr3\nr4\nr5\nr6\nr7";
+    let mut output = Vec::new();
+
+    relay_batches(input, &mut output, NonZeroUsize::new(3).unwrap())
+      .await
+      .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let batches: Vec<Vec<&str>> = output
+      .split_terminator("\n\n")
+      .map(|b| b.lines().collect())
+      .collect();
+    assert_eq!(
+      batches,
+      vec![vec!["r1", "r2", "r3"], vec!["r4", "r5", "r6"], vec!["r7"]]
+    );
+  }
+
+  #[test]
+  fn test_seed_from_id() {
+    assert_eq!(seed_from_id(""), 0xcbf29ce484222325);
+    assert_eq!(seed_from_id("a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(seed_from_id("case_1"), seed_from_id("case_1"));
+    assert_ne!(seed_from_id("case_1"), seed_from_id("case_2"));
+  }
+
   #[test]
   fn test_parse_native_line_malformed_parts_too_few() {
     let res = parse_native_line("45000");
@@ -11,73 +11,393 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::cli::DiffArgs;
 use crate::cli::FilterArgs;
 use crate::cli::ManifestArgs;
 use crate::error::BuildError;
+use crate::manifest::ArgsDelivery;
 use crate::manifest::BuildManifest;
+use crate::manifest::BuildWarnings;
 use crate::manifest::CommandArgs;
 use crate::manifest::ComponentType;
 use crate::manifest::ManifestComponent;
+use notify::RecursiveMode;
+use notify::Watcher;
+use regex::Regex;
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::btree_map::Entry;
 use std::fs;
+use std::io::Read;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Output;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
 
-/// Scans a directory for components and runs their build steps.
+/// Scans directories for components and runs their build steps.
 ///
-/// This function finds all `impafile.toml` files in the `components_dir`,
-/// runs their optional `[build]` steps, and generates a manifest file
-/// at `manifest_out`.
+/// This function finds all `impafile.toml` files in each of the `components_dirs`,
+/// runs their optional `[build]` steps, and generates a single manifest file
+/// at `manifest_out`. Component names must be unique across all directories.
+///
+/// Each build step is killed if it runs longer than `build_timeout`. With a
+/// `warning_pattern`, matching lines of build output are counted into the
+/// manifest entries.
+///
+/// With `--diff` or `--diff-only`, the changes against the existing manifest are
+/// printed to stdout; `--diff-only` skips writing the new manifest.
+///
+/// With `dry_run`, the build plan and the resulting manifest entries are
+/// printed to stdout instead; no build step runs and no manifest is written.
 pub fn build_components(
-  components_dir: PathBuf,
+  components_dirs: Vec<PathBuf>,
   manifest_arg: ManifestArgs,
   filter_args: &FilterArgs,
+  diff_args: &DiffArgs,
+  build_timeout: Option<Duration>,
+  warning_pattern: Option<&Regex>,
+  dry_run: bool,
 ) -> Result<(), BuildError> {
   let manifest_out: PathBuf = manifest_arg.get_path();
-  tracing::info!("Scanning for components in {}", components_dir.display());
 
-  if !components_dir.exists() {
-    return Err(BuildError::ComponentsDirNotFound(components_dir));
+  if let Some(missing) = components_dirs.iter().find(|dir| !dir.exists()) {
+    return Err(BuildError::ComponentsDirNotFound(missing.clone()));
+  }
+
+  let (manifest, _) = scan_components(
+    &components_dirs,
+    &manifest_arg,
+    filter_args,
+    build_timeout,
+    warning_pattern,
+    dry_run,
+  )?;
+
+  if dry_run {
+    let json = serde_json::to_string_pretty(&manifest).map_err(BuildError::SerializeManifest)?;
+    println!("Manifest entries ({}):", manifest_out.display());
+    println!("{}", json);
+    tracing::info!("Dry run. No build steps executed and manifest not written.");
+    return Ok(());
+  }
+
+  if diff_args.diff || diff_args.diff_only {
+    let existing = match manifest_arg.get_content()? {
+      Some(content) => serde_json::from_str(&content).map_err(BuildError::ParseExistingManifest)?,
+      None => BuildManifest::default(),
+    };
+    print!("{}", existing.diff(&manifest));
+
+    if diff_args.diff_only {
+      tracing::info!("Diff only. Manifest not written.");
+      return Ok(());
+    }
+  }
+
+  write_manifest(&manifest, &manifest_out)
+}
+
+/// Builds all components like [`build_components`], then watches the
+/// `components_dirs` and rebuilds only the component directories that change,
+/// rewriting the manifest after each round of rebuilds.
+///
+/// New component directories are built and registered, and the entries of
+/// removed ones are pruned. A failing rebuild is logged and keeps the
+/// component's previous entries. A round of rebuilds starts once no change has
+/// been seen for `debounce`; changes made by the build steps themselves, i.e.
+/// until the directories are quiet again, are ignored. Runs until the watcher
+/// stops.
+pub fn watch_components(
+  components_dirs: Vec<PathBuf>,
+  manifest_arg: ManifestArgs,
+  filter_args: &FilterArgs,
+  build_timeout: Option<Duration>,
+  warning_pattern: Option<&Regex>,
+  debounce: Duration,
+) -> Result<(), BuildError> {
+  let manifest_out: PathBuf = manifest_arg.get_path();
+
+  if let Some(missing) = components_dirs.iter().find(|dir| !dir.exists()) {
+    return Err(BuildError::ComponentsDirNotFound(missing.clone()));
+  }
+
+  let (mut manifest, mut sources) = scan_components(
+    &components_dirs,
+    &manifest_arg,
+    filter_args,
+    build_timeout,
+    warning_pattern,
+    false,
+  )?;
+  write_manifest(&manifest, &manifest_out)?;
+
+  let roots = components_dirs
+    .iter()
+    .map(|dir| {
+      dir
+        .canonicalize()
+        .map_err(|e| BuildError::CanonicalizePath {
+          path: dir.clone(),
+          source: e,
+        })
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let (tx, rx) = std::sync::mpsc::channel();
+  let mut watcher = notify::recommended_watcher(tx).map_err(BuildError::Watch)?;
+  for root in &roots {
+    watcher
+      .watch(root, RecursiveMode::Recursive)
+      .map_err(BuildError::Watch)?;
+  }
+  tracing::info!("Watching {} for changes", roots.len());
+
+  // Maps an event to the component directories it touches. Reads are ignored.
+  let changed_dirs = |event: notify::Result<notify::Event>| {
+    let event = event.map_err(BuildError::Watch)?;
+    if event.kind.is_access() {
+      return Ok(Vec::new());
+    }
+    Ok::<_, BuildError>(
+      event
+        .paths
+        .iter()
+        .filter_map(|path| {
+          let root = roots.iter().find(|root| path.starts_with(root))?;
+          let name = path.strip_prefix(root).ok()?.components().next()?;
+          Some(root.join(name))
+        })
+        .collect(),
+    )
+  };
+
+  let mut pending = BTreeSet::new();
+  loop {
+    let mut changed = std::mem::take(&mut pending);
+    if changed.is_empty() {
+      match rx.recv() {
+        Ok(event) => changed.extend(changed_dirs(event)?),
+        Err(_) => break,
+      }
+    }
+    while let Ok(event) = rx.recv_timeout(debounce) {
+      changed.extend(changed_dirs(event)?);
+    }
+    changed
+      .retain(|dir: &PathBuf| sources.contains_key(dir) || dir.join("impafile.toml").is_file());
+    if changed.is_empty() {
+      continue;
+    }
+
+    for dir in &changed {
+      // Rebuild into a copy, so a failed build keeps the previous entries.
+      let mut updated = manifest.clone();
+      for name in sources.get(dir).into_iter().flatten() {
+        updated.components.remove(name);
+        if updated.default_generator.as_ref() == Some(name) {
+          updated.default_generator = None;
+        }
+      }
+
+      let names = if dir.join("impafile.toml").is_file() {
+        tracing::info!("Change detected in {}. Rebuilding", dir.display());
+        process_component(
+          &manifest_arg,
+          dir,
+          &mut updated,
+          filter_args,
+          build_timeout,
+          warning_pattern,
+          false,
+        )
+      } else {
+        tracing::info!("{} was removed. Pruning its components", dir.display());
+        Ok(Vec::new())
+      };
+      match names {
+        Ok(names) => {
+          manifest = updated;
+          if names.is_empty() {
+            sources.remove(dir);
+          } else {
+            sources.insert(dir.clone(), names);
+          }
+        }
+        Err(e) => tracing::error!(
+          error = %e,
+          "Rebuild failed for {}. Keeping its previous manifest entries",
+          dir.display()
+        ),
+      }
+    }
+    write_manifest(&manifest, &manifest_out)?;
+
+    // Until they are quiet again, changes to the rebuilt directories come from
+    // their own build steps.
+    while let Ok(event) = rx.recv_timeout(debounce) {
+      pending.extend(
+        changed_dirs(event)?
+          .into_iter()
+          .filter(|dir| !changed.contains(dir)),
+      );
+    }
   }
 
+  Ok(())
+}
+
+/// Builds the components of every directory in `components_dirs` into a new
+/// manifest.
+///
+/// Also returns the names of the components registered from each (canonical)
+/// component directory.
+fn scan_components(
+  components_dirs: &[PathBuf],
+  manifest_arg: &ManifestArgs,
+  filter_args: &FilterArgs,
+  build_timeout: Option<Duration>,
+  warning_pattern: Option<&Regex>,
+  dry_run: bool,
+) -> Result<(BuildManifest, BTreeMap<PathBuf, Vec<String>>), BuildError> {
   let mut manifest = BuildManifest::default();
+  let mut sources = BTreeMap::new();
 
-  for entry in fs::read_dir(&components_dir).map_err(BuildError::ReadDir)? {
-    let entry = entry.map_err(BuildError::ReadDir)?;
-    let path: PathBuf = entry.path();
+  for components_dir in components_dirs {
+    tracing::info!("Scanning for components in {}", components_dir.display());
 
-    if path.is_dir() {
-      let config_path = path.join("impafile.toml");
-      if config_path.exists() && config_path.is_file() {
-        let path_canon: PathBuf =
-          path
-            .canonicalize()
-            .map_err(|e| BuildError::CanonicalizePath {
-              path: path.clone(),
-              source: e,
-            })?;
+    for entry in fs::read_dir(components_dir).map_err(BuildError::ReadDir)? {
+      let entry = entry.map_err(BuildError::ReadDir)?;
+      let path: PathBuf = entry.path();
+
+      if path.is_dir() {
+        let config_path = path.join("impafile.toml");
+        if config_path.exists() && config_path.is_file() {
+          let path_canon: PathBuf =
+            path
+              .canonicalize()
+              .map_err(|e| BuildError::CanonicalizePath {
+                path: path.clone(),
+                source: e,
+              })?;
 
-        process_component(&manifest_arg, &path_canon, &mut manifest, filter_args)?;
+          let names = process_component(
+            manifest_arg,
+            &path_canon,
+            &mut manifest,
+            filter_args,
+            build_timeout,
+            warning_pattern,
+            dry_run,
+          )?;
+          sources.insert(path_canon, names);
+        }
       }
     }
   }
 
-  let json = serde_json::to_string_pretty(&manifest).map_err(BuildError::SerializeManifest)?;
-  fs::write(&manifest_out, json).map_err(BuildError::WriteManifest)?;
+  Ok((manifest, sources))
+}
+
+fn write_manifest(manifest: &BuildManifest, manifest_out: &Path) -> Result<(), BuildError> {
+  let json = serde_json::to_string_pretty(manifest).map_err(BuildError::SerializeManifest)?;
+  replace_file(manifest_out, |file| file.write_all(json.as_bytes()))
+    .map_err(BuildError::WriteManifest)?;
   tracing::info!("Build manifest written to {}", manifest_out.display());
+  Ok(())
+}
 
+/// Replaces `path` with what `write` writes, through a temporary file in the
+/// same directory that is renamed over it. Readers, and a build killed midway,
+/// therefore never leave a partially written file behind. The temporary file
+/// is removed if `write` fails.
+fn replace_file(
+  path: &Path,
+  write: impl FnOnce(&mut fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+  let mut file = tempfile::Builder::new()
+    .prefix(".impa_manifest")
+    .suffix(".tmp")
+    .tempfile_in(dir)?;
+  // Temporary files are private; keep the mode of the file being replaced.
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path).map_or(0o644, |m| m.permissions().mode());
+    file
+      .as_file()
+      .set_permissions(fs::Permissions::from_mode(mode))?;
+  }
+  write(file.as_file_mut())?;
+  file.as_file().sync_all()?;
+  file.persist(path).map_err(|e| e.error)?;
   Ok(())
 }
 
+/// Runs `cmd` to completion like [`Command::output`], killing it if `timeout` elapses.
+///
+/// Returns `Ok(None)` when the process was killed for exceeding its timeout.
+fn output_with_timeout(
+  mut cmd: Command,
+  timeout: Option<Duration>,
+) -> std::io::Result<Option<Output>> {
+  let Some(timeout) = timeout else {
+    return cmd.output().map(Some);
+  };
+
+  let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+  // Drain the pipes on separate threads so a chatty build cannot block on a full pipe.
+  let drain = |pipe: Option<Box<dyn Read + Send>>| {
+    std::thread::spawn(move || {
+      let mut buf = Vec::new();
+      if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buf);
+      }
+      buf
+    })
+  };
+  let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+  let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+
+  let deadline = Instant::now() + timeout;
+  let status = loop {
+    if let Some(status) = child.try_wait()? {
+      break status;
+    }
+    if Instant::now() >= deadline {
+      child.kill()?;
+      child.wait()?;
+      return Ok(None);
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  };
+
+  Ok(Some(Output {
+    status,
+    stdout: stdout.join().unwrap_or_default(),
+    stderr: stderr.join().unwrap_or_default(),
+  }))
+}
+
+/// Builds the components of the impafile in `base_dir` and registers them in
+/// `manifest`, returning their names.
 fn process_component(
   manifest_arg: &ManifestArgs,
   base_dir: &Path,
   manifest: &mut BuildManifest,
   filter_args: &FilterArgs,
-) -> Result<(), BuildError> {
+  build_timeout: Option<Duration>,
+  warning_pattern: Option<&Regex>,
+  dry_run: bool,
+) -> Result<Vec<String>, BuildError> {
   let content =
     fs::read_to_string(base_dir.join("impafile.toml")).map_err(BuildError::ReadConfig)?;
 
@@ -87,7 +407,31 @@ fn process_component(
     #[serde(rename = "type")]
     component_type: ComponentType,
     build: Option<CommandArgs>,
-    run: CommandArgs,
+    /// Artifacts, relative to the component directory, the build step must produce.
+    #[serde(default)]
+    produces: Vec<String>,
+    /// One-time command run before the component's first pipeline.
+    setup: Option<CommandArgs>,
+    /// One-time command run after the component's last pipeline.
+    teardown: Option<CommandArgs>,
+    /// Marks a generator as the manifest's default generator.
+    #[serde(default)]
+    default: bool,
+    run: RunSection,
+    metadata_file: Option<String>,
+    #[serde(default)]
+    args_delivery: ArgsDelivery,
+    config_template: Option<PathBuf>,
+    #[serde(default)]
+    tags: Vec<String>,
+  }
+  /// The `[run]` table: the run command plus an optional `[run.env]` table.
+  #[derive(Debug, Deserialize)]
+  struct RunSection {
+    #[serde(flatten)]
+    command: CommandArgs,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
   }
   #[derive(Debug, Deserialize)]
   struct Impafile {
@@ -95,8 +439,11 @@ fn process_component(
   }
   let impafile: Impafile = toml::from_str(&content).map_err(BuildError::TomlParse)?;
 
+  let mut names = Vec::new();
   for config in impafile.components {
-    // Excluded or non-included components bypass the build step but are still registered.
+    let mut build_warnings = None;
+    // Excluded, non-included or untagged components bypass the build step but
+    // are still registered.
     let should_build = if let Some(es) = &filter_args.exclude
       && es.contains(&config.name)
     {
@@ -105,11 +452,26 @@ fn process_component(
       && !is.contains(&config.name)
     {
       false
+    } else if let Some(tags) = &filter_args.tag
+      && !config.tags.iter().any(|t| tags.contains(t))
+    {
+      false
     } else {
       true
     };
 
-    if should_build {
+    if dry_run {
+      match (&config.build, should_build) {
+        (Some(build_step), true) => println!(
+          "build {} ({:?}): {}",
+          config.name,
+          config.component_type,
+          build_step.to_shell_string()
+        ),
+        (None, true) => println!("skip  {} (no build step)", config.name),
+        (_, false) => println!("skip  {} (filtered out)", config.name),
+      }
+    } else if should_build {
       // Run optional build step
       if let Some(build_step) = &config.build {
         tracing::info!(
@@ -117,18 +479,23 @@ fn process_component(
           config.name,
           config.component_type
         );
+        tracing::debug!(cmd = %build_step.to_shell_string(), "Running build step");
+
+        let mut build_cmd = Command::new(&build_step.command);
+        build_cmd.args(&build_step.args).current_dir(base_dir);
 
         let Output {
           status,
           stdout,
           stderr,
-        } = Command::new(&build_step.command)
-          .args(&build_step.args)
-          .current_dir(base_dir)
-          .output()
+        } = output_with_timeout(build_cmd, build_timeout)
           .map_err(|e| BuildError::BuildCommandExecFailed {
             component_name: config.name.clone(),
             source: e,
+          })?
+          .ok_or_else(|| BuildError::BuildCommandTimedOut {
+            component_name: config.name.clone(),
+            timeout: build_timeout.unwrap_or_default(),
           })?;
 
         if !status.success() {
@@ -141,6 +508,35 @@ fn process_component(
             stderr,
           });
         }
+
+        if let Some(pattern) = warning_pattern {
+          let warnings = BuildWarnings::scan(
+            [
+              String::from_utf8_lossy(&stdout).as_ref(),
+              String::from_utf8_lossy(&stderr).as_ref(),
+            ],
+            pattern,
+          );
+          if warnings.count > 0 {
+            tracing::warn!(
+              count = warnings.count,
+              "Build step of {} emitted warnings",
+              config.name
+            );
+          }
+          build_warnings = Some(warnings);
+        }
+
+        if let Some(artifact) = config
+          .produces
+          .iter()
+          .find(|artifact| !base_dir.join(artifact).exists())
+        {
+          return Err(BuildError::MissingArtifact {
+            component_name: config.name,
+            artifact: artifact.clone(),
+          });
+        }
       } else {
         tracing::info!("No build step for {}. Skipping.", config.name);
       }
@@ -151,6 +547,22 @@ fn process_component(
       );
     }
 
+    if config.default {
+      if config.component_type != ComponentType::Generator {
+        return Err(BuildError::DefaultNotGenerator {
+          component_name: config.name,
+        });
+      }
+      if let Some(existing) = &manifest.default_generator {
+        return Err(BuildError::MultipleDefaultGenerators {
+          first: existing.clone(),
+          second: config.name,
+        });
+      }
+      manifest.default_generator = Some(config.name.clone());
+    }
+
+    names.push(config.name.clone());
     match manifest.components.entry(config.name) {
       Entry::Occupied(entry) => {
         return Err(BuildError::DuplicateComponentName {
@@ -174,13 +586,53 @@ fn process_component(
         entry.insert(ManifestComponent {
           component_type: config.component_type,
           run: CommandArgs {
-            working_dir: Some(cmp_relpath),
-            ..config.run
+            working_dir: Some(cmp_relpath.clone()),
+            ..config.run.command
           },
+          setup: config.setup.map(|setup| CommandArgs {
+            working_dir: Some(cmp_relpath.clone()),
+            ..setup
+          }),
+          teardown: config.teardown.map(|teardown| CommandArgs {
+            working_dir: Some(cmp_relpath),
+            ..teardown
+          }),
+          metadata_file: config.metadata_file,
+          args_delivery: config.args_delivery,
+          config_template: config.config_template,
+          run_env: config.run.env,
+          build_warnings,
+          tags: config.tags,
         });
       }
     }
   }
 
-  Ok(())
+  Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_replace_file_keeps_original_on_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("impa_manifest.json");
+    fs::write(&path, "{\"components\":{}}").unwrap();
+
+    // A write that fails partway through, as if serialization were interrupted.
+    let err = replace_file(&path, |file| {
+      file.write_all(b"{\"compo")?;
+      Err(std::io::Error::other("interrupted"))
+    })
+    .unwrap_err();
+    assert_eq!(err.to_string(), "interrupted");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"components\":{}}");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    replace_file(&path, |file| file.write_all(b"{}")).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+  }
 }
@@ -0,0 +1,131 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Generators implemented inside the orchestrator, for smoke tests that should not
+//! depend on an external script.
+//!
+//! A builtin generator is selected with a `builtin:` prefixed generator name, e.g.
+//! `--set generator.name=builtin:ints`. It needs no manifest entry.
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::error::ConfigError;
+
+/// Prefix marking a generator name as a builtin generator.
+pub const PREFIX: &str = "builtin:";
+
+/// Names of the available builtin generators, without [`PREFIX`].
+pub const NAMES: &[&str] = &["ints"];
+
+/// An in-process generator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuiltinGenerator {
+  /// A single line of `count` space-separated random `i32`s. Takes the count as
+  /// its optional first argument (default 1000), or from `--sizes`.
+  Ints { count: usize },
+}
+
+impl BuiltinGenerator {
+  /// Resolves `name` (including its [`PREFIX`]) and the generator's `args`.
+  pub fn parse(name: &str, args: &[String]) -> Result<Self, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidBuiltinGeneratorArgs {
+      name: name.to_owned(),
+      reason,
+    };
+    match name.strip_prefix(PREFIX) {
+      Some("ints") => {
+        let count = match args {
+          [] => 1000,
+          [count] => count
+            .parse()
+            .map_err(|e| invalid(format!("invalid count '{}': {}", count, e)))?,
+          _ => {
+            return Err(invalid(format!(
+              "expected at most 1 argument, got {}",
+              args.len()
+            )))
+          }
+        };
+        Ok(BuiltinGenerator::Ints { count })
+      }
+      _ => Err(ConfigError::UnknownBuiltinGenerator {
+        name: name.to_owned(),
+        available: NAMES.iter().map(|n| format!("{}{}", PREFIX, n)).collect(),
+      }),
+    }
+  }
+
+  /// Replaces the generator's count with `size`, when given (`--sizes`).
+  pub fn sized(self, size: Option<u64>) -> Self {
+    match (self, size) {
+      (BuiltinGenerator::Ints { .. }, Some(size)) => BuiltinGenerator::Ints {
+        count: size as usize,
+      },
+      (generator, None) => generator,
+    }
+  }
+
+  /// Produces the generator's complete output for `seed`. The same seed always
+  /// yields the same bytes.
+  pub fn generate(&self, seed: u64) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    match self {
+      BuiltinGenerator::Ints { count } => {
+        let mut line = (0..*count)
+          .map(|_| rng.random::<i32>().to_string())
+          .collect::<Vec<_>>()
+          .join(" ");
+        line.push('\n');
+        line.into_bytes()
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_builtin_ints() {
+    let generator = BuiltinGenerator::parse("builtin:ints", &["5".to_string()]).unwrap();
+    assert_eq!(generator, BuiltinGenerator::Ints { count: 5 });
+
+    let output = String::from_utf8(generator.generate(42)).unwrap();
+    assert!(output.ends_with('\n'));
+    assert_eq!(output.trim_end().split(' ').count(), 5);
+    assert!(
+      output
+        .trim_end()
+        .split(' ')
+        .all(|n| n.parse::<i32>().is_ok())
+    );
+    assert_eq!(generator.generate(42), output.into_bytes());
+    assert_ne!(generator.generate(42), generator.generate(43));
+    assert_eq!(
+      generator.clone().sized(Some(3)),
+      BuiltinGenerator::Ints { count: 3 }
+    );
+    assert_eq!(generator.clone().sized(None), generator);
+
+    assert!(matches!(
+      BuiltinGenerator::parse("builtin:ints", &["many".to_string()]),
+      Err(ConfigError::InvalidBuiltinGeneratorArgs { .. })
+    ));
+    assert!(matches!(
+      BuiltinGenerator::parse("builtin:floats", &[]),
+      Err(ConfigError::UnknownBuiltinGenerator { .. })
+    ));
+  }
+}
@@ -0,0 +1,167 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::BenchmarkError;
+
+/// One pipeline invocation of a run, identified independently of its position
+/// in the sweep so that a checkpoint stays valid if the task list is reordered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorkItem {
+  pub executor: String,
+  pub args: Vec<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub case_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub size: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub seed: Option<u64>,
+  pub rep_index: usize,
+}
+
+/// Append-only record of completed work items, stored as one JSON object per line.
+#[derive(Debug)]
+pub struct Checkpoint {
+  path: PathBuf,
+  completed: HashSet<WorkItem>,
+  file: File,
+}
+
+impl Checkpoint {
+  /// Opens (or creates) the checkpoint at `path` and loads the items it already records.
+  ///
+  /// Lines that cannot be parsed, such as a final line cut short when the
+  /// previous run was killed, are skipped with a warning.
+  pub fn open(path: &Path) -> Result<Self, BenchmarkError> {
+    let open_err = |source| BenchmarkError::OpenCheckpoint {
+      path: path.to_path_buf(),
+      source,
+    };
+
+    let contents = match std::fs::read_to_string(path) {
+      Ok(contents) => contents,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+      Err(e) => return Err(open_err(e)),
+    };
+
+    let mut completed = HashSet::new();
+    for (line_no, line) in contents.lines().enumerate() {
+      if line.trim().is_empty() {
+        continue;
+      }
+      match serde_json::from_str::<WorkItem>(line) {
+        Ok(item) => {
+          completed.insert(item);
+        }
+        Err(e) => tracing::warn!(
+          path = %path.display(),
+          line = line_no + 1,
+          error = %e,
+          "Ignoring unreadable checkpoint entry"
+        ),
+      }
+    }
+
+    let mut file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .map_err(open_err)?;
+
+    // Start on a fresh line if the previous run was cut off mid-write.
+    if !contents.is_empty() && !contents.ends_with('\n') {
+      file.write_all(b"\n").map_err(open_err)?;
+    }
+
+    tracing::info!(
+      path = %path.display(),
+      completed = completed.len(),
+      "Loaded checkpoint"
+    );
+
+    Ok(Self {
+      path: path.to_path_buf(),
+      completed,
+      file,
+    })
+  }
+
+  pub fn is_completed(&self, item: &WorkItem) -> bool {
+    self.completed.contains(item)
+  }
+
+  /// Appends `item` to the checkpoint and flushes it to disk.
+  pub fn record(&mut self, item: WorkItem) -> Result<(), BenchmarkError> {
+    let write_err = |source| BenchmarkError::WriteCheckpoint {
+      path: self.path.clone(),
+      source,
+    };
+
+    let mut line = serde_json::to_string(&item).map_err(BenchmarkError::SerializeResult)?;
+    line.push('\n');
+    self.file.write_all(line.as_bytes()).map_err(write_err)?;
+    self.file.sync_data().map_err(write_err)?;
+    self.completed.insert(item);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn item(rep_index: usize) -> WorkItem {
+    WorkItem {
+      executor: "exec".to_string(),
+      args: vec!["quick".to_string()],
+      case_id: None,
+      size: None,
+      seed: Some(42),
+      rep_index,
+    }
+  }
+
+  #[test]
+  fn test_checkpoint_round_trip_skips_partial_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("checkpoint.jsonl");
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    checkpoint.record(item(0)).unwrap();
+    drop(checkpoint);
+
+    // Simulate a run killed halfway through writing an entry.
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"executor":"exec","ar"#).unwrap();
+    drop(file);
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    assert!(checkpoint.is_completed(&item(0)));
+    assert!(!checkpoint.is_completed(&item(1)));
+    checkpoint.record(item(1)).unwrap();
+    drop(checkpoint);
+
+    let checkpoint = Checkpoint::open(&path).unwrap();
+    assert!(checkpoint.is_completed(&item(0)));
+    assert!(checkpoint.is_completed(&item(1)));
+  }
+}
@@ -13,11 +13,18 @@
 // limitations under the License.
 use clap::Parser;
 use clap::Subcommand;
+use regex::Regex;
 use std::io::ErrorKind;
+use std::num::NonZeroU32;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::compare::Threshold;
 use crate::error::ConfigError;
+use crate::manifest::ComponentType;
+use crate::scaffold::Language;
 
 /// Benchmarking Orchestrator
 #[derive(Debug, Parser)]
@@ -27,24 +34,161 @@ pub struct Cli {
   pub command: Commands,
 }
 
-/// Defines the main subcommands: `build` and `run`.
+/// Defines the main subcommands: `init`, `build`, `run`, `compare` and `doctor`.
+// Parsed once per process, so the size difference between variants is irrelevant.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+  /// Scaffolds a new component directory with an impafile and a stub run script.
+  Init(InitArgs),
+
   /// Scans the components directory and builds all found components.
   Build {
-    /// Root directory containing component subdirectories.
+    /// Root directory containing component subdirectories. May be given
+    /// multiple times to scan several directories into one manifest.
     #[arg(long, default_value = ".")]
-    components_dir: PathBuf,
+    components_dir: Vec<PathBuf>,
 
     #[command(flatten)]
     manifest: ManifestArgs,
 
     #[command(flatten)]
     filter_args: FilterArgs,
+
+    #[command(flatten)]
+    diff_args: DiffArgs,
+
+    /// Maximum duration of each component build step (e.g. `90s`, `5m`).
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    build_timeout: Option<Duration>,
+
+    /// Count the lines of build step output that match `--build-warning-pattern`
+    /// and record the count and a sample in the component's manifest entry.
+    #[arg(long)]
+    capture_build_warnings: bool,
+
+    /// Regular expression identifying warning lines in build output.
+    #[arg(
+      long,
+      value_name = "REGEX",
+      default_value = "warning:",
+      requires = "capture_build_warnings",
+      value_parser = Regex::new
+    )]
+    build_warning_pattern: Regex,
+
+    /// List which components would be built or skipped and the resulting
+    /// manifest entries, without running build steps or writing the manifest.
+    #[arg(long, conflicts_with_all = ["diff", "diff_only"])]
+    dry_run: bool,
+
+    /// After building, keep watching the components directories and rebuild
+    /// only the components whose directories change, updating the manifest.
+    #[arg(long, conflicts_with_all = ["diff", "diff_only", "dry_run"])]
+    watch: bool,
+
+    /// How long changes must settle before `--watch` rebuilds.
+    #[arg(
+      long,
+      value_name = "DURATION",
+      default_value = "500ms",
+      requires = "watch",
+      value_parser = parse_duration
+    )]
+    debounce: Duration,
   },
 
   /// Runs the benchmark using built components.
   Run(RunArgs),
+
+  /// Compares two result files and reports per-key speedups and regressions.
+  Compare(CompareArgs),
+
+  /// Checks the manifest, the programs it references and the environment.
+  Doctor(DoctorArgs),
+
+  /// Lists the generators and executors recorded in the build manifest.
+  List(ListArgs),
+
+  /// Checks that every program the build manifest references can be run.
+  Validate(ValidateArgs),
+}
+
+/// Arguments for the `validate` subcommand.
+#[derive(Debug, clap::Args)]
+pub struct ValidateArgs {
+  #[command(flatten)]
+  pub manifest: ManifestArgs,
+
+  /// Also check that arguments that look like paths exist.
+  #[arg(long)]
+  pub strict: bool,
+}
+
+/// Arguments for the `list` subcommand.
+#[derive(Debug, clap::Args)]
+pub struct ListArgs {
+  #[command(flatten)]
+  pub manifest: ManifestArgs,
+
+  /// Print the manifest as JSON instead of a table.
+  #[arg(long)]
+  pub json: bool,
+}
+
+/// Arguments for the `doctor` subcommand.
+#[derive(Debug, clap::Args)]
+pub struct DoctorArgs {
+  /// Components directory to check. May be given multiple times.
+  #[arg(long, default_value = ".")]
+  pub components_dir: Vec<PathBuf>,
+
+  #[command(flatten)]
+  pub manifest: ManifestArgs,
+}
+
+/// Arguments for the `init` subcommand.
+#[derive(Debug, clap::Args)]
+pub struct InitArgs {
+  /// Directory to create the component in.
+  pub dir: PathBuf,
+
+  /// Kind of component to scaffold.
+  #[arg(long = "type", value_enum)]
+  pub component_type: ComponentType,
+
+  /// Language of the stub run script.
+  #[arg(long, value_enum)]
+  pub language: Language,
+
+  /// Component name. Defaults to the directory name.
+  #[arg(long)]
+  pub name: Option<String>,
+}
+
+/// Arguments for the `compare` subcommand.
+#[derive(Debug, clap::Args)]
+pub struct CompareArgs {
+  /// Baseline results file (JSON Lines).
+  pub old: PathBuf,
+
+  /// Results file compared against the baseline (JSON Lines).
+  pub new: PathBuf,
+
+  /// Change of the median metric below which a key is reported as unchanged:
+  /// a percentage (`5%`, or a bare `5`) or an absolute duration (`200ns`,
+  /// `1.5ms`), taking the metric as nanoseconds.
+  #[arg(long, default_value = "5%", value_name = "THRESHOLD")]
+  pub threshold: Threshold,
+
+  /// Print the comparison as JSON instead of a table.
+  #[arg(long)]
+  pub json: bool,
+
+  /// Exit successfully even if a key regressed. By default, a regression
+  /// makes the comparison exit with a non-zero status, e.g. to fail a CI job.
+  #[arg(long)]
+  pub no_fail_on_regression: bool,
 }
 
 #[derive(Debug, clap::Args)]
@@ -56,6 +200,22 @@ pub struct FilterArgs {
   /// Comma-separated list of components to exclude from build step execution.
   #[arg(long, conflicts_with = "include", value_delimiter = ',')]
   pub exclude: Option<Vec<String>>,
+
+  /// Comma-separated tags. Only components carrying at least one of them
+  /// execute build steps.
+  #[arg(long, value_delimiter = ',', value_name = "TAGS")]
+  pub tag: Option<Vec<String>>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct DiffArgs {
+  /// Print the changes against the existing manifest before writing it.
+  #[arg(long)]
+  pub diff: bool,
+
+  /// Print the changes against the existing manifest without writing it.
+  #[arg(long, conflicts_with = "diff")]
+  pub diff_only: bool,
 }
 
 /// Arguments for the `run` subcommand.
@@ -64,13 +224,564 @@ pub struct RunArgs<F: FileReader + Default + std::fmt::Debug = RealFileSystem> {
   #[command(flatten)]
   pub manifest: ManifestArgs<F>,
 
-  /// Override configuration values.
+  /// Override configuration values. `@PATH` loads the overrides of a `.json`
+  /// or `.toml` file.
   #[arg(long = "set", value_name = "KEY=VALUE")]
   pub overrides: Vec<String>,
 
   /// Path to the unified configuration JSON file, or '-' to read from stdin.
   #[arg(long)]
   pub config: Option<PathBuf>,
+
+  /// Comma-separated allowlist of environment variables forwarded to child
+  /// processes. All other inherited variables are cleared.
+  #[arg(long, value_delimiter = ',', value_name = "VARS")]
+  pub env_passthrough: Option<Vec<String>>,
+
+  /// Log, at debug level, the exact environment of every generator and executor
+  /// process about to be spawned, after `--env-passthrough` and `--env`.
+  #[arg(long)]
+  pub dump_effective_env: bool,
+
+  /// Set an environment variable for every generator and executor, as
+  /// `KEY=VALUE`. Repeatable. Overrides the component's `run_env`.
+  #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+  pub env: Vec<(String, String)>,
+
+  /// Comma-separated tags. Only tasks whose executor carries at least one of
+  /// them are run.
+  #[arg(long, value_delimiter = ',', value_name = "TAGS")]
+  pub tag: Option<Vec<String>>,
+
+  /// Invoke the generator once per case id, seeding it with a hash of the id.
+  #[arg(long)]
+  pub seed_from_id: bool,
+
+  /// Comma-separated list of case ids for `--seed-from-id`.
+  #[arg(long, value_delimiter = ',', requires = "seed_from_id")]
+  pub ids: Option<Vec<String>>,
+
+  /// File containing one case id per line for `--seed-from-id`.
+  #[arg(long, conflicts_with = "ids", requires = "seed_from_id")]
+  pub ids_file: Option<PathBuf>,
+
+  /// Comma-separated input sizes. Every pipeline is run once per size, with the
+  /// size passed to the generator as `IMPALAB_SIZE` and recorded as the `size`
+  /// of its results.
+  #[arg(
+    long,
+    value_delimiter = ',',
+    value_name = "N,N,...",
+    conflicts_with = "input_files"
+  )]
+  pub sizes: Option<Vec<u64>>,
+
+  /// Write per-task aggregate statistics to this CSV file at the end of the run.
+  #[arg(long, value_name = "PATH")]
+  pub summary_csv: Option<PathBuf>,
+
+  /// Write per-task aggregate statistics to this file as a JSON array at the
+  /// end of the run.
+  #[arg(long, value_name = "PATH")]
+  pub summary_json: Option<PathBuf>,
+
+  /// Write the run's context (resolved generator and tasks, seeds, impalab
+  /// version, start and end times and host) to this JSON file at the end of
+  /// the run.
+  #[arg(long, value_name = "PATH")]
+  pub meta_file: Option<PathBuf>,
+
+  /// Print a table of per-task aggregate statistics to stderr at the end of the run.
+  #[arg(long)]
+  pub print_summary: bool,
+
+  /// Emit only one `{"type":"summary",...}` object of aggregate statistics per
+  /// task at the end of the run, instead of the individual result rows.
+  /// Percentiles are approximate beyond 10000 metrics per task.
+  #[arg(long, conflicts_with = "output_file")]
+  pub only_summary: bool,
+
+  /// Maximum duration of each executor process (e.g. `500ms`, `2m`, `1h30m`).
+  #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+  pub timeout: Option<Duration>,
+
+  /// Maximum duration of each generator process.
+  #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+  pub generator_timeout: Option<Duration>,
+
+  /// Maximum wait for each line of executor output. An executor that stalls
+  /// for longer between lines is killed and the run fails.
+  #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+  pub line_timeout: Option<Duration>,
+
+  /// Maximum duration of the whole benchmark run.
+  #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+  pub total_timeout: Option<Duration>,
+
+  /// On a timeout, send SIGTERM and allow this long for a clean exit before
+  /// sending SIGKILL. Without it, timed-out processes are killed immediately.
+  #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+  pub kill_grace: Option<Duration>,
+
+  /// Drop result rows whose metric is below this threshold (in the metric's own unit).
+  #[arg(long, value_name = "N")]
+  pub min_metric: Option<f64>,
+
+  /// Stamp every result row with an `items_per_sec` metric, taking `metric` as
+  /// a duration in nanoseconds, and add it to the summaries. The item count
+  /// comes from a `#count=<N>` line in the executor's output (e.g. relayed from
+  /// the generator) or from `--item-count`.
+  #[arg(long)]
+  pub report_throughput: bool,
+
+  /// Number of items each result row processed, for `--report-throughput`.
+  /// A `#count=<N>` line in the executor's output overrides it for the rows
+  /// that follow.
+  #[arg(long, value_name = "N", requires = "report_throughput")]
+  pub item_count: Option<u64>,
+
+  /// Fail the run if no result rows were produced.
+  #[arg(long)]
+  pub fail_if_empty: bool,
+
+  /// Only log a generator or executor that exits with a non-zero status, and
+  /// keep its result rows, instead of failing the run.
+  #[arg(long)]
+  pub no_fail_on_error: bool,
+
+  /// Stamp every result with an `input_checksum` of the generator input it ran
+  /// on. Buffers each generator's complete output before starting the executor.
+  #[arg(long)]
+  pub checksum_input: bool,
+
+  /// Fail the run if tasks that should have shared a generator input received
+  /// different bytes, e.g. because the generator is not seed-deterministic.
+  /// Buffers each generator's complete output before starting the executor.
+  #[arg(long, conflicts_with = "generator_mix")]
+  pub verify_input_identical: bool,
+
+  /// Check every line of generator output against this JSON Schema before it
+  /// reaches an executor, failing the run on the first line that is not JSON or
+  /// does not match. Buffers each generator's complete output before starting
+  /// the executor.
+  #[cfg(feature = "jsonschema")]
+  #[arg(long, value_name = "PATH")]
+  pub validate_input_schema: Option<PathBuf>,
+
+  /// Stamp every result with `orchestrator_duration_ms`: the executor's wall
+  /// time from spawn to exit, as a cross-check of its self-reported metric.
+  /// Rows are held back until their executor exits.
+  #[arg(long)]
+  pub orchestrator_duration: bool,
+
+  /// On SIGTERM, stop the run and flush the results collected so far (closing a
+  /// `json` array or finishing the output file) before exiting. Unix only.
+  #[arg(long)]
+  pub output_flush_on_signal: bool,
+
+  /// Fail the run if an executor's teardown command fails, instead of only logging it.
+  #[arg(long)]
+  pub strict: bool,
+
+  /// Fail if the build manifest contains fields this version of `impa` does not
+  /// know, such as a misspelled key in a hand-edited manifest. By default they
+  /// are ignored.
+  #[arg(long)]
+  pub strict_manifest: bool,
+
+  /// Address-space limit for the generator process, in bytes (`K`, `M` and `G`
+  /// suffixes are accepted). Unix only.
+  #[arg(long, value_name = "BYTES", value_parser = parse_bytes)]
+  pub generator_max_mem: Option<u64>,
+
+  /// CPU-time limit for the generator process, in seconds. Unix only.
+  #[arg(long, value_name = "SECS")]
+  pub generator_max_cpu_secs: Option<u64>,
+
+  /// Relay generator output to executors in batches of this many newline-delimited
+  /// records, each batch followed by an empty line.
+  #[arg(long, value_name = "N")]
+  pub batch_records: Option<NonZeroUsize>,
+
+  /// Spawn a fresh generator for every repetition, with the seed advanced by the
+  /// repetition index. When false, the first repetition's generator output is
+  /// buffered and replayed unchanged to the remaining repetitions.
+  #[arg(long, value_name = "BOOL", default_value_t = false, action = clap::ArgAction::Set)]
+  pub regenerate_per_rep: bool,
+
+  /// Maximum number of pipelines of a repetition run at once. Pipelines that
+  /// share the machine can disturb each other's measurements.
+  #[arg(long, value_name = "N")]
+  pub jobs: Option<NonZeroUsize>,
+
+  /// Run this many repetitions at once, each with its own generator, so that
+  /// generator and executor work of different repetitions overlaps. Only takes
+  /// effect when repetitions do not replay a shared generator output, e.g. with
+  /// `--regenerate-per-rep`.
+  #[arg(long, value_name = "N")]
+  pub rep_concurrency: Option<NonZeroUsize>,
+
+  /// With `--rep-concurrency`, hold back each task and case id's rows until
+  /// those of its earlier repetitions have been written, so that they arrive in
+  /// `rep_index` order. Held rows are kept in memory and arrive later.
+  #[arg(long)]
+  pub ordered_per_key: bool,
+
+  /// Run the generator once per case and replay its output to every task,
+  /// rather than once per task, so that all tasks see byte-identical input.
+  #[arg(long, conflicts_with_all = ["regenerate_per_rep", "generator_mix", "input_files"])]
+  pub cache_generator: bool,
+
+  /// Also write each cached generator output to this directory, as
+  /// `<seed>.bin` (`<seed>_<size>.bin` with `--sizes`), for inspection.
+  #[arg(long, value_name = "DIR", requires = "cache_generator")]
+  pub cache_generator_path: Option<PathBuf>,
+
+  /// Number of warmup pipelines run per task before measurement. Their output is
+  /// discarded.
+  #[arg(long, default_value_t = 0, value_name = "N")]
+  pub warmup: usize,
+
+  /// Generator seed for warmup pipelines: a number, or `random` for a fresh seed
+  /// per warmup. Defaults to the measurement seed.
+  #[arg(long, value_name = "SEED", requires = "warmup", value_parser = parse_warmup_seed)]
+  pub warmup_seed: Option<WarmupSeed>,
+
+  /// Number of times a failed pipeline is retried before the run fails.
+  #[arg(long, default_value_t = 0, value_name = "N")]
+  pub retries: usize,
+
+  /// Only retry failures whose generator or executor stderr matches this regex.
+  /// Other failures are treated as permanent.
+  #[arg(long, value_name = "REGEX", requires = "retries", value_parser = Regex::new)]
+  pub retry_if_stderr_matches: Option<Regex>,
+
+  /// After each pipeline, write a `{"type":"process_status",...}` line with the
+  /// exit codes of its executor and generator, even when the pipeline fails.
+  /// The lines go to stderr, or to `--process-status-file`, never among the
+  /// result rows.
+  #[arg(long)]
+  pub emit_process_status: bool,
+
+  /// Write the `--emit-process-status` lines to this file instead of stderr.
+  /// Implies `--emit-process-status`.
+  #[arg(long, value_name = "PATH")]
+  pub process_status_file: Option<PathBuf>,
+
+  /// Maximum number of lines written to stdout per second. Slower consumers
+  /// then apply backpressure to the executors instead of output piling up.
+  #[arg(long, value_name = "N")]
+  pub emit_rate: Option<NonZeroU32>,
+
+  /// Format of the result rows.
+  #[arg(long, value_enum, default_value_t)]
+  pub output_format: OutputFormat,
+
+  /// Line terminator of the JSON Lines, JSON and summary CSV output.
+  #[arg(long, value_enum, default_value_t)]
+  pub line_ending: LineEnding,
+
+  /// Format of the result lines printed by executors.
+  #[arg(long, value_enum, default_value_t)]
+  pub exec_output_format: ExecOutputFormat,
+
+  /// Maximum length of an executor output line, in bytes (`K`, `M` and `G`
+  /// suffixes are accepted). Longer lines fail the run. [default: 16M]
+  #[arg(long, value_name = "BYTES", value_parser = parse_bytes)]
+  pub max_line_length: Option<u64>,
+
+  /// File the results are written to. Required for binary output formats.
+  #[arg(long, value_name = "PATH", required_if_eq_any([("output_format", "parquet"), ("output_format", "msgpack"), ("output_format", "sqlite")]))]
+  pub output_file: Option<PathBuf>,
+
+  /// Checkpoint file recording completed pipelines. If it already exists, pipelines
+  /// it records are skipped, resuming an interrupted run.
+  #[arg(long, value_name = "PATH")]
+  pub resume: Option<PathBuf>,
+
+  /// Generator seed, overriding the one in the configuration. `--set generator.seed`
+  /// still takes precedence.
+  #[arg(long, env = "IMPA_SEED")]
+  pub seed: Option<u64>,
+
+  /// Run every pipeline with this many generator seeds: the run's seed and
+  /// seeds derived from it, so that a pinned `--seed` always yields the same
+  /// set. Result rows record the `seed` they ran on.
+  #[arg(
+    long,
+    value_name = "N",
+    conflicts_with_all = ["no_seed", "seed_from_id", "input_files"]
+  )]
+  pub seed_count: Option<NonZeroUsize>,
+
+  /// Don't pass `IMPALAB_SEED` to generators, leaving them to seed themselves.
+  #[arg(long, conflicts_with = "seed_from_id")]
+  pub no_seed: bool,
+
+  /// Make the result rows reproducible byte for byte, e.g. for golden tests,
+  /// provided the components are deterministic themselves. A generator seed
+  /// that is not configured defaults to 0 instead of a random one, and options
+  /// that add scheduling or timing variance are rejected.
+  #[arg(
+    long,
+    conflicts_with_all = ["no_seed", "jobs", "rep_concurrency", "orchestrator_duration"]
+  )]
+  pub deterministic: bool,
+
+  /// Print the generator seed of the run to stderr as a single `IMPA_SEED=<n>`
+  /// line, whether it was given or chosen at random, so that wrapper scripts
+  /// can capture it to reproduce the run.
+  #[arg(long, conflicts_with = "no_seed")]
+  pub echo_seed: bool,
+
+  /// Number of times each task is run, overriding the global `reps` of the
+  /// configuration. Tasks with their own `reps` keep them. `--set reps` still
+  /// takes precedence.
+  #[arg(long, value_name = "N")]
+  pub repetitions: Option<usize>,
+
+  /// Identifier of this run, recorded as the `run_id` attribute of every result row.
+  #[arg(long, env = "IMPA_RUN_ID", value_name = "ID")]
+  pub run_id: Option<String>,
+
+  /// Comma-separated `KEY=VALUE` labels, recorded as string attributes of every
+  /// result row.
+  #[arg(
+    long,
+    env = "IMPA_LABELS",
+    value_delimiter = ',',
+    value_name = "KEY=VALUE",
+    value_parser = parse_label
+  )]
+  pub labels: Vec<(String, String)>,
+
+  /// Pass each task's args to its executor as one argument joined by this
+  /// separator (e.g. `:`), instead of one argument each. Overrides
+  /// `args_separator` in the configuration. Only affects argv delivery.
+  #[arg(long, value_name = "SEP", value_parser = parse_args_separator)]
+  pub args_separator: Option<String>,
+
+  /// Pick each repetition's generator at random from weighted generators, e.g.
+  /// `gen-a:3,gen-b:1`. Picks are seeded by the generator seed, so a fixed
+  /// `--seed` reproduces them. The pick is recorded as the `generator` attribute.
+  #[arg(
+    long,
+    value_delimiter = ',',
+    value_name = "NAME:WEIGHT",
+    value_parser = parse_weighted_generator
+  )]
+  pub generator_mix: Vec<(String, NonZeroU32)>,
+
+  /// Expand glob patterns (e.g. `data/*.txt`) in the generator's args into the
+  /// sorted paths they match. Args without glob characters are passed unchanged.
+  #[arg(long)]
+  pub expand_arg_globs: bool,
+
+  /// Drop generator arg globs that match nothing instead of failing.
+  #[arg(long, requires = "expand_arg_globs")]
+  pub allow_empty_glob: bool,
+
+  /// Feed each file matching this glob (e.g. `data/*.bin`) to every task as its
+  /// stdin, instead of generator output. Results are tagged with the file path
+  /// as their `case_id`. `-` feeds the data piped to `impa` itself.
+  #[arg(
+    long,
+    alias = "input-file",
+    value_name = "GLOB",
+    conflicts_with_all = ["seed_from_id", "generator_mix"]
+  )]
+  pub input_files: Option<String>,
+
+  /// Show a progress bar of completed pipelines on stderr. Ignored when stderr
+  /// is not a terminal.
+  #[arg(long)]
+  pub progress: bool,
+
+  /// Show a live progress view on stderr while the run is in flight.
+  #[cfg(feature = "tui")]
+  #[arg(long, conflicts_with = "progress")]
+  pub tui: bool,
+}
+
+/// Format in which result rows are emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+  /// One JSON object per line on stdout.
+  #[default]
+  Jsonl,
+
+  /// A single JSON array on stdout, streamed element by element.
+  Json,
+
+  /// Comma-separated values on stdout, preceded by a single header row.
+  Csv,
+
+  /// One indented JSON object per row on stdout.
+  Pretty,
+
+  /// An Apache Parquet file with a fixed schema, written to `--output-file`.
+  #[cfg(feature = "parquet")]
+  Parquet,
+
+  /// Length-prefixed MessagePack frames, one per row, written to `--output-file`.
+  #[cfg(feature = "msgpack")]
+  Msgpack,
+
+  /// Rows appended to the `results` table of an SQLite database at
+  /// `--output-file`.
+  #[cfg(feature = "sqlite")]
+  Sqlite,
+}
+
+/// Line terminator of text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LineEnding {
+  /// `\n`, on every platform.
+  #[default]
+  Lf,
+
+  /// `\r\n`.
+  Crlf,
+}
+
+impl LineEnding {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      LineEnding::Lf => "\n",
+      LineEnding::Crlf => "\r\n",
+    }
+  }
+}
+
+/// Format of the result lines printed by executors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExecOutputFormat {
+  /// `metric|data_token[|exec_meta]`.
+  #[default]
+  Native,
+
+  /// Space-separated `key=value` pairs with required `metric` and
+  /// `data_token` keys. Other keys are collected into `exec_meta`.
+  Kv,
+}
+
+/// Generator seed used by warmup pipelines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmupSeed {
+  Fixed(u64),
+  /// A freshly drawn seed for every warmup pipeline.
+  Random,
+}
+
+/// Parses a warmup seed: either a `u64` or `random`.
+pub fn parse_warmup_seed(s: &str) -> Result<WarmupSeed, String> {
+  if s.eq_ignore_ascii_case("random") {
+    return Ok(WarmupSeed::Random);
+  }
+  s.parse()
+    .map(WarmupSeed::Fixed)
+    .map_err(|_| format!("invalid warmup seed '{}': expected a number or 'random'", s))
+}
+
+impl OutputFormat {
+  /// Whether rows are written to `--output-file` rather than streamed to stdout.
+  pub fn writes_to_file(self) -> bool {
+    match self {
+      OutputFormat::Jsonl | OutputFormat::Json | OutputFormat::Csv | OutputFormat::Pretty => false,
+      #[cfg(feature = "parquet")]
+      OutputFormat::Parquet => true,
+      #[cfg(feature = "msgpack")]
+      OutputFormat::Msgpack => true,
+      #[cfg(feature = "sqlite")]
+      OutputFormat::Sqlite => true,
+    }
+  }
+}
+
+/// Parses a byte count with an optional binary `K`, `M` or `G` suffix (e.g. `512M`).
+pub fn parse_bytes(s: &str) -> Result<u64, String> {
+  let (digits, multiplier) = match s.trim().to_ascii_uppercase() {
+    t if t.ends_with('K') => (t[..t.len() - 1].to_string(), 1u64 << 10),
+    t if t.ends_with('M') => (t[..t.len() - 1].to_string(), 1u64 << 20),
+    t if t.ends_with('G') => (t[..t.len() - 1].to_string(), 1u64 << 30),
+    t => (t, 1),
+  };
+  let value: u64 = digits
+    .parse()
+    .map_err(|_| format!("invalid byte count: '{}'", s))?;
+  value
+    .checked_mul(multiplier)
+    .filter(|v| *v > 0)
+    .ok_or_else(|| format!("byte count must be between 1 and {}", u64::MAX))
+}
+
+/// Parses a `KEY=VALUE` label with a non-empty key.
+pub fn parse_label(s: &str) -> Result<(String, String), String> {
+  match s.split_once('=') {
+    Some((key, value)) if !key.trim().is_empty() => {
+      Ok((key.trim().to_string(), value.trim().to_string()))
+    }
+    _ => Err(format!("invalid label '{}': expected KEY=VALUE", s)),
+  }
+}
+
+/// Parses a `KEY=VALUE` environment variable with a non-empty key. The value is
+/// kept verbatim.
+pub fn parse_env_var(s: &str) -> Result<(String, String), String> {
+  match s.split_once('=') {
+    Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+    _ => Err(format!(
+      "invalid environment variable '{}': expected KEY=VALUE",
+      s
+    )),
+  }
+}
+
+/// Parses a `NAME:WEIGHT` generator with a positive integer weight. The name may
+/// itself contain colons (e.g. `builtin:ints:2`).
+pub fn parse_weighted_generator(s: &str) -> Result<(String, NonZeroU32), String> {
+  let (name, weight) = s
+    .rsplit_once(':')
+    .filter(|(name, _)| !name.trim().is_empty())
+    .ok_or_else(|| format!("invalid weighted generator '{}': expected NAME:WEIGHT", s))?;
+  let weight = weight
+    .trim()
+    .parse()
+    .map_err(|_| format!("invalid weight '{}': expected a positive integer", weight))?;
+  Ok((name.trim().to_string(), weight))
+}
+
+/// Parses a non-empty task args separator.
+pub fn parse_args_separator(s: &str) -> Result<String, String> {
+  if s.is_empty() {
+    return Err("separator must not be empty".to_string());
+  }
+  Ok(s.to_string())
+}
+
+/// Parses a build profile name, which becomes part of the manifest filename
+/// and so may only contain ASCII letters, digits, `-` and `_`.
+pub fn parse_profile(s: &str) -> Result<String, String> {
+  if s.is_empty()
+    || !s
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+  {
+    return Err(format!(
+      "invalid profile '{}': expected ASCII letters, digits, '-' or '_'",
+      s
+    ));
+  }
+  Ok(s.to_string())
+}
+
+/// Parses a human-friendly, non-zero duration such as `1500ms`, `2m` or `1h30m`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+  let duration = humantime::parse_duration(s).map_err(|e| e.to_string())?;
+  if duration.is_zero() {
+    return Err("duration must be greater than zero".to_string());
+  }
+  Ok(duration)
 }
 
 #[derive(Debug, clap::Args, Default)]
@@ -84,13 +795,26 @@ pub struct ManifestArgs<F: FileReader + Default + std::fmt::Debug = RealFileSyst
   #[arg(id = "manifest-filename", long)]
   pub file_path: Option<PathBuf>,
 
+  /// Build profile, e.g. `release`. The manifest becomes
+  /// `impa_manifest.<PROFILE>.json`, so profiles do not overwrite each other.
+  #[arg(
+    long,
+    value_name = "PROFILE",
+    conflicts_with = "manifest-filename",
+    value_parser = parse_profile
+  )]
+  pub profile: Option<String>,
+
   #[arg(skip)]
   pub file_reader: F,
 }
 
 impl<F: FileReader + Default + std::fmt::Debug> ManifestArgs<F> {
   pub fn get_path(&self) -> PathBuf {
-    let default = PathBuf::from("impa_manifest.json");
+    let default = match &self.profile {
+      Some(profile) => PathBuf::from(format!("impa_manifest.{}.json", profile)),
+      None => PathBuf::from("impa_manifest.json"),
+    };
     self
       .root_dir
       .join(self.file_path.as_ref().unwrap_or(&default))
@@ -124,3 +848,89 @@ impl FileReader for RealFileSystem {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_duration() {
+    assert_eq!(parse_duration("1500ms"), Ok(Duration::from_millis(1500)));
+    assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+    assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+  }
+
+  #[test]
+  fn test_parse_profile() {
+    assert_eq!(parse_profile("release"), Ok("release".to_string()));
+    assert_eq!(parse_profile("opt_3-lto"), Ok("opt_3-lto".to_string()));
+    assert!(parse_profile("").is_err());
+    assert!(parse_profile("../release").is_err());
+    assert!(parse_profile("a.b").is_err());
+  }
+
+  #[test]
+  fn test_parse_bytes() {
+    assert_eq!(parse_bytes("4096"), Ok(4096));
+    assert_eq!(parse_bytes("64k"), Ok(64 * 1024));
+    assert_eq!(parse_bytes("512M"), Ok(512 * 1024 * 1024));
+    assert!(parse_bytes("0").is_err());
+    assert!(parse_bytes("lots").is_err());
+  }
+
+  #[test]
+  fn test_parse_warmup_seed() {
+    assert_eq!(parse_warmup_seed("7"), Ok(WarmupSeed::Fixed(7)));
+    assert_eq!(parse_warmup_seed("Random"), Ok(WarmupSeed::Random));
+    assert!(parse_warmup_seed("-1").is_err());
+    assert!(parse_warmup_seed("later").is_err());
+  }
+
+  #[test]
+  fn test_parse_label() {
+    assert_eq!(
+      parse_label("zone = eu-west"),
+      Ok(("zone".to_string(), "eu-west".to_string()))
+    );
+    assert_eq!(
+      parse_label("empty="),
+      Ok(("empty".to_string(), String::new()))
+    );
+    assert!(parse_label("=value").is_err());
+    assert!(parse_label("novalue").is_err());
+  }
+
+  #[test]
+  fn test_parse_env_var() {
+    assert_eq!(
+      parse_env_var("OPTS= -O2 =x"),
+      Ok(("OPTS".to_string(), " -O2 =x".to_string()))
+    );
+    assert!(parse_env_var("=value").is_err());
+    assert!(parse_env_var("novalue").is_err());
+  }
+
+  #[test]
+  fn test_parse_weighted_generator() {
+    let weight = |n| NonZeroU32::new(n).unwrap();
+    assert_eq!(
+      parse_weighted_generator("gen-a:3"),
+      Ok(("gen-a".to_string(), weight(3)))
+    );
+    assert_eq!(
+      parse_weighted_generator("builtin:ints:2"),
+      Ok(("builtin:ints".to_string(), weight(2)))
+    );
+    assert!(parse_weighted_generator("gen-a:0").is_err());
+    assert!(parse_weighted_generator("gen-a:-1").is_err());
+    assert!(parse_weighted_generator("gen-a").is_err());
+    assert!(parse_weighted_generator(":1").is_err());
+  }
+
+  #[test]
+  fn test_parse_duration_rejects_invalid() {
+    assert!(parse_duration("0s").is_err());
+    assert!(parse_duration("-5s").is_err());
+    assert!(parse_duration("soon").is_err());
+  }
+}
@@ -0,0 +1,188 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Apache Parquet output for large sweeps (requires the `parquet` cargo feature).
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use arrow_array::RecordBatch;
+use arrow_array::builder::Float64Builder;
+use arrow_array::builder::ListBuilder;
+use arrow_array::builder::StringBuilder;
+use arrow_array::builder::UInt64Builder;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use arrow_schema::SchemaRef;
+use parquet::arrow::ArrowWriter;
+
+use crate::benchmark::BufferedRows;
+use crate::error::BenchmarkError;
+use crate::output::Sorted;
+
+/// The fixed schema of the Parquet output.
+///
+/// Columns mirror the JSON result fields. Attributes and metadata objects are
+/// stored as JSON strings with sorted keys; optional fields are nullable.
+pub fn schema() -> SchemaRef {
+  Arc::new(Schema::new(vec![
+    Field::new("task_index", DataType::UInt64, false),
+    Field::new("executor", DataType::Utf8, false),
+    Field::new(
+      "args",
+      DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true))),
+      false,
+    ),
+    Field::new("rep_index", DataType::UInt64, false),
+    Field::new("case_id", DataType::Utf8, true),
+    Field::new("size", DataType::UInt64, true),
+    Field::new("seed", DataType::UInt64, true),
+    Field::new("attributes", DataType::Utf8, true),
+    Field::new("input_checksum", DataType::Utf8, true),
+    Field::new("orchestrator_duration_ms", DataType::Float64, true),
+    Field::new("data_token", DataType::Utf8, false),
+    Field::new("gen_meta", DataType::Utf8, true),
+    Field::new("exec_meta", DataType::Utf8, true),
+    Field::new("metric", DataType::Float64, true),
+    Field::new("metrics", DataType::Utf8, true),
+    Field::new("status", DataType::Utf8, false),
+  ]))
+}
+
+/// Writes result rows to a Parquet file, one record batch per pipeline.
+///
+/// The underlying writer flushes a row group whenever its buffer reaches the
+/// default row-group size, so memory stays bounded on long sweeps.
+pub struct ParquetSink {
+  path: PathBuf,
+  writer: ArrowWriter<File>,
+}
+
+impl ParquetSink {
+  pub fn create(path: &Path) -> Result<Self, BenchmarkError> {
+    let file = File::create(path).map_err(|e| BenchmarkError::CreateOutputFile {
+      path: path.to_path_buf(),
+      source: e,
+    })?;
+    let writer = ArrowWriter::try_new(file, schema(), None).map_err(|e| write_err(path, e))?;
+    Ok(Self {
+      path: path.to_path_buf(),
+      writer,
+    })
+  }
+
+  pub(crate) fn write(
+    &mut self,
+    BufferedRows { meta, rows }: &BufferedRows,
+  ) -> Result<(), BenchmarkError> {
+    if rows.is_empty() {
+      return Ok(());
+    }
+
+    let json = |value: &serde_json::Value| {
+      serde_json::to_string(&Sorted(value)).map_err(BenchmarkError::SerializeResult)
+    };
+    let attributes = if meta.attributes.is_empty() {
+      None
+    } else {
+      Some(json(&serde_json::Value::Object(meta.attributes.clone()))?)
+    };
+
+    let mut task_index = UInt64Builder::new();
+    let mut executor = StringBuilder::new();
+    let mut args = ListBuilder::new(StringBuilder::new());
+    let mut rep_index = UInt64Builder::new();
+    let mut case_id = StringBuilder::new();
+    let mut size = UInt64Builder::new();
+    let mut seed = UInt64Builder::new();
+    let mut attrs = StringBuilder::new();
+    let mut input_checksum = StringBuilder::new();
+    let mut orchestrator_duration_ms = Float64Builder::new();
+    let mut data_token = StringBuilder::new();
+    let mut gen_meta = StringBuilder::new();
+    let mut exec_meta = StringBuilder::new();
+    let mut metric = Float64Builder::new();
+    let mut metrics = StringBuilder::new();
+    let mut status = StringBuilder::new();
+
+    for row in rows {
+      task_index.append_value(meta.task_index as u64);
+      executor.append_value(&meta.executor);
+      for arg in &meta.task_args {
+        args.values().append_value(arg);
+      }
+      args.append(true);
+      rep_index.append_value(meta.rep_index as u64);
+      case_id.append_option(meta.case_id.as_deref());
+      size.append_option(meta.size);
+      seed.append_option(meta.seed);
+      attrs.append_option(attributes.as_deref());
+      input_checksum.append_option(meta.input_checksum.as_deref());
+      orchestrator_duration_ms.append_option(meta.orchestrator_duration_ms);
+      data_token.append_value(&row.data_token);
+      gen_meta.append_option(row.gen_meta.as_ref().map(json).transpose()?);
+      exec_meta.append_option(row.exec_meta.as_ref().map(json).transpose()?);
+      metric.append_option(row.metric.as_ref().map(|m| m.as_f64().unwrap_or(f64::NAN)));
+      metrics.append_option(
+        (!row.metrics.is_empty())
+          .then(|| serde_json::to_string(&row.metrics).map_err(BenchmarkError::SerializeResult))
+          .transpose()?,
+      );
+      status.append_value(row.status.as_str());
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+      Arc::new(task_index.finish()),
+      Arc::new(executor.finish()),
+      Arc::new(args.finish()),
+      Arc::new(rep_index.finish()),
+      Arc::new(case_id.finish()),
+      Arc::new(size.finish()),
+      Arc::new(seed.finish()),
+      Arc::new(attrs.finish()),
+      Arc::new(input_checksum.finish()),
+      Arc::new(orchestrator_duration_ms.finish()),
+      Arc::new(data_token.finish()),
+      Arc::new(gen_meta.finish()),
+      Arc::new(exec_meta.finish()),
+      Arc::new(metric.finish()),
+      Arc::new(metrics.finish()),
+      Arc::new(status.finish()),
+    ];
+    let batch = RecordBatch::try_new(schema(), columns)
+      .map_err(|e| write_err(&self.path, parquet::errors::ParquetError::from(e)))?;
+    self
+      .writer
+      .write(&batch)
+      .map_err(|e| write_err(&self.path, e))
+  }
+
+  /// Flushes the remaining rows and writes the file footer.
+  pub fn finish(self) -> Result<(), BenchmarkError> {
+    self
+      .writer
+      .close()
+      .map(|_| ())
+      .map_err(|e| write_err(&self.path, e))
+  }
+}
+
+fn write_err(path: &Path, source: parquet::errors::ParquetError) -> BenchmarkError {
+  BenchmarkError::WriteParquet {
+    path: path.to_path_buf(),
+    source,
+  }
+}
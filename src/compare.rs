@@ -0,0 +1,340 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::CompareError;
+use crate::summary::MetricStats;
+
+/// Identifies the same measurement across two result files.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CompareKey {
+  pub executor: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub args: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub case_id: Option<String>,
+  /// The `--sizes` entry the row was measured at.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub size: Option<u64>,
+  pub data_token: String,
+}
+
+impl fmt::Display for CompareKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.executor)?;
+    for arg in &self.args {
+      write!(f, " {}", arg)?;
+    }
+    if let Some(case_id) = &self.case_id {
+      write!(f, " [{}]", case_id)?;
+    }
+    if let Some(size) = self.size {
+      write!(f, " size={}", size)?;
+    }
+    write!(f, " @ {}", self.data_token)
+  }
+}
+
+/// The subset of a result row needed to compare runs.
+#[derive(Debug, Deserialize)]
+struct ResultRow {
+  executor: String,
+  #[serde(default)]
+  args: Vec<String>,
+  #[serde(default)]
+  case_id: Option<String>,
+  #[serde(default)]
+  size: Option<u64>,
+  data_token: String,
+  metric: f64,
+}
+
+/// Loads a JSON Lines results file, grouping metrics of all repetitions by key.
+///
+/// Objects carrying a `type` field, such as `{"type":"summary",...}` lines of
+/// `--only-summary`, are not result rows and are skipped, as are rows reporting
+/// a failed executor, whose `status` is not `"ok"`.
+pub fn load_results(path: &Path) -> Result<BTreeMap<CompareKey, Vec<f64>>, CompareError> {
+  let content = std::fs::read_to_string(path).map_err(|e| CompareError::ReadResults {
+    path: path.to_path_buf(),
+    source: e,
+  })?;
+
+  let mut grouped: BTreeMap<CompareKey, Vec<f64>> = BTreeMap::new();
+  for (idx, line) in content.lines().enumerate() {
+    if line.trim().is_empty() {
+      continue;
+    }
+    let parse_error = |e| CompareError::ParseResults {
+      path: path.to_path_buf(),
+      line: idx + 1,
+      source: e,
+    };
+    let value: serde_json::Value = serde_json::from_str(line).map_err(parse_error)?;
+    if value.get("type").is_some() || value.get("status").is_some_and(|s| s != "ok") {
+      continue;
+    }
+    let row: ResultRow = serde_json::from_value(value).map_err(parse_error)?;
+    grouped
+      .entry(CompareKey {
+        executor: row.executor,
+        args: row.args,
+        case_id: row.case_id,
+        size: row.size,
+        data_token: row.data_token,
+      })
+      .or_default()
+      .push(row.metric);
+  }
+  Ok(grouped)
+}
+
+/// How much the median must change for a key to count as an improvement or
+/// regression.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+  /// Relative change, in percent.
+  Percent(f64),
+  /// Absolute change, in nanoseconds.
+  Absolute(f64),
+}
+
+impl Threshold {
+  /// Whether the change from `old` to `new` reaches the threshold.
+  fn is_reached(self, old: f64, new: f64) -> bool {
+    match self {
+      // Any change from a zero baseline is unbounded in relative terms.
+      Threshold::Percent(_) if old == 0.0 => new != 0.0,
+      Threshold::Percent(pct) => ((new - old) / old * 100.0).abs() >= pct,
+      Threshold::Absolute(nanos) => (new - old).abs() >= nanos,
+    }
+  }
+}
+
+/// Parses a percentage (`5%`, or a bare `5`) or a duration (`200ns`, `1.5ms`).
+impl FromStr for Threshold {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    let percent = s.strip_suffix('%').unwrap_or(s);
+    if let Ok(pct) = percent.trim().parse::<f64>() {
+      return if pct.is_finite() && pct >= 0.0 {
+        Ok(Threshold::Percent(pct))
+      } else {
+        Err(format!(
+          "threshold must be a non-negative percentage: '{}'",
+          s
+        ))
+      };
+    }
+    humantime::parse_duration(s)
+      .map(|d| Threshold::Absolute(d.as_nanos() as f64))
+      .map_err(|_| {
+        format!(
+          "invalid threshold '{}': expected a percentage (e.g. 5%) or a duration (e.g. 200ns)",
+          s
+        )
+      })
+  }
+}
+
+impl fmt::Display for Threshold {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Threshold::Percent(pct) => write!(f, "{}%", pct),
+      Threshold::Absolute(nanos) => write!(
+        f,
+        "{}",
+        humantime::format_duration(Duration::from_nanos(*nanos as u64))
+      ),
+    }
+  }
+}
+
+/// Classification of a key present in both runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+  Improvement,
+  Regression,
+  Unchanged,
+}
+
+/// Comparison of the median metric of one key between two runs.
+///
+/// Metrics are treated as lower-is-better (e.g. durations).
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonEntry {
+  #[serde(flatten)]
+  pub key: CompareKey,
+  pub old_median: f64,
+  pub new_median: f64,
+  /// `old_median / new_median`; above 1 means the new run is faster. `None`
+  /// when the new median is 0.
+  pub speedup: Option<f64>,
+  /// Relative change of the median in percent; negative means the new run is
+  /// faster. `None` when the old median is 0.
+  pub change_pct: Option<f64>,
+  pub verdict: Verdict,
+}
+
+/// Counts of each verdict and of unmatched keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ComparisonSummary {
+  pub improvements: usize,
+  pub regressions: usize,
+  pub unchanged: usize,
+  pub only_old: usize,
+  pub only_new: usize,
+}
+
+/// The result of comparing two result files.
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+  /// Change of the median below which a difference counts as unchanged.
+  pub threshold: Threshold,
+  pub entries: Vec<ComparisonEntry>,
+  pub only_old: Vec<CompareKey>,
+  pub only_new: Vec<CompareKey>,
+  pub summary: ComparisonSummary,
+}
+
+impl Comparison {
+  /// Joins `old` and `new` on their keys and classifies each shared key.
+  pub fn new(
+    old: &BTreeMap<CompareKey, Vec<f64>>,
+    new: &BTreeMap<CompareKey, Vec<f64>>,
+    threshold: Threshold,
+  ) -> Self {
+    let mut entries = Vec::new();
+    let mut only_old = Vec::new();
+    let mut summary = ComparisonSummary::default();
+
+    for (key, old_samples) in old {
+      let Some(new_samples) = new.get(key) else {
+        only_old.push(key.clone());
+        continue;
+      };
+      let (Some(old_stats), Some(new_stats)) = (
+        MetricStats::from_samples(old_samples),
+        MetricStats::from_samples(new_samples),
+      ) else {
+        continue;
+      };
+
+      let (old_median, new_median) = (old_stats.median, new_stats.median);
+      let verdict = if !threshold.is_reached(old_median, new_median) {
+        summary.unchanged += 1;
+        Verdict::Unchanged
+      } else if new_median < old_median {
+        summary.improvements += 1;
+        Verdict::Improvement
+      } else {
+        summary.regressions += 1;
+        Verdict::Regression
+      };
+
+      entries.push(ComparisonEntry {
+        key: key.clone(),
+        old_median,
+        new_median,
+        speedup: (new_median != 0.0).then(|| old_median / new_median),
+        change_pct: (old_median != 0.0).then(|| (new_median - old_median) / old_median * 100.0),
+        verdict,
+      });
+    }
+
+    let only_new: Vec<CompareKey> = new
+      .keys()
+      .filter(|k| !old.contains_key(*k))
+      .cloned()
+      .collect();
+    summary.only_old = only_old.len();
+    summary.only_new = only_new.len();
+
+    Self {
+      threshold,
+      entries,
+      only_old,
+      only_new,
+      summary,
+    }
+  }
+}
+
+impl fmt::Display for Comparison {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let labels: Vec<String> = self.entries.iter().map(|e| e.key.to_string()).collect();
+    let width = labels.iter().map(String::len).max().unwrap_or(0).max(3);
+
+    writeln!(
+      f,
+      "{:<width$}  {:>12}  {:>12}  {:>8}  {:>9}  verdict",
+      "key", "old", "new", "speedup", "change"
+    )?;
+    for (entry, label) in self.entries.iter().zip(&labels) {
+      let verdict = match entry.verdict {
+        Verdict::Improvement => "improvement",
+        Verdict::Regression => "REGRESSION",
+        Verdict::Unchanged => "unchanged",
+      };
+      let speedup = entry
+        .speedup
+        .map_or_else(|| "n/a".to_string(), |s| format!("{:.2}x", s));
+      let change = entry
+        .change_pct
+        .map_or_else(|| "n/a".to_string(), |c| format!("{:+.1}%", c));
+      writeln!(
+        f,
+        "{:<width$}  {:>12.3}  {:>12.3}  {:>8}  {:>9}  {}",
+        label, entry.old_median, entry.new_median, speedup, change, verdict
+      )?;
+    }
+    for key in &self.only_old {
+      writeln!(f, "- {} (only in old)", key)?;
+    }
+    for key in &self.only_new {
+      writeln!(f, "+ {} (only in new)", key)?;
+    }
+
+    let s = &self.summary;
+    write!(
+      f,
+      "\n{} improvements, {} regressions, {} unchanged (threshold {}), {} only in old, {} only in new",
+      s.improvements, s.regressions, s.unchanged, self.threshold, s.only_old, s.only_new
+    )
+  }
+}
+
+/// Loads both files and compares them.
+pub fn compare_files(
+  old: &Path,
+  new: &Path,
+  threshold: Threshold,
+) -> Result<Comparison, CompareError> {
+  Ok(Comparison::new(
+    &load_results(old)?,
+    &load_results(new)?,
+    threshold,
+  ))
+}
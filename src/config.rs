@@ -11,30 +11,59 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::benchmark::ResultSender;
+use crate::builtin;
+use crate::builtin::BuiltinGenerator;
+use crate::cli::ExecOutputFormat;
+use crate::cli::LineEnding;
+use crate::cli::OutputFormat;
 use crate::cli::RunArgs;
+use crate::cli::WarmupSeed;
 use crate::error::ConfigError;
+use crate::manifest::ArgsDelivery;
+use crate::manifest::BuildManifest;
 use crate::manifest::CommandArgs;
 use crate::manifest::ComponentType;
 use crate::manifest::ManifestComponent;
+use crate::progress::ProgressSender;
 
 use crate::figment_ext::*;
 
 use serde::Deserialize;
 
+use regex::Regex;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::io::IsTerminal;
 use std::io::Read;
+use std::num::NonZeroU32;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Generator seed used by `--deterministic` runs that do not configure one.
+pub const DETERMINISTIC_SEED: u64 = 0;
 
 #[derive(Debug, Deserialize, Clone, Default)]
 struct RawConfig {
   generator: Option<RawGenerator>,
   tasks: Option<Vec<Task>>,
   #[serde(default)]
-  components: HashMap<String, ManifestComponent>,
+  components: BTreeMap<String, ManifestComponent>,
   reps: Option<usize>,
   #[serde(default)]
   attributes: serde_json::Map<String, serde_json::Value>,
+  /// Generator used when none is configured; recorded in the build manifest.
+  /// An empty name disables it.
+  default_generator: Option<String>,
+  /// Joins each task's args into a single executor argument.
+  args_separator: Option<String>,
+  /// Weighted generators from `--generator-mix`.
+  #[serde(skip)]
+  generator_mix: Vec<(String, NonZeroU32)>,
+  /// Seed of the generator mix from `--seed`. Falls back to `generator.seed`.
+  #[serde(skip)]
+  generator_mix_seed: Option<u64>,
 }
 
 impl RawConfig {
@@ -45,14 +74,16 @@ impl RawConfig {
     root_dir: &std::path::Path,
   ) -> Result<ManifestComponent, ConfigError> {
     let Some(cmp) = self.components.get(component_name) else {
+      let available: Vec<String> = self
+        .components
+        .iter()
+        .filter(|(_, c)| c.component_type == component_type)
+        .map(|(k, _)| k.to_owned())
+        .collect();
       return Err(ConfigError::ComponentNotFound {
         component_name: component_name.to_owned(),
-        available: self
-          .components
-          .iter()
-          .filter(|(_, c)| c.component_type == component_type)
-          .map(|(k, _)| k.to_owned())
-          .collect(),
+        suggestions: suggest_names(component_name, &available),
+        available,
       });
     };
 
@@ -73,27 +104,82 @@ impl RawConfig {
     if let Some(ref mut wd) = cmp.run.working_dir {
       *wd = root_dir.join(&wd);
     }
+    for hook in [&mut cmp.setup, &mut cmp.teardown] {
+      if let Some(wd) = hook.as_mut().and_then(|h| h.working_dir.as_mut()) {
+        *wd = root_dir.join(&wd);
+      }
+    }
 
     Ok(cmp)
   }
 
+  /// Resolves a generator from the manifest, or as a builtin generator when its
+  /// name carries the `builtin:` prefix.
+  fn resolve_generator(
+    &self,
+    generator_cfg: &RawGenerator,
+    root_dir: &std::path::Path,
+  ) -> Result<ResolvedGenerator, ConfigError> {
+    let seed = generator_cfg.seed.unwrap_or_else(rand::random);
+    tracing::info!(seed, generator = %generator_cfg.name, "Using generator seed");
+
+    if generator_cfg.name.starts_with(builtin::PREFIX) {
+      let builtin = BuiltinGenerator::parse(&generator_cfg.name, &generator_cfg.args)?;
+      return Ok(ResolvedGenerator {
+        name: generator_cfg.name.clone(),
+        seed,
+        command_args: CommandArgs {
+          command: PathBuf::from(&generator_cfg.name),
+          args: generator_cfg.args.clone(),
+          working_dir: None,
+        },
+        metadata_file: None,
+        run_env: BTreeMap::new(),
+        builtin: Some(builtin),
+      });
+    }
+
+    let mut cmp =
+      self.resolve_component(&generator_cfg.name, ComponentType::Generator, root_dir)?;
+    cmp.run.args.extend(generator_cfg.args.to_owned());
+    Ok(ResolvedGenerator {
+      name: generator_cfg.name.clone(),
+      seed,
+      command_args: cmp.run,
+      metadata_file: cmp.metadata_file,
+      run_env: cmp.run_env,
+      builtin: None,
+    })
+  }
+
   fn resolve_all(&self, root_dir: &std::path::Path) -> Result<ResolvedConfig, ConfigError> {
     let mut errors = Vec::new();
 
     let mut resolved_generator = None;
-    if let Some(generator_cfg) = self.generator.as_ref() {
-      match self.resolve_component(&generator_cfg.name, ComponentType::Generator, root_dir) {
-        Ok(mut cmp) => {
-          let seed = generator_cfg.seed.unwrap_or_else(rand::random);
-          tracing::info!(seed, "Using generator seed");
-          cmp.run.args.extend(generator_cfg.args.to_owned());
-          resolved_generator = Some(ResolvedGenerator {
-            name: generator_cfg.name.clone(),
-            seed,
-            command_args: cmp.run,
-          });
+    let mut generator_mix = Vec::new();
+    if self.generator_mix.is_empty() {
+      if let Some(generator_cfg) = self.generator.as_ref() {
+        match self.resolve_generator(generator_cfg, root_dir) {
+          Ok(generator) => resolved_generator = Some(generator),
+          Err(e) => errors.push(e),
+        }
+      }
+    } else {
+      // All mix members share one seed, which also drives the per-repetition pick.
+      let seed = self
+        .generator_mix_seed
+        .or_else(|| self.generator.as_ref().and_then(|g| g.seed))
+        .unwrap_or_else(rand::random);
+      for (name, weight) in &self.generator_mix {
+        let generator_cfg = RawGenerator {
+          name: name.clone(),
+          seed: Some(seed),
+          args: Vec::new(),
+        };
+        match self.resolve_generator(&generator_cfg, root_dir) {
+          Ok(generator) => generator_mix.push((generator, *weight)),
+          Err(e) => errors.push(e),
         }
-        Err(e) => errors.push(e),
       }
     }
 
@@ -102,7 +188,28 @@ impl RawConfig {
       for task in tasks {
         match self.resolve_component(&task.executor_name, ComponentType::Executor, root_dir) {
           Ok(mut cmp) => {
-            cmp.run.args.extend(task.args.clone());
+            if cmp.args_delivery == ArgsDelivery::Argv {
+              match &self.args_separator {
+                Some(separator) if !task.args.is_empty() => {
+                  cmp.run.args.push(task.args.join(separator))
+                }
+                _ => cmp.run.args.extend(task.args.clone()),
+              }
+            }
+
+            let stdin_prelude = match task.stdin_prelude.as_deref().map(|p| p.strip_prefix('@')) {
+              Some(Some(path)) => {
+                let path = root_dir.join(path);
+                match std::fs::read_to_string(&path) {
+                  Ok(prelude) => Some(prelude),
+                  Err(source) => {
+                    errors.push(ConfigError::ReadStdinPrelude { path, source });
+                    continue;
+                  }
+                }
+              }
+              _ => task.stdin_prelude.clone(),
+            };
 
             let effective_reps = task.reps.or(self.reps).unwrap_or(1);
 
@@ -128,6 +235,12 @@ impl RawConfig {
               executor: task.executor_name.clone(),
               args: task.args.clone(),
               command_args: cmp.run,
+              args_delivery: cmp.args_delivery,
+              config_template: cmp.config_template,
+              run_env: cmp.run_env,
+              setup: cmp.setup,
+              teardown: cmp.teardown,
+              stdin_prelude,
 
               effective_reps,
               effective_attributes,
@@ -144,7 +257,9 @@ impl RawConfig {
 
     Ok(ResolvedConfig {
       generator: resolved_generator,
+      generator_mix,
       tasks: resolved_tasks,
+      options: RunOptions::default(),
     })
   }
 }
@@ -160,6 +275,10 @@ pub struct Task {
   pub reps: Option<usize>,
   #[serde(default)]
   pub attributes: serde_json::Map<String, serde_json::Value>,
+
+  /// Written to the executor's stdin before the generator data: either literal
+  /// text, or `@<path>` to read it from a file relative to the root directory.
+  pub stdin_prelude: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -167,6 +286,12 @@ pub struct ResolvedTask {
   pub executor: String,
   pub args: Vec<String>,
   pub command_args: CommandArgs,
+  pub args_delivery: ArgsDelivery,
+  pub config_template: Option<PathBuf>,
+  pub run_env: BTreeMap<String, String>,
+  pub setup: Option<CommandArgs>,
+  pub teardown: Option<CommandArgs>,
+  pub stdin_prelude: Option<String>,
   pub effective_reps: usize,
   pub effective_attributes: serde_json::Map<String, serde_json::Value>,
 }
@@ -176,12 +301,222 @@ pub struct ResolvedGenerator {
   pub name: String,
   pub seed: u64,
   pub command_args: CommandArgs,
+  pub metadata_file: Option<String>,
+  pub run_env: BTreeMap<String, String>,
+  /// Set for `builtin:` generators, which run in-process instead of from `command_args`.
+  pub builtin: Option<BuiltinGenerator>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedConfig {
   pub generator: Option<ResolvedGenerator>,
+  /// Generators picked at random, by weight, for each repetition
+  /// (`--generator-mix`). When non-empty, `generator` is unused.
+  pub generator_mix: Vec<(ResolvedGenerator, NonZeroU32)>,
   pub tasks: Vec<ResolvedTask>,
+  pub options: RunOptions,
+}
+
+/// Run-time behaviour toggled from the command line rather than the configuration hierarchy.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+  /// Environment variables inherited by child processes. `None` inherits everything.
+  pub env_passthrough: Option<Vec<String>>,
+
+  /// Whether the environment of each spawned component is logged.
+  pub dump_effective_env: bool,
+
+  /// Case ids for per-id seeding. When set, the generator is invoked once per id
+  /// with a seed derived from that id.
+  pub seed_ids: Option<Vec<String>>,
+
+  /// Input files fed to every task in place of generator output, one pipeline
+  /// per file. Each path doubles as the case id of its results.
+  pub input_files: Option<Vec<String>>,
+
+  /// Input sizes the whole sweep is repeated for, passed to generators as
+  /// `IMPALAB_SIZE`.
+  pub sizes: Option<Vec<u64>>,
+
+  /// Destination for the per-task aggregate statistics CSV.
+  pub summary_csv: Option<PathBuf>,
+
+  /// Destination for the per-task aggregate statistics as JSON.
+  pub summary_json: Option<PathBuf>,
+
+  /// Destination for the run context sidecar (`--meta-file`).
+  pub meta_file: Option<PathBuf>,
+
+  /// Whether a table of per-task aggregate statistics is printed to stderr.
+  pub print_summary: bool,
+
+  /// Whether per-task summary objects are emitted instead of result rows.
+  pub only_summary: bool,
+
+  /// Maximum duration of each executor process.
+  pub timeout: Option<Duration>,
+
+  /// Maximum duration of each generator process.
+  pub generator_timeout: Option<Duration>,
+
+  /// Maximum wait for each line of executor output.
+  pub line_timeout: Option<Duration>,
+
+  /// Maximum duration of the whole run.
+  pub total_timeout: Option<Duration>,
+
+  /// Time a timed-out process is given to exit after SIGTERM before SIGKILL.
+  pub kill_grace: Option<Duration>,
+
+  /// Result rows whose metric is below this threshold are dropped.
+  pub min_metric: Option<f64>,
+
+  /// Stamp result rows with an `items_per_sec` metric (`--report-throughput`).
+  pub report_throughput: bool,
+
+  /// Items processed per result row, unless the executor reports a `#count=`.
+  pub item_count: Option<u64>,
+
+  /// Fail the run with [`BenchmarkError::NoResults`](crate::error::BenchmarkError::NoResults)
+  /// when no result rows were produced.
+  pub fail_if_empty: bool,
+
+  /// Whether non-zero exit statuses are only logged rather than failing the run.
+  pub no_fail_on_error: bool,
+
+  /// Whether results carry a checksum of their generator input.
+  pub checksum_input: bool,
+
+  /// Whether tasks sharing a case and seed must have received identical input.
+  pub verify_input_identical: bool,
+
+  /// Schema each line of generator output is validated against.
+  #[cfg(feature = "jsonschema")]
+  pub validate_input_schema: Option<std::sync::Arc<jsonschema::Validator>>,
+
+  /// Whether results carry the executor's wall time measured by the orchestrator.
+  pub orchestrator_duration: bool,
+
+  /// Whether SIGTERM stops the run after flushing the results collected so far.
+  pub output_flush_on_signal: bool,
+
+  /// Treat a failing teardown command as a run error rather than a warning.
+  pub strict: bool,
+
+  /// Resource limits applied to the generator process.
+  pub generator_limits: ResourceLimits,
+
+  /// Number of generator records relayed to the executor per batch.
+  pub batch_records: Option<NonZeroUsize>,
+
+  /// Whether every repetition spawns its own generator instead of replaying the
+  /// first repetition's output.
+  pub regenerate_per_rep: bool,
+
+  /// Maximum number of concurrently running pipelines. `None` runs one at a time.
+  pub jobs: Option<NonZeroUsize>,
+
+  /// Number of repetitions run at once. `None` runs them one after another.
+  pub rep_concurrency: Option<NonZeroUsize>,
+
+  /// Whether rows of concurrent repetitions are reordered by `rep_index`
+  /// within each task and case id.
+  pub ordered_per_key: bool,
+
+  /// Whether generator output is shared by all tasks rather than per task.
+  pub cache_generator: bool,
+
+  /// Directory the cached generator outputs are also written to.
+  pub cache_generator_path: Option<PathBuf>,
+
+  /// Number of discarded warmup pipelines run per task before measurement.
+  pub warmup: usize,
+
+  /// Generator seed for warmup pipelines. `None` reuses the measurement seed.
+  pub warmup_seed: Option<WarmupSeed>,
+
+  /// Whether generators are spawned without `IMPALAB_SEED`.
+  pub no_seed: bool,
+
+  /// Number of generator seeds every pipeline is run with, derived from the
+  /// generator's seed.
+  pub seed_count: Option<NonZeroUsize>,
+
+  /// Whether the resolved generator seed is printed to stderr.
+  pub echo_seed: bool,
+
+  /// Number of times a failed pipeline is retried.
+  pub retries: usize,
+
+  /// Restricts retries to failures whose stderr matches this pattern.
+  pub retry_if_stderr_matches: Option<Regex>,
+
+  /// Whether a process status line is written after each pipeline.
+  pub emit_process_status: bool,
+
+  /// File the process status lines are written to instead of stderr.
+  pub process_status_file: Option<PathBuf>,
+
+  /// Maximum number of lines written to stdout per second.
+  pub emit_rate: Option<NonZeroU32>,
+
+  /// Format of the result rows.
+  pub output_format: OutputFormat,
+
+  /// Line terminator of text output.
+  pub line_ending: LineEnding,
+
+  /// Format of executor result lines.
+  pub exec_output_format: ExecOutputFormat,
+
+  /// Maximum length of an executor output line, in bytes. Defaults to
+  /// [`DEFAULT_MAX_LINE_LENGTH`](crate::benchmark::DEFAULT_MAX_LINE_LENGTH).
+  pub max_line_length: Option<u64>,
+
+  /// File the results are written to, for file-based output formats.
+  pub output_file: Option<PathBuf>,
+
+  /// Checkpoint file used to skip already-completed pipelines.
+  pub resume: Option<PathBuf>,
+
+  /// Channel that receives progress events while the run is in flight.
+  pub progress: Option<ProgressSender>,
+
+  /// Channel that receives every result row in place of stdout, as it is
+  /// parsed (see [`run_benchmarks_stream`]). File-based output formats still
+  /// write to `output_file`.
+  ///
+  /// [`run_benchmarks_stream`]: crate::benchmark::run_benchmarks_stream
+  pub results: Option<ResultSender>,
+}
+
+/// OS resource limits applied to a child process via `setrlimit` (Unix only).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+  /// Maximum address-space size, in bytes.
+  pub max_mem: Option<u64>,
+
+  /// Maximum CPU time, in seconds.
+  pub max_cpu_secs: Option<u64>,
+}
+
+impl ResourceLimits {
+  pub fn is_empty(&self) -> bool {
+    self.max_mem.is_none() && self.max_cpu_secs.is_none()
+  }
+}
+
+impl std::fmt::Display for ResourceLimits {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut parts = Vec::new();
+    if let Some(bytes) = self.max_mem {
+      parts.push(format!("max_mem={} bytes", bytes));
+    }
+    if let Some(secs) = self.max_cpu_secs {
+      parts.push(format!("max_cpu={}s", secs));
+    }
+    write!(f, "{}", parts.join(", "))
+  }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -281,9 +616,19 @@ impl RawConfig {
     // Top Layer
     figment = figment.merge(p_top);
 
-    let raw: RawConfig = figment
+    let mut raw: RawConfig = figment
       .extract()
       .map_err(|err| ConfigError::FigmentError(Box::new(err)))?;
+    if raw.generator.is_none()
+      && let Some(name) = raw.default_generator.take().filter(|n| !n.is_empty())
+    {
+      tracing::info!(generator = %name, "Using the manifest's default generator");
+      raw.generator = Some(RawGenerator {
+        name,
+        seed: None,
+        args: Vec::new(),
+      });
+    }
     Ok(raw)
   }
 }
@@ -308,30 +653,108 @@ fn merge_patch(target: &mut serde_json::Value, patch: serde_json::Value) {
   }
 }
 
-fn parse_cli_overrides(overrides: &[String]) -> Result<HashMap<String, String>, ConfigError> {
+/// Parses `--set` overrides into a map from dotted key to raw value. An
+/// override of the form `@PATH` loads every entry of a `.json` or `.toml` file
+/// of overrides instead.
+fn parse_cli_overrides<F: crate::cli::FileReader>(
+  overrides: &[String],
+  file_reader: &F,
+) -> Result<HashMap<String, String>, ConfigError> {
   let mut map = HashMap::new();
   for override_str in overrides {
+    if let Some(path) = override_str.strip_prefix('@') {
+      for (key, value) in read_overrides_file(std::path::Path::new(path), file_reader)? {
+        insert_override(&mut map, key, value)?;
+      }
+      continue;
+    }
+
     let (key, value) = override_str
       .split_once('=')
       .ok_or_else(|| ConfigError::InvalidOverrideFormat(override_str.to_string()))?;
+    insert_override(&mut map, key.to_string(), value.to_string())?;
+  }
+  Ok(map)
+}
 
-    if key.contains('[') || key.contains(']') {
-      return Err(ConfigError::ArrayOverrideNotSupported {
-        key: key.to_string(),
-      });
+fn insert_override(
+  map: &mut HashMap<String, String>,
+  key: String,
+  value: String,
+) -> Result<(), ConfigError> {
+  if key.contains('[') || key.contains(']') {
+    return Err(ConfigError::ArrayOverrideNotSupported { key });
+  }
+
+  for segment in key.split('.') {
+    if segment.parse::<usize>().is_ok() {
+      return Err(ConfigError::ArrayOverrideNotSupported { key });
     }
+  }
 
-    for segment in key.split('.') {
-      if segment.parse::<usize>().is_ok() {
-        return Err(ConfigError::ArrayOverrideNotSupported {
-          key: key.to_string(),
-        });
+  map.insert(key, value);
+  Ok(())
+}
+
+/// Reads a file of overrides, a JSON object or TOML table whose nested tables
+/// are flattened into dotted keys, e.g. `{"generator": {"seed": 42}}` into
+/// `generator.seed=42`. The format is chosen by the file extension.
+fn read_overrides_file<F: crate::cli::FileReader>(
+  path: &std::path::Path,
+  file_reader: &F,
+) -> Result<Vec<(String, String)>, ConfigError> {
+  let content = file_reader
+    .read_to_string(path)
+    .map_err(|e| ConfigError::ReadOverridesFile {
+      path: path.to_owned(),
+      source: e,
+    })?
+    .ok_or_else(|| ConfigError::OverridesFileNotFound {
+      path: path.to_owned(),
+    })?;
+
+  let parse_error = |reason: String| ConfigError::ParseOverridesFile {
+    path: path.to_owned(),
+    reason,
+  };
+  let table = match path.extension().and_then(|ext| ext.to_str()) {
+    Some("json") => serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+    Some("toml") => toml::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+    _ => return Err(parse_error("expected a .json or .toml file".to_string())),
+  };
+  let serde_json::Value::Object(table) = table else {
+    return Err(parse_error("expected a table of overrides".to_string()));
+  };
+
+  fn flatten(
+    prefix: &str,
+    table: serde_json::Map<String, serde_json::Value>,
+    overrides: &mut Vec<(String, String)>,
+  ) -> Result<(), String> {
+    for (key, value) in table {
+      let key = if prefix.is_empty() {
+        key
+      } else {
+        format!("{prefix}.{key}")
+      };
+      match value {
+        serde_json::Value::Object(table) => flatten(&key, table, overrides)?,
+        serde_json::Value::String(value) => overrides.push((key, value)),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => {
+          overrides.push((key, value.to_string()))
+        }
+        serde_json::Value::Array(_) => {
+          return Err(format!("arrays cannot be overridden, found one at '{key}'"));
+        }
+        serde_json::Value::Null => return Err(format!("'{key}' has no value")),
       }
     }
-
-    map.insert(key.to_string(), value.to_string());
+    Ok(())
   }
-  Ok(map)
+
+  let mut overrides = Vec::new();
+  flatten("", table, &mut overrides).map_err(parse_error)?;
+  Ok(overrides)
 }
 
 fn read_config_source<F: crate::cli::FileReader>(
@@ -360,6 +783,175 @@ fn read_config_source<F: crate::cli::FileReader>(
   Ok(None)
 }
 
+/// Reads and compiles the JSON Schema given by `--validate-input-schema`.
+#[cfg(feature = "jsonschema")]
+fn load_input_schema(
+  path: &std::path::Path,
+) -> Result<std::sync::Arc<jsonschema::Validator>, ConfigError> {
+  let invalid = |reason: String| ConfigError::InvalidInputSchema {
+    path: path.to_owned(),
+    reason,
+  };
+  let content = std::fs::read_to_string(path).map_err(|e| ConfigError::ReadInputSchema {
+    path: path.to_owned(),
+    source: e,
+  })?;
+  let schema: serde_json::Value =
+    serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+  jsonschema::validator_for(&schema)
+    .map(std::sync::Arc::new)
+    .map_err(|e| invalid(e.to_string()))
+}
+
+/// Reads one case id per line, ignoring blank lines.
+fn read_ids_file<F: crate::cli::FileReader>(
+  path: &std::path::Path,
+  file_reader: &F,
+) -> Result<Vec<String>, ConfigError> {
+  let content = file_reader
+    .read_to_string(path)
+    .map_err(|e| ConfigError::ReadIdsFile {
+      path: path.to_owned(),
+      source: e,
+    })?
+    .ok_or_else(|| ConfigError::ReadIdsFile {
+      path: path.to_owned(),
+      source: std::io::ErrorKind::NotFound.into(),
+    })?;
+
+  Ok(
+    content
+      .lines()
+      .map(str::trim)
+      .filter(|l| !l.is_empty())
+      .map(str::to_owned)
+      .collect(),
+  )
+}
+
+/// Replaces each arg containing glob characters with the sorted paths it
+/// matches. A glob matching nothing is an error unless `allow_empty` is set.
+fn expand_globs(args: &[String], allow_empty: bool) -> Result<Vec<String>, ConfigError> {
+  let mut expanded = Vec::with_capacity(args.len());
+  for arg in args {
+    if !arg.contains(['*', '?', '[']) {
+      expanded.push(arg.clone());
+      continue;
+    }
+
+    let paths = glob::glob(arg).map_err(|source| ConfigError::InvalidArgGlob {
+      pattern: arg.clone(),
+      source,
+    })?;
+    let mut matches = paths
+      .map(|p| p.map(|p| p.to_string_lossy().into_owned()))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|source| ConfigError::ExpandArgGlob {
+        pattern: arg.clone(),
+        source,
+      })?;
+    if matches.is_empty() && !allow_empty {
+      return Err(ConfigError::EmptyArgGlob {
+        pattern: arg.clone(),
+      });
+    }
+    matches.sort();
+    expanded.extend(matches);
+  }
+  Ok(expanded)
+}
+
+/// Expands the `--input-files` glob into the sorted paths it matches. `-`
+/// stands for stdin and is kept as is.
+fn expand_input_files(pattern: &str) -> Result<Vec<String>, ConfigError> {
+  if pattern == "-" {
+    return Ok(vec![pattern.to_string()]);
+  }
+  let paths = glob::glob(pattern).map_err(|source| ConfigError::InvalidInputGlob {
+    pattern: pattern.to_string(),
+    source,
+  })?;
+  let mut files = Vec::new();
+  for path in paths {
+    let path = path.map_err(|source| ConfigError::ExpandInputGlob {
+      pattern: pattern.to_string(),
+      source,
+    })?;
+    if path.is_file() {
+      files.push(path.to_string_lossy().into_owned());
+    }
+  }
+  if files.is_empty() {
+    return Err(ConfigError::NoInputFiles {
+      pattern: pattern.to_string(),
+    });
+  }
+  files.sort();
+  Ok(files)
+}
+
+/// Returns up to two names from `available` that are a likely misspelling of
+/// `name`, closest first. A name qualifies when its edit distance is at most a
+/// third of the length of `name` (but at least 1), so short names only match
+/// near-identical ones.
+fn suggest_names(name: &str, available: &[String]) -> Vec<String> {
+  let max_distance = (name.chars().count() / 3).max(1);
+  let mut candidates: Vec<(usize, &String)> = available
+    .iter()
+    .map(|candidate| (edit_distance(name, candidate), candidate))
+    .filter(|(distance, _)| *distance <= max_distance)
+    .collect();
+  candidates.sort();
+  candidates
+    .into_iter()
+    .take(2)
+    .map(|(_, candidate)| candidate.clone())
+    .collect()
+}
+
+/// Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  for (i, ca) in a.chars().enumerate() {
+    let mut current = vec![i + 1];
+    for (j, cb) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(ca != *cb);
+      current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+    }
+    previous = current;
+  }
+  previous[b.len()]
+}
+
+/// Replaces each `${VAR}` in a component name with the value `lookup` returns
+/// for `VAR`. Other `$` characters are kept as they are.
+fn interpolate_env(
+  name: &str,
+  lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ConfigError> {
+  let mut out = String::with_capacity(name.len());
+  let mut rest = name;
+  while let Some(start) = rest.find("${") {
+    out.push_str(&rest[..start]);
+    let after = &rest[start + 2..];
+    let end = after
+      .find('}')
+      .ok_or_else(|| ConfigError::UnterminatedEnvVar {
+        name: name.to_string(),
+      })?;
+    let var = &after[..end];
+    let value = lookup(var).ok_or_else(|| ConfigError::UnsetEnvVar {
+      var: var.to_string(),
+      name: name.to_string(),
+    })?;
+    out.push_str(&value);
+    rest = &after[end + 1..];
+  }
+  out.push_str(rest);
+  Ok(out)
+}
+
 impl TryFrom<RunArgs> for ResolvedConfig {
   type Error = ConfigError;
 
@@ -368,18 +960,324 @@ impl TryFrom<RunArgs> for ResolvedConfig {
       manifest,
       config,
       overrides,
+      env_passthrough,
+      dump_effective_env,
+      tag,
+      env,
+      seed_from_id,
+      ids,
+      ids_file,
+      summary_csv,
+      summary_json,
+      meta_file,
+      print_summary,
+      only_summary,
+      timeout,
+      generator_timeout,
+      line_timeout,
+      total_timeout,
+      kill_grace,
+      min_metric,
+      report_throughput,
+      item_count,
+      fail_if_empty,
+      no_fail_on_error,
+      checksum_input,
+      verify_input_identical,
+      #[cfg(feature = "jsonschema")]
+      validate_input_schema,
+      orchestrator_duration,
+      output_flush_on_signal,
+      strict,
+      strict_manifest,
+      generator_max_mem,
+      generator_max_cpu_secs,
+      batch_records,
+      regenerate_per_rep,
+      jobs,
+      rep_concurrency,
+      ordered_per_key,
+      cache_generator,
+      cache_generator_path,
+      warmup,
+      warmup_seed,
+      retries,
+      retry_if_stderr_matches,
+      emit_process_status,
+      process_status_file,
+      emit_rate,
+      output_format,
+      line_ending,
+      exec_output_format,
+      max_line_length,
+      output_file,
+      resume,
+      seed,
+      no_seed,
+      deterministic,
+      seed_count,
+      echo_seed,
+      repetitions,
+      run_id,
+      labels,
+      args_separator,
+      generator_mix,
+      expand_arg_globs,
+      allow_empty_glob,
+      input_files,
+      sizes,
+      progress: _,
+      #[cfg(feature = "tui")]
+        tui: _,
     }: RunArgs,
   ) -> Result<Self, Self::Error> {
-    let cli_overrides = parse_cli_overrides(&overrides)?;
+    if input_files.as_deref() == Some("-") && config.as_ref().is_some_and(|p| p.as_os_str() == "-")
+    {
+      return Err(ConfigError::StdinInputWithStdinConfig);
+    }
+    if !output_format.writes_to_file() && output_file.is_some() {
+      return Err(ConfigError::OutputFileRequiresFileFormat);
+    }
+    if output_format == OutputFormat::Csv && only_summary {
+      return Err(ConfigError::CsvOutputWithJsonLines);
+    }
+
+    let cli_overrides = parse_cli_overrides(&overrides, &manifest.file_reader)?;
     let config_src =
       read_config_source(config.as_ref(), &manifest.file_reader)?.map(ConfigSource::String);
 
-    let raw_config = RawConfig::build(
+    let overridden = |key: &str| cli_overrides.contains_key(key);
+    if no_seed && seed.is_some() {
+      tracing::warn!("--seed has no effect with --no-seed");
+    }
+    let seed = seed.filter(|_| !overridden("generator.seed"));
+    let repetitions = repetitions.filter(|_| !overridden("reps"));
+    let args_separator = args_separator.filter(|_| !overridden("args_separator"));
+    let mut env_attributes = Vec::new();
+    if let Some(run_id) = run_id {
+      env_attributes.push(("run_id".to_string(), run_id));
+    }
+    env_attributes.extend(labels);
+    env_attributes.retain(|(key, _)| !overridden(&format!("attributes.{}", key)));
+
+    // A zero-byte manifest is left behind by an interrupted build, and would
+    // otherwise surface as a JSON syntax error. One without components only
+    // matters once a component cannot be found.
+    let manifest_is_empty = match manifest.get_content()? {
+      Some(content) if content.trim().is_empty() => {
+        return Err(ConfigError::EmptyManifest {
+          path: manifest.get_path(),
+        });
+      }
+      Some(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(document) => {
+          if strict_manifest {
+            let fields = BuildManifest::unknown_fields(&document);
+            if !fields.is_empty() {
+              return Err(ConfigError::UnknownManifestFields {
+                path: manifest.get_path(),
+                fields,
+              });
+            }
+          }
+          document
+            .get("components")
+            .is_none_or(|c| c == &serde_json::json!({}))
+        }
+        Err(_) => false,
+      },
+      None => false,
+    };
+
+    let mut raw_config = RawConfig::build(
       ConfigSource::File(manifest.get_path()),
       config_src,
       cli_overrides,
     )?;
-    let resolved = raw_config.resolve_all(&manifest.root_dir)?;
+    // `--seed`, `--run-id`, `--labels` (or their `IMPA_*` environment variables),
+    // `--repetitions` and `--args-separator` sit between `--set` overrides and the
+    // configuration files.
+    if let Some(seed) = seed
+      && let Some(generator) = raw_config.generator.as_mut()
+    {
+      generator.seed = Some(seed);
+    }
+    if let Some(repetitions) = repetitions {
+      raw_config.reps = Some(repetitions);
+    }
+    raw_config.generator_mix = generator_mix;
+    raw_config.generator_mix_seed = seed;
+    // `--deterministic` pins the seed that would otherwise be picked at random.
+    if deterministic {
+      let generator_seed = raw_config
+        .generator
+        .as_mut()
+        .map(|g| *g.seed.get_or_insert(DETERMINISTIC_SEED));
+      raw_config
+        .generator_mix_seed
+        .get_or_insert(generator_seed.unwrap_or(DETERMINISTIC_SEED));
+    }
+    // Component names may reference environment variables, e.g. `${GEN_CHOICE}`,
+    // resolved before they are looked up in the manifest.
+    let env_var = |var: &str| std::env::var(var).ok();
+    if let Some(generator) = raw_config.generator.as_mut() {
+      generator.name = interpolate_env(&generator.name, env_var)?;
+    }
+    for (name, _) in &mut raw_config.generator_mix {
+      *name = interpolate_env(name, env_var)?;
+    }
+    for task in raw_config.tasks.iter_mut().flatten() {
+      task.executor_name = interpolate_env(&task.executor_name, env_var)?;
+    }
+    // `--tag` keeps only the tasks whose executor carries one of the tags.
+    if let Some(tags) = &tag
+      && let Some(tasks) = raw_config.tasks.as_mut()
+    {
+      let components = &raw_config.components;
+      tasks.retain(|task| match components.get(&task.executor_name) {
+        Some(component) if !component.tags.iter().any(|t| tags.contains(t)) => {
+          tracing::info!(executor = %task.executor_name, "Skipping task without a selected tag");
+          false
+        }
+        // Unknown executors are kept so that resolution reports them.
+        _ => true,
+      });
+    }
+    if let Some(separator) = args_separator {
+      raw_config.args_separator = Some(separator);
+    }
+    if raw_config.args_separator.as_deref() == Some("") {
+      return Err(ConfigError::EmptyArgsSeparator);
+    }
+    if expand_arg_globs && let Some(generator) = raw_config.generator.as_mut() {
+      generator.args = expand_globs(&generator.args, allow_empty_glob)?;
+    }
+    // Input files replace the generator entirely.
+    let input_files = input_files
+      .map(|pattern| expand_input_files(&pattern))
+      .transpose()?;
+    if input_files.is_some()
+      && let Some(generator) = raw_config.generator.take()
+    {
+      tracing::info!(generator = %generator.name, "Input files given. Not running the generator");
+    }
+    for (key, value) in env_attributes {
+      raw_config
+        .attributes
+        .insert(key, serde_json::Value::String(value));
+    }
+    let seed_ids = if seed_from_id {
+      let (ids, origin) = match (ids, ids_file) {
+        (Some(ids), _) => (
+          ids
+            .into_iter()
+            .map(|id| id.trim().to_owned())
+            .filter(|id| !id.is_empty())
+            .collect::<Vec<_>>(),
+          "`--ids`".to_string(),
+        ),
+        (None, Some(path)) => (
+          read_ids_file(&path, &manifest.file_reader)?,
+          format!("ids file {}", path.display()),
+        ),
+        (None, None) => return Err(ConfigError::MissingSeedIds),
+      };
+      // An empty list would otherwise run nothing and still succeed.
+      if ids.is_empty() {
+        return Err(ConfigError::EmptySeedIds { origin });
+      }
+      Some(ids)
+    } else {
+      None
+    };
+
+    let mut resolved = raw_config
+      .resolve_all(&manifest.root_dir)
+      .map_err(|e| match e {
+        ConfigError::GraphValidationFailed(errors)
+          if manifest_is_empty
+            && errors
+              .iter()
+              .any(|e| matches!(e, ConfigError::ComponentNotFound { .. })) =>
+        {
+          ConfigError::EmptyManifest {
+            path: manifest.get_path(),
+          }
+        }
+        e => e,
+      })?;
+    // `--env` variables override each component's `run_env`.
+    let generators = resolved
+      .generator
+      .iter_mut()
+      .chain(resolved.generator_mix.iter_mut().map(|(g, _)| g));
+    for run_env in generators
+      .map(|g| &mut g.run_env)
+      .chain(resolved.tasks.iter_mut().map(|t| &mut t.run_env))
+    {
+      run_env.extend(env.iter().cloned());
+    }
+    resolved.options = RunOptions {
+      env_passthrough,
+      dump_effective_env,
+      seed_ids,
+      input_files,
+      sizes,
+      summary_csv,
+      summary_json,
+      meta_file,
+      print_summary,
+      only_summary,
+      timeout,
+      generator_timeout,
+      line_timeout,
+      total_timeout,
+      kill_grace,
+      min_metric,
+      report_throughput,
+      item_count,
+      fail_if_empty,
+      no_fail_on_error,
+      checksum_input,
+      verify_input_identical,
+      #[cfg(feature = "jsonschema")]
+      validate_input_schema: validate_input_schema
+        .as_deref()
+        .map(load_input_schema)
+        .transpose()?,
+      orchestrator_duration,
+      output_flush_on_signal,
+      strict,
+      generator_limits: ResourceLimits {
+        max_mem: generator_max_mem,
+        max_cpu_secs: generator_max_cpu_secs,
+      },
+      batch_records,
+      regenerate_per_rep,
+      jobs,
+      rep_concurrency,
+      ordered_per_key,
+      cache_generator,
+      cache_generator_path,
+      warmup,
+      warmup_seed,
+      no_seed,
+      seed_count,
+      echo_seed,
+      retries,
+      retry_if_stderr_matches,
+      emit_process_status: emit_process_status || process_status_file.is_some(),
+      process_status_file,
+      emit_rate,
+      output_format,
+      line_ending,
+      exec_output_format,
+      max_line_length,
+      output_file,
+      resume,
+      progress: None,
+      results: None,
+    };
 
     Ok(resolved)
   }
@@ -388,6 +1286,7 @@ impl TryFrom<RunArgs> for ResolvedConfig {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::cli::RealFileSystem;
   use crate::manifest::CommandArgs;
   use serde_json::json;
 
@@ -397,7 +1296,7 @@ mod tests {
       "generator.seed=42".to_string(),
       "components.python.command=python3".to_string(),
     ];
-    let map = parse_cli_overrides(&overrides).unwrap();
+    let map = parse_cli_overrides(&overrides, &RealFileSystem).unwrap();
     assert_eq!(map.get("generator.seed").unwrap(), "42");
     assert_eq!(map.get("components.python.command").unwrap(), "python3");
   }
@@ -405,14 +1304,14 @@ mod tests {
   #[test]
   fn test_parse_cli_overrides_missing_equals() {
     let overrides = vec!["invalid_format".to_string()];
-    let res = parse_cli_overrides(&overrides);
+    let res = parse_cli_overrides(&overrides, &RealFileSystem);
     assert!(matches!(res, Err(ConfigError::InvalidOverrideFormat(_))));
   }
 
   #[test]
   fn test_parse_cli_overrides_array_bracket_ban() {
     let overrides = vec!["tasks[0].executor=foo".to_string()];
-    let res = parse_cli_overrides(&overrides);
+    let res = parse_cli_overrides(&overrides, &RealFileSystem);
     assert!(matches!(
       res,
       Err(ConfigError::ArrayOverrideNotSupported { .. })
@@ -422,13 +1321,88 @@ mod tests {
   #[test]
   fn test_parse_cli_overrides_numeric_segment_ban() {
     let overrides = vec!["tasks.0.executor=foo".to_string()];
-    let res = parse_cli_overrides(&overrides);
+    let res = parse_cli_overrides(&overrides, &RealFileSystem);
     assert!(matches!(
       res,
       Err(ConfigError::ArrayOverrideNotSupported { .. })
     ));
   }
 
+  #[test]
+  fn test_parse_cli_overrides_from_json_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("overrides.json");
+    std::fs::write(
+      &path,
+      r#"{"generator": {"seed": 42, "name": "py-gen"}, "components.python.command": "python3"}"#,
+    )
+    .unwrap();
+
+    let overrides = vec![
+      format!("@{}", path.display()),
+      "generator.seed=7".to_string(),
+    ];
+    let map = parse_cli_overrides(&overrides, &RealFileSystem).unwrap();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["generator.seed"], "7");
+    assert_eq!(map["generator.name"], "py-gen");
+    assert_eq!(map["components.python.command"], "python3");
+  }
+
+  #[test]
+  fn test_parse_cli_overrides_from_toml_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("overrides.toml");
+    std::fs::write(
+      &path,
+      "reps = 3\nattributes.threshold = 0.95\n\n[generator]\nname = \"py-gen\"\n",
+    )
+    .unwrap();
+
+    let overrides = vec![format!("@{}", path.display())];
+    let map = parse_cli_overrides(&overrides, &RealFileSystem).unwrap();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["reps"], "3");
+    assert_eq!(map["attributes.threshold"], "0.95");
+    assert_eq!(map["generator.name"], "py-gen");
+  }
+
+  #[test]
+  fn test_parse_cli_overrides_file_errors() {
+    let temp = tempfile::tempdir().unwrap();
+    let parse = |name: &str| {
+      let overrides = vec![format!("@{}", temp.path().join(name).display())];
+      parse_cli_overrides(&overrides, &RealFileSystem)
+    };
+
+    assert!(matches!(
+      parse("missing.toml"),
+      Err(ConfigError::OverridesFileNotFound { .. })
+    ));
+
+    std::fs::write(temp.path().join("broken.toml"), "reps = ").unwrap();
+    assert!(matches!(
+      parse("broken.toml"),
+      Err(ConfigError::ParseOverridesFile { .. })
+    ));
+
+    std::fs::write(temp.path().join("broken.json"), r#"{"reps": }"#).unwrap();
+    assert!(matches!(
+      parse("broken.json"),
+      Err(ConfigError::ParseOverridesFile { .. })
+    ));
+
+    std::fs::write(
+      temp.path().join("args.json"),
+      r#"{"generator": {"args": ["a"]}}"#,
+    )
+    .unwrap();
+    assert!(matches!(
+      parse("args.json"),
+      Err(ConfigError::ParseOverridesFile { .. })
+    ));
+  }
+
   #[test]
   fn test_raw_config_build_task_replacement() {
     let base = json!({
@@ -500,6 +1474,31 @@ mod tests {
     assert!(generator_cfg.args.is_empty());
   }
 
+  #[test]
+  fn test_raw_config_build_default_generator() {
+    let base = json!({ "default_generator": "gen_default" }).to_string();
+
+    let config =
+      RawConfig::build(ConfigSource::String(base.clone()), None, HashMap::new()).unwrap();
+    let generator_cfg = config.generator.unwrap();
+    assert_eq!(generator_cfg.name, "gen_default");
+    assert_eq!(generator_cfg.seed, None);
+
+    let mid = json!({ "generator": { "name": "gen_b" } }).to_string();
+    let config = RawConfig::build(
+      ConfigSource::String(base.clone()),
+      Some(ConfigSource::String(mid)),
+      HashMap::new(),
+    )
+    .unwrap();
+    assert_eq!(config.generator.unwrap().name, "gen_b");
+
+    let mut overrides = HashMap::new();
+    overrides.insert("default_generator".to_string(), "".to_string());
+    let config = RawConfig::build(ConfigSource::String(base), None, overrides).unwrap();
+    assert!(config.generator.is_none());
+  }
+
   #[test]
   fn test_raw_config_resolve_all_valid() {
     let raw = RawConfig {
@@ -513,9 +1512,10 @@ mod tests {
         args: vec!["run-this".to_string()],
         reps: None,
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
       components: {
-        let mut map = HashMap::new();
+        let mut map = BTreeMap::new();
         map.insert(
           "my-gen".to_string(),
           ManifestComponent {
@@ -525,6 +1525,14 @@ mod tests {
               args: vec![],
               working_dir: None,
             },
+            metadata_file: None,
+            args_delivery: ArgsDelivery::Argv,
+            config_template: None,
+            run_env: BTreeMap::new(),
+            setup: None,
+            teardown: None,
+            build_warnings: None,
+            tags: Vec::new(),
           },
         );
         map.insert(
@@ -536,6 +1544,14 @@ mod tests {
               command: PathBuf::from("exec-bin"),
               args: vec!["base-arg".to_string()],
             },
+            metadata_file: None,
+            args_delivery: ArgsDelivery::Argv,
+            config_template: None,
+            run_env: BTreeMap::new(),
+            setup: None,
+            teardown: None,
+            build_warnings: None,
+            tags: Vec::new(),
           },
         );
         map
@@ -566,8 +1582,9 @@ mod tests {
         args: vec![],
         reps: None,
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
-      components: HashMap::new(),
+      components: BTreeMap::new(),
       ..Default::default()
     };
 
@@ -580,9 +1597,87 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_suggest_names() {
+    assert_eq!(edit_distance("qucksort", "quicksort"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+
+    let available: Vec<String> = ["quicksort", "quicksort-v2", "mergesort", "heapsort"]
+      .iter()
+      .map(|s| s.to_string())
+      .collect();
+    assert_eq!(suggest_names("qucksort", &available), ["quicksort"]);
+    assert_eq!(
+      suggest_names("quicksort-v3", &available),
+      ["quicksort-v2", "quicksort"]
+    );
+    assert!(suggest_names("bubble", &available).is_empty());
+  }
+
+  #[test]
+  fn test_interpolate_env() {
+    let lookup = |var: &str| (var == "GEN_CHOICE").then(|| "gen-b".to_string());
+    assert_eq!(interpolate_env("${GEN_CHOICE}", lookup).unwrap(), "gen-b");
+    assert_eq!(
+      interpolate_env("pre-${GEN_CHOICE}-$x", lookup).unwrap(),
+      "pre-gen-b-$x"
+    );
+    assert_eq!(interpolate_env("plain", lookup).unwrap(), "plain");
+    assert!(matches!(
+      interpolate_env("${MISSING}", lookup),
+      Err(ConfigError::UnsetEnvVar { var, .. }) if var == "MISSING"
+    ));
+    assert!(matches!(
+      interpolate_env("${GEN_CHOICE", lookup),
+      Err(ConfigError::UnterminatedEnvVar { .. })
+    ));
+  }
+
+  #[test]
+  fn test_raw_config_missing_component_lists_sorted() {
+    let mut components = BTreeMap::new();
+    for name in ["zeta", "alpha", "mid"] {
+      components.insert(
+        name.to_string(),
+        ManifestComponent {
+          component_type: ComponentType::Executor,
+          run: CommandArgs {
+            command: PathBuf::from("bin"),
+            args: vec![],
+            working_dir: None,
+          },
+          metadata_file: None,
+          args_delivery: ArgsDelivery::Argv,
+          config_template: None,
+          run_env: BTreeMap::new(),
+          setup: None,
+          teardown: None,
+          build_warnings: None,
+          tags: Vec::new(),
+        },
+      );
+    }
+    let raw = RawConfig {
+      components,
+      ..Default::default()
+    };
+
+    match raw.resolve_component(
+      "missing",
+      ComponentType::Executor,
+      std::path::Path::new("."),
+    ) {
+      Err(ConfigError::ComponentNotFound { available, .. }) => {
+        assert_eq!(available, ["alpha", "mid", "zeta"]);
+      }
+      other => panic!("Expected ComponentNotFound, got {:?}", other),
+    }
+  }
+
   #[test]
   fn test_raw_config_resolve_all_type_mismatch() {
-    let mut components = HashMap::new();
+    let mut components = BTreeMap::new();
     components.insert(
       "not-an-executor".to_string(),
       ManifestComponent {
@@ -592,6 +1687,14 @@ mod tests {
           args: vec![],
           working_dir: None,
         },
+        metadata_file: None,
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        build_warnings: None,
+        tags: Vec::new(),
       },
     );
 
@@ -602,6 +1705,7 @@ mod tests {
         args: vec![],
         reps: None,
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
       components,
       ..Default::default()
@@ -621,7 +1725,7 @@ mod tests {
 
   #[test]
   fn test_raw_config_resolve_reps_fallback() {
-    let mut components = HashMap::new();
+    let mut components = BTreeMap::new();
     components.insert(
       "exec".to_string(),
       ManifestComponent {
@@ -631,6 +1735,14 @@ mod tests {
           args: vec![],
           working_dir: None,
         },
+        metadata_file: None,
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        build_warnings: None,
+        tags: Vec::new(),
       },
     );
 
@@ -642,6 +1754,7 @@ mod tests {
         args: vec![],
         reps: Some(10),
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
       components: components.clone(),
       ..Default::default()
@@ -657,6 +1770,7 @@ mod tests {
         args: vec![],
         reps: None,
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
       components: components.clone(),
       ..Default::default()
@@ -672,6 +1786,7 @@ mod tests {
         args: vec![],
         reps: None,
         attributes: serde_json::Map::new(),
+        stdin_prelude: None,
       }]),
       components: components.clone(),
       ..Default::default()
@@ -680,9 +1795,54 @@ mod tests {
     assert_eq!(resolved.tasks[0].effective_reps, 1);
   }
 
+  #[test]
+  fn test_raw_config_resolve_all_args_separator() {
+    let mut components = BTreeMap::new();
+    components.insert(
+      "exec".to_string(),
+      ManifestComponent {
+        component_type: ComponentType::Executor,
+        run: CommandArgs {
+          command: PathBuf::from("run"),
+          args: vec!["--base".to_string()],
+          working_dir: None,
+        },
+        metadata_file: None,
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        build_warnings: None,
+        tags: Vec::new(),
+      },
+    );
+    let task = |args: &[&str]| Task {
+      executor_name: "exec".to_string(),
+      args: args.iter().map(|a| a.to_string()).collect(),
+      reps: None,
+      attributes: serde_json::Map::new(),
+      stdin_prelude: None,
+    };
+    let raw = RawConfig {
+      tasks: Some(vec![task(&["sort", "search"]), task(&[])]),
+      components,
+      args_separator: Some(":".to_string()),
+      ..Default::default()
+    };
+
+    let resolved = raw.resolve_all(std::path::Path::new(".")).unwrap();
+    assert_eq!(
+      resolved.tasks[0].command_args.args,
+      ["--base", "sort:search"]
+    );
+    assert_eq!(resolved.tasks[0].args, ["sort", "search"]);
+    assert_eq!(resolved.tasks[1].command_args.args, ["--base"]);
+  }
+
   #[test]
   fn test_raw_config_resolve_attributes_merge() {
-    let mut components = HashMap::new();
+    let mut components = BTreeMap::new();
     components.insert(
       "exec".to_string(),
       ManifestComponent {
@@ -692,6 +1852,14 @@ mod tests {
           args: vec![],
           working_dir: None,
         },
+        metadata_file: None,
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        build_warnings: None,
+        tags: Vec::new(),
       },
     );
 
@@ -714,6 +1882,7 @@ mod tests {
         args: vec![],
         reps: None,
         attributes: task_attributes,
+        stdin_prelude: None,
       }]),
       components,
       ..Default::default()
@@ -736,7 +1905,7 @@ mod tests {
 
   #[test]
   fn test_resolve_reps_and_attributes() {
-    let mut components = HashMap::new();
+    let mut components = BTreeMap::new();
     components.insert(
       "my-exec".to_string(),
       ManifestComponent {
@@ -746,6 +1915,14 @@ mod tests {
           args: vec![],
           working_dir: None,
         },
+        metadata_file: None,
+        args_delivery: ArgsDelivery::Argv,
+        config_template: None,
+        run_env: BTreeMap::new(),
+        setup: None,
+        teardown: None,
+        build_warnings: None,
+        tags: Vec::new(),
       },
     );
 
@@ -775,15 +1952,21 @@ mod tests {
           args: vec![],
           reps: None,
           attributes: Default::default(),
+          stdin_prelude: None,
         },
         Task {
           executor_name: "my-exec".to_string(),
           args: vec![],
           reps: Some(10),
           attributes: task_attributes,
+          stdin_prelude: None,
         },
       ]),
       components,
+      default_generator: None,
+      args_separator: None,
+      generator_mix: Vec::new(),
+      generator_mix_seed: None,
     };
 
     let resolved = raw.resolve_all(std::path::Path::new(".")).unwrap();
@@ -830,6 +2013,28 @@ mod tests {
     assert_eq!(resolved.tasks[1].effective_attributes.len(), 4);
   }
 
+  #[test]
+  fn test_expand_globs() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["b.txt", "a.txt", "c.csv"] {
+      std::fs::write(dir.path().join(name), "").unwrap();
+    }
+    let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+
+    let args = vec!["--inputs".to_string(), path("*.txt")];
+    assert_eq!(
+      expand_globs(&args, false).unwrap(),
+      ["--inputs".to_string(), path("a.txt"), path("b.txt")]
+    );
+
+    let args = vec![path("*.json")];
+    assert!(matches!(
+      expand_globs(&args, false),
+      Err(ConfigError::EmptyArgGlob { .. })
+    ));
+    assert!(expand_globs(&args, true).unwrap().is_empty());
+  }
+
   #[test]
   fn test_single_override_parsing() {
     let mut overrides = HashMap::new();
@@ -0,0 +1,312 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The `impa doctor` environment report and `impa validate` manifest checks.
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::cli::FileReader;
+use crate::cli::ManifestArgs;
+use crate::error::ConfigError;
+use crate::list::load_manifest;
+use crate::manifest::BuildManifest;
+use crate::manifest::CommandArgs;
+
+/// Outcome of a single `impa doctor` check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+  pub ok: bool,
+  pub message: String,
+}
+
+/// Everything `impa doctor` found, in the order it was checked.
+#[derive(Debug, Default)]
+pub struct DoctorReport {
+  pub checks: Vec<Check>,
+  /// Logging and `IMPA_*` variables as seen by this process. `None` means unset.
+  pub env: Vec<(String, Option<String>)>,
+}
+
+impl DoctorReport {
+  /// Number of failed checks.
+  pub fn problems(&self) -> usize {
+    self.checks.iter().filter(|c| !c.ok).count()
+  }
+
+  fn pass(&mut self, message: String) {
+    self.checks.push(Check { ok: true, message });
+  }
+
+  fn fail(&mut self, message: String) {
+    self.checks.push(Check { ok: false, message });
+  }
+}
+
+impl fmt::Display for DoctorReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "Checks:")?;
+    for check in &self.checks {
+      let status = if check.ok { "ok  " } else { "FAIL" };
+      writeln!(f, "  [{}] {}", status, check.message)?;
+    }
+    writeln!(f, "Environment:")?;
+    for (key, value) in &self.env {
+      match value {
+        Some(value) => writeln!(f, "  {}={}", key, value)?,
+        None => writeln!(f, "  {} (unset)", key)?,
+      }
+    }
+    match self.problems() {
+      0 => write!(f, "No problems found."),
+      n => write!(f, "{} problem(s) found.", n),
+    }
+  }
+}
+
+/// Checks the components directories, the build manifest and every program it
+/// references. Nothing is built, run or written.
+pub fn diagnose<F: FileReader + Default + std::fmt::Debug>(
+  components_dirs: &[PathBuf],
+  manifest_args: &ManifestArgs<F>,
+) -> DoctorReport {
+  let mut report = DoctorReport::default();
+
+  for dir in components_dirs {
+    match std::fs::read_dir(dir) {
+      Ok(_) => report.pass(format!(
+        "Components directory is readable: {}",
+        dir.display()
+      )),
+      Err(e) => report.fail(format!(
+        "Components directory is not readable: {} ({})",
+        dir.display(),
+        e
+      )),
+    }
+  }
+
+  let manifest_path = manifest_args.get_path();
+  match manifest_args.get_content() {
+    Ok(Some(content)) => match serde_json::from_str::<BuildManifest>(&content) {
+      Ok(manifest) => {
+        report.pass(format!(
+          "Manifest is valid: {} ({} components)",
+          manifest_path.display(),
+          manifest.components.len()
+        ));
+        check_manifest(&mut report, &manifest, &manifest_args.root_dir);
+      }
+      Err(e) => report.fail(format!(
+        "Manifest is not valid JSON: {} ({})",
+        manifest_path.display(),
+        e
+      )),
+    },
+    Ok(None) => report.fail(format!(
+      "Manifest not found: {}. Run `impa build` first",
+      manifest_path.display()
+    )),
+    Err(e) => report.fail(format!("{} ({})", e, manifest_path.display())),
+  }
+
+  report.env = ["RUST_LOG", "BENCH_LOG_FILE"]
+    .into_iter()
+    .map(|key| (key.to_string(), std::env::var(key).ok()))
+    .collect();
+  let mut impa_vars: Vec<(String, Option<String>)> = std::env::vars()
+    .filter(|(key, _)| key.starts_with("IMPA_"))
+    .map(|(key, value)| (key, Some(value)))
+    .collect();
+  impa_vars.sort();
+  report.env.extend(impa_vars);
+
+  report
+}
+
+fn check_manifest(report: &mut DoctorReport, manifest: &BuildManifest, root_dir: &Path) {
+  for (name, component) in &manifest.components {
+    let commands = [
+      ("run", Some(&component.run)),
+      ("setup", component.setup.as_ref()),
+      ("teardown", component.teardown.as_ref()),
+    ];
+    for (stage, command_args) in commands {
+      let Some(command_args) = command_args else {
+        continue;
+      };
+      let working_dir = command_args
+        .working_dir
+        .as_ref()
+        .map(|dir| root_dir.join(dir));
+      if let Some(dir) = &working_dir
+        && !dir.is_dir()
+      {
+        report.fail(format!(
+          "{} ({}): working directory does not exist: {}",
+          name,
+          stage,
+          dir.display()
+        ));
+        continue;
+      }
+      match resolve_program(command_args, working_dir.as_deref()) {
+        Some(path) => report.pass(format!(
+          "{} ({}): {} resolves to {}",
+          name,
+          stage,
+          command_args.command.display(),
+          path.display()
+        )),
+        None => report.fail(format!(
+          "{} ({}): program not found or not executable: {}",
+          name,
+          stage,
+          command_args.command.display()
+        )),
+      }
+    }
+  }
+
+  if let Some(name) = &manifest.default_generator
+    && !manifest.components.contains_key(name)
+  {
+    report.fail(format!(
+      "Default generator '{}' is not a manifest component",
+      name
+    ));
+  }
+}
+
+/// Checks that every program the manifest references resolves to an executable,
+/// returning one message per problem. With `strict`, arguments that look like
+/// paths (containing a `/` but no whitespace or `{` placeholder) must exist too,
+/// relative to the component's working directory.
+pub fn validate<F: FileReader + Default + std::fmt::Debug>(
+  manifest_args: &ManifestArgs<F>,
+  strict: bool,
+) -> Result<Vec<String>, ConfigError> {
+  let manifest = load_manifest(manifest_args)?;
+  let mut report = DoctorReport::default();
+  check_manifest(&mut report, &manifest, &manifest_args.root_dir);
+  let mut problems: Vec<String> = report
+    .checks
+    .into_iter()
+    .filter(|c| !c.ok)
+    .map(|c| c.message)
+    .collect();
+
+  if strict {
+    for (name, component) in &manifest.components {
+      let commands = [
+        ("run", Some(&component.run)),
+        ("setup", component.setup.as_ref()),
+        ("teardown", component.teardown.as_ref()),
+      ];
+      for (stage, command_args) in commands {
+        let Some(command_args) = command_args else {
+          continue;
+        };
+        let working_dir = command_args.working_dir.as_ref().map_or_else(
+          || manifest_args.root_dir.clone(),
+          |dir| manifest_args.root_dir.join(dir),
+        );
+        for arg in command_args.args.iter().filter(|a| is_path_like(a)) {
+          if !working_dir.join(arg).exists() {
+            problems.push(format!(
+              "{} ({}): argument path does not exist: {}",
+              name, stage, arg
+            ));
+          }
+        }
+      }
+    }
+  }
+  Ok(problems)
+}
+
+fn is_path_like(arg: &str) -> bool {
+  arg.contains('/')
+    && !arg.starts_with('-')
+    && !arg.contains("://")
+    && !arg.contains('{')
+    && !arg.contains(char::is_whitespace)
+}
+
+/// Resolves the program of `command_args` the way it is spawned: paths relative
+/// to the working directory, bare names through `PATH`.
+pub fn resolve_program(command_args: &CommandArgs, working_dir: Option<&Path>) -> Option<PathBuf> {
+  let program = &command_args.command;
+  if program.components().count() > 1 || program.is_absolute() {
+    let path = match working_dir {
+      Some(dir) if program.is_relative() => dir.join(program),
+      _ => program.clone(),
+    };
+    return is_executable(&path).then_some(path);
+  }
+
+  std::env::split_paths(&std::env::var_os("PATH")?)
+    .map(|dir| dir.join(program))
+    .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+  use std::os::unix::fs::PermissionsExt;
+  path
+    .metadata()
+    .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+  path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn command(program: &str) -> CommandArgs {
+    CommandArgs {
+      command: PathBuf::from(program),
+      args: Vec::new(),
+      working_dir: None,
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_resolve_program() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let script = temp.path().join("run.sh");
+    std::fs::write(&script, "#!/bin/sh\n").unwrap();
+
+    // Relative paths resolve against the working directory, and must be executable.
+    assert_eq!(
+      resolve_program(&command("./run.sh"), Some(temp.path())),
+      None
+    );
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(
+      resolve_program(&command("./run.sh"), Some(temp.path())),
+      Some(temp.path().join("./run.sh"))
+    );
+
+    // Bare names are looked up on `PATH`.
+    assert!(resolve_program(&command("sh"), Some(temp.path())).is_some());
+    assert_eq!(resolve_program(&command("run.sh"), Some(temp.path())), None);
+  }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::manifest::ComponentType;
@@ -28,6 +29,21 @@ pub enum ImpalabError {
   #[error("Benchmark run failed")]
   Benchmark(#[from] BenchmarkError),
 
+  #[error("Comparison failed")]
+  Compare(#[from] CompareError),
+
+  #[error("Scaffolding failed")]
+  Scaffold(#[from] ScaffoldError),
+
+  #[error("impa doctor found {problems} problem(s)")]
+  Doctor { problems: usize },
+
+  #[error("impa validate found {problems} problem(s)")]
+  Validate { problems: usize },
+
+  #[error("impa compare found {regressions} regression(s)")]
+  Regression { regressions: usize },
+
   #[error("I/O error: {0}")]
   Io(#[from] std::io::Error),
 
@@ -59,6 +75,18 @@ pub enum BuildError {
     stderr: String,
   },
 
+  #[error("Build command for {component_name} timed out after {timeout:?}")]
+  BuildCommandTimedOut {
+    component_name: String,
+    timeout: Duration,
+  },
+
+  #[error("Build step for {component_name} succeeded but did not produce artifact: {artifact}")]
+  MissingArtifact {
+    component_name: String,
+    artifact: String,
+  },
+
   #[error("Failed to execute build command for {component_name}")]
   BuildCommandExecFailed {
     component_name: String,
@@ -84,6 +112,21 @@ pub enum BuildError {
 
   #[error("Build failed for component: {component_name}. Components should have unique names.")]
   DuplicateComponentName { component_name: String },
+
+  #[error("Component '{component_name}' is marked `default = true` but is not a generator")]
+  DefaultNotGenerator { component_name: String },
+
+  #[error("Both '{first}' and '{second}' are marked as the default generator")]
+  MultipleDefaultGenerators { first: String, second: String },
+
+  #[error("Failed to read existing manifest")]
+  ReadExistingManifest(#[from] ConfigError),
+
+  #[error("Failed to parse existing manifest")]
+  ParseExistingManifest(#[source] serde_json::Error),
+
+  #[error("Failed to watch the components directories")]
+  Watch(#[source] notify::Error),
 }
 
 /// Errors related to configuration resolution (src/config.rs).
@@ -99,12 +142,42 @@ pub enum ConfigError {
   #[error("Failed to parse manifest JSON")]
   ParseManifest(#[from] serde_json::Error),
 
+  #[error("Build manifest at {path} has unknown fields: {fields:?}")]
+  UnknownManifestFields { path: PathBuf, fields: Vec<String> },
+
+  #[error("Build manifest not found at {path}. Run `impa build` first")]
+  ManifestNotFound { path: PathBuf },
+
+  #[error(
+    "Build manifest at {path} is empty or lists no components. Run `impa build` to regenerate it"
+  )]
+  EmptyManifest { path: PathBuf },
+
   #[error("Array overrides are not supported. Found array index or brackets in key: '{key}'")]
   ArrayOverrideNotSupported { key: String },
 
   #[error("Invalid override format for '{0}'. Expected KEY=VALUE")]
   InvalidOverrideFormat(String),
 
+  #[error("Overrides file not found: {path}")]
+  OverridesFileNotFound { path: PathBuf },
+
+  #[error("Failed to read overrides file: {path}")]
+  ReadOverridesFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to parse overrides file {path}: {reason}")]
+  ParseOverridesFile { path: PathBuf, reason: String },
+
+  #[error("--output-file is only supported with a file-based --output-format such as parquet")]
+  OutputFileRequiresFileFormat,
+
+  #[error("--output-format csv cannot be combined with --only-summary")]
+  CsvOutputWithJsonLines,
+
   #[error("Expected configuration data on stdin but stdin is a terminal")]
   MissingStdinData,
 
@@ -137,13 +210,106 @@ pub enum ConfigError {
     component_type: ComponentType,
   },
 
-  #[error("Component '{component_name}' not found in manifest. Available: {available:?}.")]
+  #[error(
+    "Component '{component_name}' not found in manifest. {}",
+    not_found_hint(suggestions, available)
+  )]
   ComponentNotFound {
     component_name: String,
     available: Vec<String>,
+    /// The available names closest to `component_name`, most similar first.
+    suggestions: Vec<String>,
+  },
+
+  #[error("Unknown builtin generator '{name}'. Available: {available:?}.")]
+  UnknownBuiltinGenerator {
+    name: String,
+    available: Vec<String>,
+  },
+
+  #[error("Invalid arguments for builtin generator '{name}': {reason}")]
+  InvalidBuiltinGeneratorArgs { name: String, reason: String },
+
+  #[error("Failed to read stdin prelude file: {path}")]
+  ReadStdinPrelude {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
   },
 
-  #[error("Component resolution graph validation failed: {0:?}")]
+  #[error("`args_separator` must not be empty")]
+  EmptyArgsSeparator,
+
+  #[error("Invalid glob pattern in generator args: '{pattern}'")]
+  InvalidArgGlob {
+    pattern: String,
+    #[source]
+    source: glob::PatternError,
+  },
+
+  #[error("Failed to expand glob pattern in generator args: '{pattern}'")]
+  ExpandArgGlob {
+    pattern: String,
+    #[source]
+    source: glob::GlobError,
+  },
+
+  #[error("Glob pattern in generator args matched no files: '{pattern}'")]
+  EmptyArgGlob { pattern: String },
+
+  #[error("`--seed-from-id` requires case ids via `--ids` or `--ids-file`")]
+  MissingSeedIds,
+
+  #[error("`--seed-from-id` was given no case ids: {origin} is empty")]
+  EmptySeedIds { origin: String },
+
+  #[error("Invalid `--input-files` glob pattern: '{pattern}'")]
+  InvalidInputGlob {
+    pattern: String,
+    #[source]
+    source: glob::PatternError,
+  },
+
+  #[error("Failed to expand `--input-files` glob pattern: '{pattern}'")]
+  ExpandInputGlob {
+    pattern: String,
+    #[source]
+    source: glob::GlobError,
+  },
+
+  #[error("`--input-files` glob pattern matched no files: '{pattern}'")]
+  NoInputFiles { pattern: String },
+
+  #[error("`--input-files -` and `--config -` cannot both read from stdin")]
+  StdinInputWithStdinConfig,
+
+  #[error("Environment variable '{var}' referenced by component name '{name}' is not set")]
+  UnsetEnvVar { var: String, name: String },
+
+  #[error("Unterminated `${{` in component name '{name}'")]
+  UnterminatedEnvVar { name: String },
+
+  #[error("Failed to read input schema file: {path}")]
+  ReadInputSchema {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Invalid input schema {path}: {reason}")]
+  InvalidInputSchema { path: PathBuf, reason: String },
+
+  #[error("Failed to read ids file: {path}")]
+  ReadIdsFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error(
+    "Component resolution graph validation failed: {}",
+    .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+  )]
   GraphValidationFailed(Vec<ConfigError>),
 }
 
@@ -156,6 +322,36 @@ pub enum BenchmarkError {
   #[error("Failed to spawn generator")]
   SpawnGenerator(#[source] std::io::Error),
 
+  #[error(
+    "Program for component '{component}' not found: {program}. Check its `command`, its working directory and, for scripts, the interpreter in its `#!` line",
+    program = program.display()
+  )]
+  ProgramNotFound { component: String, program: PathBuf },
+
+  #[error(
+    "Permission denied running {program} for component '{component}'. Make it executable with `chmod +x {program}`",
+    program = program.display()
+  )]
+  PermissionDenied { component: String, program: PathBuf },
+
+  #[error(
+    "{program} for component '{component}' is not an executable format. Add a `#!` interpreter line or set `command` to its interpreter",
+    program = program.display()
+  )]
+  NotExecutable { component: String, program: PathBuf },
+
+  #[error("Failed to spawn setup command")]
+  SpawnSetup(#[source] std::io::Error),
+
+  #[error("Setup command for executor '{executor}' failed with exit code: {code:?}")]
+  SetupFailed { executor: String, code: Option<i32> },
+
+  #[error("Failed to spawn teardown command")]
+  SpawnTeardown(#[source] std::io::Error),
+
+  #[error("Teardown command for executor '{executor}' failed with exit code: {code:?}")]
+  TeardownFailed { executor: String, code: Option<i32> },
+
   #[error("Failed to take generator stdout pipe")]
   PipeGenStdout,
 
@@ -174,6 +370,20 @@ pub enum BenchmarkError {
   #[error("Failed to take executor stderr pipe")]
   PipeExecStderr,
 
+  #[error("Failed to take executor stdin pipe")]
+  PipeExecStdin,
+
+  #[error("Failed to write to executor stdin")]
+  WriteExecStdin(#[source] std::io::Error),
+
+  #[error(
+    "Executor '{executor}' exited without reading most of its input ({bytes_unconsumed} bytes unconsumed). Check that it reads from stdin"
+  )]
+  AlgorithmIgnoredInput {
+    executor: String,
+    bytes_unconsumed: u64,
+  },
+
   #[error("Failed to wait for child processes")]
   WaitChild(#[source] std::io::Error),
 
@@ -189,9 +399,15 @@ pub enum BenchmarkError {
   #[error("Executor stderr task failed")]
   ExecStderrTask(tokio::task::JoinError),
 
+  #[error("Executor stdin task failed")]
+  StdinTask(tokio::task::JoinError),
+
   #[error("Failed to read executor stdout")]
   ReadExecStdout(#[source] std::io::Error),
 
+  #[error("Executor output line exceeds the maximum length of {limit} bytes")]
+  LineTooLong { limit: u64 },
+
   #[error("Failed to serialize benchmark result")]
   SerializeResult(#[source] serde_json::Error),
 
@@ -212,9 +428,24 @@ pub enum BenchmarkError {
     source: Box<BenchmarkError>, // Wraps parsing errors
   },
 
+  #[error("Executor was {cause} mid-write; final output line is truncated: {line}")]
+  TruncatedFinalLine { line: String, cause: String },
+
   #[error("Expected at least 2 pipe-delimited parts, got {parts} for line: {line}")]
   PipeParts { parts: usize, line: String },
 
+  #[error("Expected a key=value pair, got '{token}'")]
+  KvPair { token: String },
+
+  #[error("Unterminated quoted value for key '{key}'")]
+  UnterminatedKvQuote { key: String },
+
+  #[error("Missing required key '{key}'")]
+  MissingKvKey { key: &'static str },
+
+  #[error("Malformed extra metric '{pair}'. Expected key=<finite number>")]
+  MalformedMetric { pair: String },
+
   #[error("Failed to parse metric '{metric}'")]
   ParseMetric {
     metric: String,
@@ -234,4 +465,208 @@ pub enum BenchmarkError {
 
   #[error("Executor process failed with exit code: {code:?}")]
   ExecutorProcessFailed { code: Option<i32> },
+
+  #[error(
+    "Generator process was terminated under resource limits ({limits}): exit code {code:?}, signal {signal:?}"
+  )]
+  GeneratorResourceLimit {
+    limits: String,
+    code: Option<i32>,
+    signal: Option<i32>,
+  },
+
+  #[error("Generator process timed out after {timeout:?}")]
+  GeneratorTimedOut { timeout: Duration },
+
+  #[error("Generator output line {line} does not match the input schema: {reason}: {record}")]
+  InputSchemaViolation {
+    line: usize,
+    record: String,
+    reason: String,
+  },
+
+  #[error("Executor process timed out after {timeout:?}")]
+  ExecutorTimedOut { timeout: Duration },
+
+  #[error("Executor produced no output line for {timeout:?}")]
+  LineTimedOut { timeout: Duration },
+
+  #[error("Benchmark run produced no result rows")]
+  NoResults,
+
+  #[error(
+    "Executors {executors:?} received different generator input for seed {seed}: {digests:?}"
+  )]
+  InputMismatch {
+    seed: u64,
+    executors: Vec<String>,
+    digests: Vec<String>,
+  },
+
+  #[error("Benchmark run exceeded its total timeout of {timeout:?}")]
+  TotalTimeout { timeout: Duration },
+
+  #[error("Benchmark run was terminated by SIGTERM")]
+  Terminated,
+
+  #[error("Failed to install the SIGTERM handler")]
+  InstallSignalHandler(#[source] std::io::Error),
+
+  #[error("Failed to read config template: {path}")]
+  ReadConfigTemplate {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to write rendered config file")]
+  WriteConfigFile(#[source] std::io::Error),
+
+  #[error("Failed to write summary file: {path}")]
+  WriteSummary {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to write cached generator output: {path}")]
+  WriteCachedInput {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to write meta file: {path}")]
+  WriteMetaFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to read input file: {path}")]
+  ReadInputFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to open checkpoint file: {path}")]
+  OpenCheckpoint {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to buffer generator output")]
+  BufferGeneratorOutput(#[source] std::io::Error),
+
+  #[error("Failed to drain generator output left unread by the executor")]
+  DrainGeneratorOutput(#[source] std::io::Error),
+
+  #[error("Failed to create output file: {path}")]
+  CreateOutputFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[cfg(feature = "parquet")]
+  #[error("Failed to write Parquet output: {path}")]
+  WriteParquet {
+    path: PathBuf,
+    #[source]
+    source: parquet::errors::ParquetError,
+  },
+
+  #[error("Failed to write output file: {path}")]
+  WriteOutputFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[cfg(feature = "msgpack")]
+  #[error("Failed to encode result row as MessagePack")]
+  EncodeMsgpack(#[source] rmp_serde::encode::Error),
+
+  #[cfg(feature = "sqlite")]
+  #[error("Failed to write SQLite output: {path}")]
+  WriteSqlite {
+    path: PathBuf,
+    #[source]
+    source: rusqlite::Error,
+  },
+
+  #[error("Failed to write checkpoint file: {path}")]
+  WriteCheckpoint {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to write process status file: {path}")]
+  WriteProcessStatus {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+}
+
+/// Errors related to comparing result files (src/compare.rs).
+#[derive(Error, Debug)]
+pub enum CompareError {
+  #[error("Failed to read results file: {path}")]
+  ReadResults {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Malformed result row at {path}:{line}")]
+  ParseResults {
+    path: PathBuf,
+    line: usize,
+    #[source]
+    source: serde_json::Error,
+  },
+
+  #[error("Failed to serialize comparison")]
+  Serialize(#[source] serde_json::Error),
+}
+
+/// Errors related to scaffolding new components (src/scaffold.rs).
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+  #[error("An impafile already exists at {path}")]
+  ImpafileExists { path: PathBuf },
+
+  #[error("Refusing to overwrite existing file: {path}")]
+  FileExists { path: PathBuf },
+
+  #[error("Cannot derive a component name from {path}. Pass --name")]
+  MissingName { path: PathBuf },
+
+  #[error("Failed to create component directory: {path}")]
+  CreateDir {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+
+  #[error("Failed to write file: {path}")]
+  WriteFile {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+}
+
+/// Suggests the closest names when there are any, since the full list of
+/// components can be long, and lists every available name otherwise.
+fn not_found_hint(suggestions: &[String], available: &[String]) -> String {
+  match suggestions {
+    [] => format!("Available: {:?}.", available),
+    [name] => format!("Did you mean '{}'?", name),
+    [first, second, ..] => format!("Did you mean '{}' or '{}'?", first, second),
+  }
 }
@@ -29,20 +29,54 @@
 //! * [`config`]: Handles parsing the `RunArgs` from the CLI, applying overrides,
 //!   and resolving all component paths from the manifest to create a `Config` struct.
 //! * [`benchmark`]: Contains the `run_benchmarks` function which executes the
-//!   generator and executor processes, handling `stdin`/`stdout` piping.
+//!   generator and executor processes, handling `stdin`/`stdout` piping, and
+//!   `run_benchmarks_collect`, `run_benchmarks_collect_with` and
+//!   `run_benchmarks_stream`, which return the results instead of printing them.
+//! * [`builtin`]: In-process generators selected with a `builtin:` generator name.
 //! * [`cli`]: Defines the `clap`-based command-line interface.
 //! * [`manifest`]: Defines the structure of the `impa_manifest.json` file and shared
 //!   [`CommandArgs`](manifest::CommandArgs) struct.
 //! * [`figment_ext`]: Provides extensions and custom providers for the `figment`
 //!   configuration library.
+//! * [`output`]: Serialization helpers that keep result rows byte-stable.
+//! * [`summary`]: Aggregates per-task metric statistics at the end of a run.
+//! * `columnar`: Apache Parquet result output (requires the `parquet` cargo feature).
+//! * `msgpack`: Length-prefixed MessagePack result output (requires the `msgpack`
+//!   cargo feature).
+//! * `sqlite`: SQLite result output (requires the `sqlite` cargo feature).
+//! * [`scaffold`]: Contains the `impa init` component scaffolding.
+//! * [`compare`]: Contains the `impa compare` analysis of two result files.
+//! * [`doctor`]: Contains the `impa doctor` environment report and the
+//!   `impa validate` manifest checks.
+//! * [`list`]: Contains the `impa list` overview of the build manifest.
+//! * [`checkpoint`]: Records completed work items so an interrupted run can be resumed.
+//! * [`progress`]: Progress events emitted while a run is in flight.
+//! * `tui`: Live terminal progress view (requires the `tui` cargo feature).
 //! * [`error`]: Defines the custom error types for the library.
 //! * [`logging`]: Provides the `setup_tracing` utility.
 
 pub mod benchmark;
 pub mod builder;
+pub mod builtin;
+pub mod checkpoint;
 pub mod cli;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod compare;
 pub mod config;
+pub mod doctor;
 pub mod error;
 pub mod figment_ext;
+pub mod list;
 pub mod logging;
 pub mod manifest;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod output;
+pub mod progress;
+pub mod scaffold;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod summary;
+#[cfg(feature = "tui")]
+pub mod tui;
@@ -0,0 +1,74 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The `impa list` overview of a build manifest.
+use std::fmt;
+
+use crate::cli::FileReader;
+use crate::cli::ManifestArgs;
+use crate::error::ConfigError;
+use crate::manifest::BuildManifest;
+use crate::manifest::ComponentType;
+
+/// Reads and parses the build manifest, failing if it does not exist.
+pub fn load_manifest<F: FileReader + Default + std::fmt::Debug>(
+  manifest_args: &ManifestArgs<F>,
+) -> Result<BuildManifest, ConfigError> {
+  let path = manifest_args.get_path();
+  match manifest_args.get_content()? {
+    None => Err(ConfigError::ManifestNotFound { path }),
+    Some(content) if content.trim().is_empty() => Err(ConfigError::EmptyManifest { path }),
+    Some(content) => Ok(serde_json::from_str(&content)?),
+  }
+}
+
+/// Human-readable table of a manifest's generators and executors.
+pub struct Listing<'a>(pub &'a BuildManifest);
+
+impl fmt::Display for Listing<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let manifest = self.0;
+    let width = manifest
+      .components
+      .keys()
+      .map(String::len)
+      .max()
+      .unwrap_or(0)
+      .max(4);
+
+    for (heading, component_type) in [
+      ("Generators", ComponentType::Generator),
+      ("Executors", ComponentType::Executor),
+    ] {
+      writeln!(f, "{}:", heading)?;
+      let mut components = manifest
+        .components
+        .iter()
+        .filter(|(_, c)| c.component_type == component_type)
+        .peekable();
+      if components.peek().is_none() {
+        writeln!(f, "  (none)")?;
+        continue;
+      }
+      writeln!(f, "  {:<width$}  command", "name")?;
+      for (name, component) in components {
+        write!(f, "  {:<width$}  {}", name, component.run.to_shell_string())?;
+        if manifest.default_generator.as_ref() == Some(name) {
+          write!(f, "  (default)")?;
+        }
+        writeln!(f)?;
+      }
+    }
+    write!(f, "{} component(s).", manifest.components.len())
+  }
+}
@@ -12,14 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use Commands::Build;
+use Commands::Compare;
+use Commands::Doctor;
+use Commands::Init;
+use Commands::List;
 use Commands::Run;
+use Commands::Validate;
 use anyhow::Result;
 use clap::Parser;
 use impalab::benchmark::run_benchmarks;
 use impalab::builder::build_components;
+use impalab::builder::watch_components;
 use impalab::cli::Cli;
 use impalab::cli::Commands;
+use impalab::cli::CompareArgs;
+use impalab::cli::DoctorArgs;
+use impalab::cli::InitArgs;
+use impalab::cli::ListArgs;
+use impalab::cli::RunArgs;
+use impalab::cli::ValidateArgs;
+use impalab::compare::compare_files;
+use impalab::config::ResolvedConfig;
+use impalab::doctor::diagnose;
+use impalab::doctor::validate;
+use impalab::error::ImpalabError;
+use impalab::list::Listing;
+use impalab::list::load_manifest;
 use impalab::logging::setup_tracing;
+use impalab::progress::ProgressReceiver;
+use impalab::scaffold::init_component;
+use std::io::IsTerminal;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -30,25 +52,165 @@ async fn main() -> Result<()> {
   let _enter = main_span.enter();
 
   match command {
+    Init(InitArgs {
+      dir,
+      component_type,
+      language,
+      name,
+    }) => {
+      for path in init_component(&dir, name.as_deref(), component_type, language)? {
+        println!("Created {}", path.display());
+      }
+    }
     Build {
       components_dir,
       manifest,
       filter_args,
+      diff_args,
+      build_timeout,
+      capture_build_warnings,
+      build_warning_pattern,
+      dry_run,
+      watch,
+      debounce,
     } => {
+      let warning_pattern = capture_build_warnings.then_some(&build_warning_pattern);
+      if watch {
+        watch_components(
+          components_dir,
+          manifest,
+          &filter_args,
+          build_timeout,
+          warning_pattern,
+          debounce,
+        )?;
+        return Ok(());
+      }
+
       tracing::info!("Starting Build Process...");
 
-      build_components(components_dir, manifest, &filter_args)?;
+      build_components(
+        components_dir,
+        manifest,
+        &filter_args,
+        &diff_args,
+        build_timeout,
+        warning_pattern,
+        dry_run,
+      )?;
 
       tracing::info!("Build Process Complete.");
     }
     Run(run_args) => {
       tracing::info!("Initializing Benchmark Run...");
 
-      run_benchmarks(run_args.try_into()?).await?;
+      #[cfg(feature = "tui")]
+      let tui = run_args.tui;
+      #[cfg(not(feature = "tui"))]
+      let tui = false;
+      let progress_bar = !tui && run_args.progress && {
+        let terminal = std::io::stderr().is_terminal();
+        if !terminal {
+          tracing::debug!("stderr is not a terminal. Progress bar disabled.");
+        }
+        terminal
+      };
+
+      if tui {
+        #[cfg(feature = "tui")]
+        run_with_progress(run_args, |events| async move {
+          Ok(impalab::tui::run(events).await?)
+        })
+        .await?;
+      } else if progress_bar {
+        run_with_progress(run_args, |events| async move {
+          impalab::progress::progress_bar(events).await;
+          Ok(())
+        })
+        .await?;
+      } else {
+        run_benchmarks(run_args.try_into()?).await?;
+      }
 
       tracing::info!("Benchmark Run Complete.");
     }
+    Compare(CompareArgs {
+      old,
+      new,
+      threshold,
+      json,
+      no_fail_on_regression,
+    }) => {
+      let comparison = compare_files(&old, &new, threshold)?;
+      if json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+      } else {
+        println!("{}", comparison);
+      }
+      let regressions = comparison.summary.regressions;
+      if !no_fail_on_regression && regressions > 0 {
+        return Err(ImpalabError::Regression { regressions }.into());
+      }
+    }
+    Doctor(DoctorArgs {
+      components_dir,
+      manifest,
+    }) => {
+      let report = diagnose(&components_dir, &manifest);
+      println!("{}", report);
+      if report.problems() > 0 {
+        return Err(
+          ImpalabError::Doctor {
+            problems: report.problems(),
+          }
+          .into(),
+        );
+      }
+    }
+    List(ListArgs { manifest, json }) => {
+      let manifest = load_manifest(&manifest)?;
+      if json {
+        println!("{}", serde_json::to_string_pretty(&manifest)?);
+      } else {
+        println!("{}", Listing(&manifest));
+      }
+    }
+    Validate(ValidateArgs { manifest, strict }) => {
+      let problems = validate(&manifest, strict)?;
+      for problem in &problems {
+        println!("{}", problem);
+      }
+      if !problems.is_empty() {
+        return Err(
+          ImpalabError::Validate {
+            problems: problems.len(),
+          }
+          .into(),
+        );
+      }
+      println!("Manifest is valid: {}", manifest.get_path().display());
+    }
   }
 
   Ok(())
 }
+
+/// Runs the benchmark while `view` renders its progress events. The view is
+/// awaited before the run's result is reported, so it can restore the terminal.
+async fn run_with_progress<V>(
+  run_args: RunArgs,
+  view: impl FnOnce(ProgressReceiver) -> V,
+) -> Result<()>
+where
+  V: Future<Output = Result<()>> + Send + 'static,
+{
+  let (progress, events) = impalab::progress::channel();
+  let mut config: ResolvedConfig = run_args.try_into()?;
+  config.options.progress = Some(progress);
+
+  let view = tokio::spawn(view(events));
+  let result = run_benchmarks(config).await;
+  view.await??;
+  result?;
+  Ok(())
+}
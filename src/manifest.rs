@@ -11,12 +11,14 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::BTreeMap;
+use std::fmt;
 use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum ComponentType {
   Generator,
@@ -30,6 +32,101 @@ pub struct ManifestComponent {
 
   #[serde(flatten)]
   pub run: CommandArgs,
+
+  /// Path template of a metadata sidecar file written by a generator alongside its
+  /// stdout stream. `{seed}` is replaced with the generator seed, and relative paths
+  /// resolve against the component's working directory.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub metadata_file: Option<String>,
+
+  /// How task arguments reach an executor.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "ArgsDelivery::is_argv")]
+  pub args_delivery: ArgsDelivery,
+
+  /// Path to a config file template rendered for every executor invocation. The
+  /// rendered file's path is substituted for `{config_path}` in the command's args.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub config_template: Option<PathBuf>,
+
+  /// Environment variables set on the component's process at run time (not while
+  /// building), e.g. `LD_LIBRARY_PATH` for a binary's shared libraries.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub run_env: BTreeMap<String, String>,
+
+  /// Command run once before an executor's first pipeline, e.g. to prime a cache
+  /// file. Its output is logged, not parsed as results.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub setup: Option<CommandArgs>,
+
+  /// Command run once after an executor's last pipeline, even if the run failed,
+  /// e.g. to remove temporary state. Failures are logged unless `--strict`.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub teardown: Option<CommandArgs>,
+
+  /// Warnings found in the output of the component's build step, recorded by
+  /// `impa build --capture-build-warnings`.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub build_warnings: Option<BuildWarnings>,
+
+  /// Labels that `--tag` selects components by, e.g. `fast` or `experimental`.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+}
+
+/// Build output lines that matched the warning pattern.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct BuildWarnings {
+  /// Number of matching lines across stdout and stderr.
+  pub count: usize,
+
+  /// The first few matching lines.
+  pub sample: Vec<String>,
+}
+
+impl BuildWarnings {
+  /// Maximum number of lines kept in `sample`.
+  pub const SAMPLE_SIZE: usize = 5;
+
+  /// Collects the lines of `output` that match `pattern`.
+  pub fn scan<'a>(output: impl IntoIterator<Item = &'a str>, pattern: &Regex) -> Self {
+    let mut warnings = Self::default();
+    for line in output.into_iter().flat_map(str::lines) {
+      if pattern.is_match(line) {
+        warnings.count += 1;
+        if warnings.sample.len() < Self::SAMPLE_SIZE {
+          warnings.sample.push(line.to_string());
+        }
+      }
+    }
+    warnings
+  }
+}
+
+/// Protocol used to hand task arguments to an executor.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgsDelivery {
+  /// Task arguments are appended to the executor's command line.
+  #[default]
+  Argv,
+
+  /// Task arguments are written as a single-line JSON array on the executor's
+  /// `stdin`, ahead of any generator data. Avoids command-line length limits.
+  StdinHeader,
+}
+
+impl ArgsDelivery {
+  fn is_argv(&self) -> bool {
+    *self == ArgsDelivery::Argv
+  }
 }
 
 /// Holds the executable command and base arguments for a component.
@@ -51,9 +148,366 @@ pub struct CommandArgs {
   pub working_dir: Option<PathBuf>,
 }
 
+impl CommandArgs {
+  /// Renders the command and its arguments as a single, copy-pasteable shell
+  /// command line. Words containing spaces or shell metacharacters are quoted.
+  ///
+  /// The working directory is not part of the rendered string.
+  pub fn to_shell_string(&self) -> String {
+    let command = self.command.to_string_lossy();
+    std::iter::once(command.as_ref())
+      .chain(self.args.iter().map(String::as_str))
+      .map(|word| match shlex::try_quote(word) {
+        Ok(quoted) => quoted.into_owned(),
+        // Only words containing a NUL byte cannot be quoted; no shell can pass them anyway.
+        Err(_) => format!("{:?}", word),
+      })
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+}
+
 /// Defines the structure of the `impa_manifest.json` file.
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct BuildManifest {
   /// A map of component names to their runnable `ManifestComponent`.
   pub components: BTreeMap<String, ManifestComponent>,
+
+  /// Generator used when the run configuration names none, taken from the
+  /// component marked `default = true` in its impafile.
+  #[serde(default)]
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub default_generator: Option<String>,
+}
+
+/// Top-level keys of `impa_manifest.json`.
+const MANIFEST_FIELDS: &[&str] = &["components", "default_generator"];
+
+/// Keys of a manifest component, including the flattened [`CommandArgs`] ones.
+const COMPONENT_FIELDS: &[&str] = &[
+  "type",
+  "command",
+  "args",
+  "working_dir",
+  "metadata_file",
+  "args_delivery",
+  "config_template",
+  "run_env",
+  "setup",
+  "teardown",
+  "build_warnings",
+  "tags",
+];
+
+/// Keys of a [`CommandArgs`] object.
+const COMMAND_FIELDS: &[&str] = &["command", "args", "working_dir"];
+
+/// Keys of a [`BuildWarnings`] object.
+const BUILD_WARNINGS_FIELDS: &[&str] = &["count", "sample"];
+
+impl BuildManifest {
+  /// Returns the keys of a manifest document that no manifest field reads, as
+  /// dotted paths such as `components.zig.comand`. Deserialization silently
+  /// ignores them, so `--strict-manifest` reports them instead.
+  pub fn unknown_fields(document: &serde_json::Value) -> Vec<String> {
+    fn collect(value: &serde_json::Value, known: &[&str], prefix: &str, unknown: &mut Vec<String>) {
+      if let Some(object) = value.as_object() {
+        unknown.extend(
+          object
+            .keys()
+            .filter(|k| !known.contains(&k.as_str()))
+            .map(|k| format!("{}{}", prefix, k)),
+        );
+      }
+    }
+
+    let mut unknown = Vec::new();
+    collect(document, MANIFEST_FIELDS, "", &mut unknown);
+    let components = document.get("components").and_then(|c| c.as_object());
+    for (name, component) in components.into_iter().flatten() {
+      let prefix = format!("components.{}.", name);
+      collect(component, COMPONENT_FIELDS, &prefix, &mut unknown);
+      for key in ["setup", "teardown"] {
+        if let Some(command) = component.get(key) {
+          collect(
+            command,
+            COMMAND_FIELDS,
+            &format!("{}{}.", prefix, key),
+            &mut unknown,
+          );
+        }
+      }
+      if let Some(warnings) = component.get("build_warnings") {
+        let prefix = format!("{}build_warnings.", prefix);
+        collect(warnings, BUILD_WARNINGS_FIELDS, &prefix, &mut unknown);
+      }
+    }
+    unknown
+  }
+
+  /// Computes the changes that turn `self` into `other`.
+  ///
+  /// Components are compared field by field on their serialized form, so every
+  /// manifest field (command, args, working directory, ...) is covered.
+  pub fn diff(&self, other: &BuildManifest) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+
+    for (name, before) in &self.components {
+      match other.components.get(name) {
+        None => diff.removed.push(name.clone()),
+        Some(after) => {
+          let fields = diff_fields(before, after);
+          if !fields.is_empty() {
+            diff.changed.push(ComponentChange {
+              name: name.clone(),
+              fields,
+            });
+          }
+        }
+      }
+    }
+
+    diff.added = other
+      .components
+      .keys()
+      .filter(|name| !self.components.contains_key(*name))
+      .cloned()
+      .collect();
+
+    if self.default_generator != other.default_generator {
+      diff.default_generator = Some(FieldChange {
+        field: "default_generator".to_string(),
+        before: self.default_generator.clone().map(Into::into),
+        after: other.default_generator.clone().map(Into::into),
+      });
+    }
+
+    diff
+  }
+}
+
+fn diff_fields(before: &ManifestComponent, after: &ManifestComponent) -> Vec<FieldChange> {
+  let to_map = |c: &ManifestComponent| match serde_json::to_value(c) {
+    Ok(serde_json::Value::Object(map)) => map,
+    _ => serde_json::Map::new(),
+  };
+  let (before, after) = (to_map(before), to_map(after));
+
+  let keys: std::collections::BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+  keys
+    .into_iter()
+    .filter(|k| before.get(*k) != after.get(*k))
+    .map(|k| FieldChange {
+      field: k.clone(),
+      before: before.get(k).cloned(),
+      after: after.get(k).cloned(),
+    })
+    .collect()
+}
+
+/// Differences between two build manifests, as computed by [`BuildManifest::diff`].
+#[derive(Debug, Default, PartialEq)]
+pub struct ManifestDiff {
+  pub added: Vec<String>,
+  pub removed: Vec<String>,
+  pub changed: Vec<ComponentChange>,
+  pub default_generator: Option<FieldChange>,
+}
+
+impl ManifestDiff {
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty()
+      && self.removed.is_empty()
+      && self.changed.is_empty()
+      && self.default_generator.is_none()
+  }
+}
+
+/// A component present in both manifests whose definition differs.
+#[derive(Debug, PartialEq)]
+pub struct ComponentChange {
+  pub name: String,
+  pub fields: Vec<FieldChange>,
+}
+
+/// A single differing manifest field. `None` means the field is absent.
+#[derive(Debug, PartialEq)]
+pub struct FieldChange {
+  pub field: String,
+  pub before: Option<serde_json::Value>,
+  pub after: Option<serde_json::Value>,
+}
+
+impl fmt::Display for ManifestDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.is_empty() {
+      return writeln!(f, "No manifest changes.");
+    }
+
+    let show = |v: &Option<serde_json::Value>| match v {
+      Some(v) => v.to_string(),
+      None => "<none>".to_string(),
+    };
+
+    for name in &self.added {
+      writeln!(f, "+ {}", name)?;
+    }
+    for name in &self.removed {
+      writeln!(f, "- {}", name)?;
+    }
+    for change in &self.changed {
+      writeln!(f, "~ {}", change.name)?;
+      for field in &change.fields {
+        writeln!(
+          f,
+          "    {}: {} -> {}",
+          field.field,
+          show(&field.before),
+          show(&field.after)
+        )?;
+      }
+    }
+    if let Some(field) = &self.default_generator {
+      writeln!(
+        f,
+        "~ {}: {} -> {}",
+        field.field,
+        show(&field.before),
+        show(&field.after)
+      )?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn component(command: &str, args: &[&str]) -> ManifestComponent {
+    ManifestComponent {
+      component_type: ComponentType::Executor,
+      run: CommandArgs {
+        command: PathBuf::from(command),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        working_dir: None,
+      },
+      metadata_file: None,
+      args_delivery: ArgsDelivery::Argv,
+      config_template: None,
+      run_env: BTreeMap::new(),
+      setup: None,
+      teardown: None,
+      build_warnings: None,
+      tags: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_command_args_to_shell_string() {
+    let plain = component("python3", &["run.py", "--fast"]).run;
+    assert_eq!(plain.to_shell_string(), "python3 run.py --fast");
+
+    let quoted = component(
+      "/opt/my tools/bench",
+      &["a b", "it's", r#"say "hi""#, "$HOME", ""],
+    )
+    .run;
+    assert_eq!(
+      quoted.to_shell_string(),
+      r#"'/opt/my tools/bench' 'a b' "it's" 'say "hi"' '$HOME' ''"#
+    );
+  }
+
+  #[test]
+  fn test_unknown_fields() {
+    // Every field of a fully populated manifest is known.
+    let command = CommandArgs {
+      command: PathBuf::from("bin"),
+      args: vec!["a".to_string()],
+      working_dir: Some(PathBuf::from("dir")),
+    };
+    let full = ManifestComponent {
+      metadata_file: Some("meta_{seed}.json".to_string()),
+      args_delivery: ArgsDelivery::StdinHeader,
+      config_template: Some(PathBuf::from("algo.toml.tmpl")),
+      run_env: BTreeMap::from([("KEY".to_string(), "value".to_string())]),
+      setup: Some(command.clone()),
+      teardown: Some(command.clone()),
+      build_warnings: Some(BuildWarnings {
+        count: 1,
+        sample: vec!["warning: x".to_string()],
+      }),
+      tags: vec!["fast".to_string()],
+      run: command,
+      ..component("bin", &[])
+    };
+    let manifest = BuildManifest {
+      components: BTreeMap::from([("exec".to_string(), full)]),
+      default_generator: Some("gen".to_string()),
+    };
+    let document = serde_json::to_value(&manifest).unwrap();
+    assert!(BuildManifest::unknown_fields(&document).is_empty());
+
+    let document = serde_json::json!({
+      "componets": {},
+      "components": {
+        "exec": {
+          "type": "executor",
+          "comand": "bin",
+          "command": "bin",
+          "setup": {"command": "bin", "workdir": "."}
+        }
+      }
+    });
+    assert_eq!(
+      BuildManifest::unknown_fields(&document),
+      [
+        "componets",
+        "components.exec.comand",
+        "components.exec.setup.workdir"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_manifest_diff() {
+    let mut old = BuildManifest::default();
+    old
+      .components
+      .insert("kept".into(), component("python3", &["a.py"]));
+    old
+      .components
+      .insert("changed".into(), component("python3", &["b.py"]));
+    old
+      .components
+      .insert("removed".into(), component("zig", &[]));
+
+    let mut new = BuildManifest::default();
+    new
+      .components
+      .insert("kept".into(), component("python3", &["a.py"]));
+    new
+      .components
+      .insert("changed".into(), component("python", &["b.py"]));
+    new
+      .components
+      .insert("added".into(), component("deno", &[]));
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, vec!["added"]);
+    assert_eq!(diff.removed, vec!["removed"]);
+    assert_eq!(
+      diff.changed,
+      vec![ComponentChange {
+        name: "changed".into(),
+        fields: vec![FieldChange {
+          field: "command".into(),
+          before: Some("python3".into()),
+          after: Some("python".into()),
+        }],
+      }]
+    );
+    assert!(old.diff(&old).is_empty());
+  }
 }
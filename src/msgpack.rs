@@ -0,0 +1,217 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Length-prefixed MessagePack output (requires the `msgpack` cargo feature).
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::benchmark::BufferedRows;
+use crate::benchmark::ResultStatus;
+use crate::error::BenchmarkError;
+
+/// A single result row as stored in a MessagePack frame.
+///
+/// Fields mirror the JSON result fields and are encoded as a map keyed by
+/// field name, so frames stay readable by generic MessagePack decoders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultRecord {
+  pub task_index: usize,
+  pub executor: String,
+  pub args: Vec<String>,
+  pub rep_index: usize,
+  pub case_id: Option<String>,
+  #[serde(default)]
+  pub size: Option<u64>,
+  #[serde(default)]
+  pub seed: Option<u64>,
+  pub attributes: serde_json::Map<String, serde_json::Value>,
+  #[serde(default)]
+  pub input_checksum: Option<String>,
+  #[serde(default)]
+  pub orchestrator_duration_ms: Option<f64>,
+  pub data_token: String,
+  pub gen_meta: Option<serde_json::Value>,
+  pub exec_meta: Option<serde_json::Value>,
+  #[serde(default)]
+  pub metric: Option<f64>,
+  #[serde(default)]
+  pub metrics: BTreeMap<String, f64>,
+  #[serde(default)]
+  pub status: ResultStatus,
+}
+
+/// Writes result rows to a file as a sequence of frames, each a 4-byte
+/// big-endian length followed by one MessagePack-encoded [`ResultRecord`].
+pub struct MsgpackSink {
+  path: PathBuf,
+  writer: BufWriter<File>,
+}
+
+impl MsgpackSink {
+  pub fn create(path: &Path) -> Result<Self, BenchmarkError> {
+    let file = File::create(path).map_err(|e| BenchmarkError::CreateOutputFile {
+      path: path.to_path_buf(),
+      source: e,
+    })?;
+    Ok(Self {
+      path: path.to_path_buf(),
+      writer: BufWriter::new(file),
+    })
+  }
+
+  pub(crate) fn write(
+    &mut self,
+    BufferedRows { meta, rows }: &BufferedRows,
+  ) -> Result<(), BenchmarkError> {
+    for row in rows {
+      let record = ResultRecord {
+        task_index: meta.task_index,
+        executor: meta.executor.clone(),
+        args: meta.task_args.clone(),
+        rep_index: meta.rep_index,
+        case_id: meta.case_id.clone(),
+        size: meta.size,
+        seed: meta.seed,
+        attributes: meta.attributes.clone(),
+        input_checksum: meta.input_checksum.clone(),
+        orchestrator_duration_ms: meta.orchestrator_duration_ms,
+        data_token: row.data_token.clone(),
+        gen_meta: row.gen_meta.clone(),
+        exec_meta: row.exec_meta.clone(),
+        metric: row.metric.as_ref().map(|m| m.as_f64().unwrap_or(f64::NAN)),
+        metrics: row.metrics.clone(),
+        status: row.status,
+      };
+      let frame = rmp_serde::to_vec_named(&record).map_err(BenchmarkError::EncodeMsgpack)?;
+      self
+        .writer
+        .write_all(&(frame.len() as u32).to_be_bytes())
+        .and_then(|_| self.writer.write_all(&frame))
+        .map_err(|e| self.write_err(e))?;
+    }
+    Ok(())
+  }
+
+  /// Flushes buffered frames to the file.
+  pub fn finish(mut self) -> Result<(), BenchmarkError> {
+    self.writer.flush().map_err(|e| self.write_err(e))
+  }
+
+  fn write_err(&self, source: std::io::Error) -> BenchmarkError {
+    BenchmarkError::WriteOutputFile {
+      path: self.path.clone(),
+      source,
+    }
+  }
+}
+
+/// Reads every frame written by [`MsgpackSink`].
+pub fn read_records<R: Read>(mut reader: R) -> std::io::Result<Vec<ResultRecord>> {
+  let mut records = Vec::new();
+  let mut len = [0u8; 4];
+  loop {
+    match reader.read_exact(&mut len) {
+      Ok(()) => {}
+      Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(records),
+      Err(e) => return Err(e),
+    }
+    let mut frame = vec![0u8; u32::from_be_bytes(len) as usize];
+    reader.read_exact(&mut frame)?;
+    let record = rmp_serde::from_slice(&frame)
+      .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    records.push(record);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::benchmark::BenchmarkMeta;
+  use crate::benchmark::BufferedRow;
+  use serde_json::json;
+
+  #[test]
+  fn test_frames_round_trip() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("results.msgpack");
+
+    let mut attributes = serde_json::Map::new();
+    attributes.insert("tier".to_string(), json!("high"));
+    let rows = BufferedRows {
+      meta: BenchmarkMeta {
+        task_index: 1,
+        executor: "py-sort".to_string(),
+        task_args: vec!["quick".to_string()],
+        rep_index: 2,
+        case_id: Some("alpha".to_string()),
+        size: Some(100),
+        seed: None,
+        attributes,
+        input_checksum: Some("00000000deadbeef".to_string()),
+        orchestrator_duration_ms: Some(1.5),
+      },
+      rows: vec![
+        BufferedRow {
+          data_token: "a".to_string(),
+          gen_meta: None,
+          exec_meta: Some(json!({"k": 1})),
+          metric: serde_json::Number::from_f64(10.5),
+          metrics: BTreeMap::from([("comparisons".to_string(), 3.0)]),
+          status: ResultStatus::Ok,
+        },
+        BufferedRow {
+          data_token: "b".to_string(),
+          gen_meta: Some(json!([1, 2])),
+          exec_meta: None,
+          metric: Some(20.into()),
+          metrics: BTreeMap::new(),
+          status: ResultStatus::Ok,
+        },
+        BufferedRow::failed(ResultStatus::Crashed),
+      ],
+    };
+
+    let mut sink = MsgpackSink::create(&path).unwrap();
+    sink.write(&rows).unwrap();
+    sink.finish().unwrap();
+
+    let records = read_records(File::open(&path).unwrap()).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].executor, "py-sort");
+    assert_eq!(records[0].args, ["quick"]);
+    assert_eq!(records[0].case_id.as_deref(), Some("alpha"));
+    assert_eq!(records[0].size, Some(100));
+    assert_eq!(records[0].attributes["tier"], "high");
+    assert_eq!(
+      records[0].input_checksum.as_deref(),
+      Some("00000000deadbeef")
+    );
+    assert_eq!(records[1].orchestrator_duration_ms, Some(1.5));
+    assert_eq!(records[0].exec_meta, Some(json!({"k": 1})));
+    assert_eq!(records[0].metrics["comparisons"], 3.0);
+    assert_eq!(records[1].data_token, "b");
+    assert_eq!(records[1].gen_meta, Some(json!([1, 2])));
+    assert_eq!(records[1].metric, Some(20.0));
+    assert_eq!(records[1].status, ResultStatus::Ok);
+    assert_eq!(records[2].metric, None);
+    assert_eq!(records[2].status, ResultStatus::Crashed);
+  }
+}
@@ -0,0 +1,254 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::cli::LineEnding;
+use crate::cli::OutputFormat;
+use crate::error::BenchmarkError;
+use serde::Serialize;
+use serde::Serializer;
+use serde::ser::SerializeMap;
+use serde::ser::SerializeSeq;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Interval;
+use tokio::time::MissedTickBehavior;
+
+/// Header row of `--output-format csv`, matching the columns of
+/// `ResultLine::to_csv`.
+pub(crate) const CSV_HEADER: &str = "task_index,executor,args,rep_index,case_id,size,\
+seed,attributes,input_checksum,orchestrator_duration_ms,data_token,gen_meta,exec_meta,metric,metrics,status";
+
+/// Writes serialized result lines to stdout, either one per line or as the
+/// elements of a single JSON array, optionally paced to a maximum rate.
+///
+/// The array is streamed: `[` precedes the first element, later elements are
+/// preceded by a comma, and [`StdoutWriter::finish`] closes it, so memory use
+/// does not grow with the number of rows. CSV output is likewise preceded by
+/// [`CSV_HEADER`] exactly once, however many pipelines write rows.
+#[derive(Debug, Clone)]
+pub(crate) struct StdoutWriter {
+  format: OutputFormat,
+  /// Tracks whether the first line has been written, which opens the JSON
+  /// array or emits the CSV header.
+  started: Arc<AtomicBool>,
+  /// Ticks once per permitted line when an emission rate is configured.
+  pace: Option<Arc<Mutex<Interval>>>,
+  /// Terminator written after every line.
+  line_ending: LineEnding,
+  /// Drops every line, e.g. for warmup pipelines.
+  discard: bool,
+}
+
+impl StdoutWriter {
+  /// Creates a writer for `format`. Must be called within a Tokio runtime.
+  pub(crate) fn new(
+    format: OutputFormat,
+    line_ending: LineEnding,
+    rate: Option<NonZeroU32>,
+  ) -> Self {
+    let pace = rate.map(|rate| {
+      let mut interval = tokio::time::interval(Duration::from_secs(1) / rate.get());
+      interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+      Arc::new(Mutex::new(interval))
+    });
+    StdoutWriter {
+      format,
+      started: Arc::default(),
+      pace,
+      line_ending,
+      discard: false,
+    }
+  }
+
+  /// Creates a writer that drops every line.
+  pub(crate) fn discard() -> Self {
+    StdoutWriter {
+      format: OutputFormat::default(),
+      started: Arc::default(),
+      pace: None,
+      line_ending: LineEnding::default(),
+      discard: true,
+    }
+  }
+
+  pub(crate) fn is_discarding(&self) -> bool {
+    self.discard
+  }
+
+  /// Writes one serialized line, first waiting for the rate limit.
+  pub(crate) async fn write(&self, line: &str) {
+    if self.discard {
+      return;
+    }
+    if let Some(pace) = &self.pace {
+      pace.lock().await.tick().await;
+    }
+    let eol = self.line_ending.as_str();
+    let mut out = std::io::stdout().lock();
+    let first = !self.started.swap(true, Ordering::Relaxed);
+    match self.format {
+      OutputFormat::Json => {
+        let prefix = if first { "[" } else { "," };
+        let _ = write!(out, "{}{}{}", prefix, eol, line);
+      }
+      OutputFormat::Csv if first => {
+        let _ = write!(out, "{}{}{}{}", CSV_HEADER, eol, line, eol);
+      }
+      // A pretty-printed row spans several lines, each ended like the last.
+      OutputFormat::Pretty if self.line_ending == LineEnding::Crlf => {
+        let _ = write!(out, "{}{}", line.replace('\n', eol), eol);
+      }
+      _ => {
+        let _ = write!(out, "{}{}", line, eol);
+      }
+    }
+    let _ = out.flush();
+  }
+
+  /// Closes the JSON array, writing `[]` if it has no elements, or writes the
+  /// CSV header if no row was written.
+  pub(crate) fn finish(&self) {
+    let eol = self.line_ending.as_str();
+    let started = self.started.load(Ordering::Relaxed);
+    match self.format {
+      OutputFormat::Json if started => print!("{}]{}", eol, eol),
+      OutputFormat::Json => print!("[]{}", eol),
+      OutputFormat::Csv if !started => print!("{}{}", CSV_HEADER, eol),
+      _ => return,
+    }
+    let _ = std::io::stdout().flush();
+  }
+}
+
+/// Serializes a JSON value with object keys in lexicographic order at every
+/// nesting level.
+///
+/// `serde_json::Map` only sorts its keys while the `preserve_order` feature is
+/// off, and any dependency may turn it on. Sorting explicitly keeps result rows
+/// byte-stable for golden tests regardless of feature unification.
+pub(crate) struct Sorted<'a>(pub &'a serde_json::Value);
+
+impl Serialize for Sorted<'_> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self.0 {
+      serde_json::Value::Object(map) => sorted_map(map, serializer),
+      serde_json::Value::Array(items) => {
+        let mut seq = serializer.serialize_seq(Some(items.len()))?;
+        for item in items {
+          seq.serialize_element(&Sorted(item))?;
+        }
+        seq.end()
+      }
+      other => other.serialize(serializer),
+    }
+  }
+}
+
+/// Writes `--emit-process-status` lines to stderr or to their own file, so that
+/// they never mix with the result rows on stdout.
+#[derive(Debug)]
+pub(crate) enum StatusWriter {
+  Stderr,
+  File {
+    path: PathBuf,
+    file: std::sync::Mutex<std::fs::File>,
+  },
+}
+
+impl StatusWriter {
+  /// Creates the writer, truncating `path` if one is given.
+  pub(crate) fn open(path: Option<&Path>) -> Result<Self, BenchmarkError> {
+    let Some(path) = path else {
+      return Ok(StatusWriter::Stderr);
+    };
+    let file =
+      std::fs::File::create(path).map_err(|source| BenchmarkError::WriteProcessStatus {
+        path: path.to_path_buf(),
+        source,
+      })?;
+    Ok(StatusWriter::File {
+      path: path.to_path_buf(),
+      file: std::sync::Mutex::new(file),
+    })
+  }
+
+  pub(crate) fn write(&self, line: &str) -> Result<(), BenchmarkError> {
+    match self {
+      StatusWriter::Stderr => {
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+        Ok(())
+      }
+      StatusWriter::File { path, file } => {
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", line).map_err(|source| BenchmarkError::WriteProcessStatus {
+          path: path.clone(),
+          source,
+        })
+      }
+    }
+  }
+}
+
+/// `serialize_with` helper emitting a JSON object with sorted keys.
+pub(crate) fn sorted_map<S: Serializer>(
+  map: &serde_json::Map<String, serde_json::Value>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  let mut entries: Vec<_> = map.iter().collect();
+  entries.sort_by(|a, b| a.0.cmp(b.0));
+
+  let mut out = serializer.serialize_map(Some(entries.len()))?;
+  for (k, v) in entries {
+    out.serialize_entry(k, &Sorted(v))?;
+  }
+  out.end()
+}
+
+/// `serialize_with` helper for optional JSON values with sorted keys.
+pub(crate) fn sorted_option<S: Serializer>(
+  value: &Option<serde_json::Value>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  match value {
+    Some(v) => serializer.serialize_some(&Sorted(v)),
+    None => serializer.serialize_none(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sorted_nested_keys() {
+    let mut inner = serde_json::Map::new();
+    inner.insert("z".to_string(), 1.into());
+    inner.insert("a".to_string(), 2.into());
+    let mut outer = serde_json::Map::new();
+    outer.insert(
+      "y".to_string(),
+      serde_json::Value::Array(vec![inner.clone().into()]),
+    );
+    outer.insert("b".to_string(), inner.into());
+
+    let json = serde_json::to_string(&Sorted(&outer.into())).unwrap();
+    assert_eq!(json, r#"{"b":{"a":2,"z":1},"y":[{"a":2,"z":1}]}"#);
+  }
+}
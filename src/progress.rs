@@ -0,0 +1,109 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use indicatif::ProgressBar;
+use indicatif::ProgressDrawTarget;
+use indicatif::ProgressStyle;
+use tokio::sync::mpsc;
+
+/// Sending half of the progress channel populated by [`run_benchmarks`](crate::benchmark::run_benchmarks).
+///
+/// The run drops its sender when it finishes, so consumers can treat a closed
+/// channel as the end of the run.
+pub type ProgressSender = mpsc::UnboundedSender<ProgressEvent>;
+
+/// Receiving half of the progress channel.
+pub type ProgressReceiver = mpsc::UnboundedReceiver<ProgressEvent>;
+
+/// Creates a new progress channel.
+pub fn channel() -> (ProgressSender, ProgressReceiver) {
+  mpsc::unbounded_channel()
+}
+
+/// A progress notification emitted while a benchmark run is in flight.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+  /// The run is starting. `tasks` holds one display label per task, and
+  /// `pipelines` holds the number of pipelines scheduled for each task.
+  RunStarted {
+    tasks: Vec<String>,
+    pipelines: Vec<usize>,
+  },
+
+  /// A pipeline for the given task has been spawned.
+  PipelineStarted { task_index: usize, rep_index: usize },
+
+  /// A result row for the given task has been written to the output sink.
+  ResultRow { task_index: usize },
+
+  /// The executor of a running pipeline reported how far along it is with a
+  /// `#progress=` line, as a fraction between 0 and 1.
+  PipelineProgress {
+    task_index: usize,
+    rep_index: usize,
+    fraction: f64,
+  },
+
+  /// A pipeline for the given task has completed successfully.
+  PipelineFinished { task_index: usize, rep_index: usize },
+}
+
+/// Sends `event` if a progress channel is attached. A closed channel is ignored
+/// so that a crashed consumer never aborts the run.
+pub(crate) fn emit(sender: Option<&ProgressSender>, event: ProgressEvent) {
+  if let Some(sender) = sender {
+    let _ = sender.send(event);
+  }
+}
+
+/// Renders a progress bar of completed pipelines on stderr until the run ends.
+///
+/// The total is taken from [`ProgressEvent::RunStarted`]; result rows keep
+/// streaming to stdout untouched.
+pub async fn progress_bar(mut events: ProgressReceiver) {
+  let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+  bar.set_style(
+    ProgressStyle::with_template("{bar:40} {pos}/{len} pipelines [{elapsed_precise}] {msg}")
+      .expect("progress bar template is valid"),
+  );
+
+  let mut tasks = Vec::new();
+  while let Some(event) = events.recv().await {
+    match event {
+      ProgressEvent::RunStarted {
+        tasks: labels,
+        pipelines,
+      } => {
+        bar.set_length(pipelines.iter().sum::<usize>() as u64);
+        tasks = labels;
+      }
+      ProgressEvent::PipelineStarted { task_index, .. } => {
+        if let Some(label) = tasks.get(task_index) {
+          bar.set_message(label.clone());
+        }
+      }
+      ProgressEvent::PipelineProgress {
+        task_index,
+        fraction,
+        ..
+      } => {
+        if let Some(label) = tasks.get(task_index) {
+          bar.set_message(format!("{} {:.0}%", label, fraction * 100.0));
+        }
+      }
+      ProgressEvent::PipelineFinished { .. } => bar.inc(1),
+      ProgressEvent::ResultRow { .. } => {}
+    }
+  }
+  bar.finish_and_clear();
+}
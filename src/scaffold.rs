@@ -0,0 +1,191 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::ScaffoldError;
+use crate::manifest::ComponentType;
+
+/// Languages `impa init` can generate a stub component for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Language {
+  Python,
+  Bash,
+  Node,
+}
+
+impl Language {
+  /// The interpreter, the stub script's file name and the stub's contents.
+  fn template(self, component_type: &ComponentType) -> (&'static str, &'static str, &'static str) {
+    match (self, component_type) {
+      (Language::Python, ComponentType::Generator) => ("python3", "main.py", PYTHON_GENERATOR),
+      (Language::Python, ComponentType::Executor) => ("python3", "main.py", PYTHON_EXECUTOR),
+      (Language::Bash, ComponentType::Generator) => ("bash", "run.sh", BASH_GENERATOR),
+      (Language::Bash, ComponentType::Executor) => ("bash", "run.sh", BASH_EXECUTOR),
+      (Language::Node, ComponentType::Generator) => ("node", "main.js", NODE_GENERATOR),
+      (Language::Node, ComponentType::Executor) => ("node", "main.js", NODE_EXECUTOR),
+    }
+  }
+}
+
+const PYTHON_GENERATOR: &str = r#"import os
+import random
+
+# Each line is one test case: a unique data token followed by the input data.
+rng = random.Random(int(os.environ["IMPALAB_SEED"]))
+for case in range(10):
+    values = " ".join(str(rng.randint(0, 1000)) for _ in range(8))
+    print(f"case_{case} {values}")
+"#;
+
+const PYTHON_EXECUTOR: &str = r#"import sys
+import time
+
+for line in sys.stdin:
+    token, _, data = line.rstrip("\n").partition(" ")
+    start = time.perf_counter_ns()
+    # TODO: run the code under test on `data`.
+    elapsed = time.perf_counter_ns() - start
+    print(f"{elapsed}|{token}", flush=True)
+"#;
+
+const BASH_GENERATOR: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+
+# Each line is one test case: a unique data token followed by the input data.
+RANDOM=$((IMPALAB_SEED % 32768))
+for case in $(seq 0 9); do
+  echo "case_${case} $RANDOM $RANDOM $RANDOM $RANDOM"
+done
+"#;
+
+const BASH_EXECUTOR: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+
+while IFS=' ' read -r token data; do
+  start=$(date +%s%N)
+  # TODO: run the code under test on "$data".
+  end=$(date +%s%N)
+  echo "$((end - start))|${token}"
+done
+"#;
+
+const NODE_GENERATOR: &str = r#"// Each line is one test case: a unique data token followed by the input data.
+let state = Number(BigInt(process.env.IMPALAB_SEED) & 0xffffffffn);
+function next() {
+  state = (Math.imul(state, 1664525) + 1013904223) >>> 0;
+  return state % 1001;
+}
+
+for (let c = 0; c < 10; c++) {
+  const values = Array.from({ length: 8 }, next).join(" ");
+  console.log(`case_${c} ${values}`);
+}
+"#;
+
+const NODE_EXECUTOR: &str = r#"const readline = require("node:readline");
+
+const rl = readline.createInterface({ input: process.stdin });
+rl.on("line", (line) => {
+  const [token, ...data] = line.split(" ");
+  const start = process.hrtime.bigint();
+  // TODO: run the code under test on `data`.
+  const elapsed = process.hrtime.bigint() - start;
+  console.log(`${elapsed}|${token}`);
+});
+"#;
+
+/// Renders the `impafile.toml` of a scaffolded component.
+fn render_impafile(
+  name: &str,
+  component_type: &ComponentType,
+  command: &str,
+  script: &str,
+) -> String {
+  let type_name = match component_type {
+    ComponentType::Generator => "generator",
+    ComponentType::Executor => "executor",
+  };
+  format!(
+    r#"[[components]]
+name = "{name}"
+type = "{type_name}"
+
+# Uncomment to add a build step that runs before the component is registered.
+# [components.build]
+# command = "make"
+# args = []
+
+[components.run]
+command = "{command}"
+args = ["{script}"]
+"#
+  )
+}
+
+/// Creates `dir` (if needed) with a template `impafile.toml` and a stub run script.
+///
+/// The component name defaults to the directory's file name. Returns the paths
+/// of the files written. Fails without writing anything if `dir` already
+/// contains an `impafile.toml`.
+pub fn init_component(
+  dir: &Path,
+  name: Option<&str>,
+  component_type: ComponentType,
+  language: Language,
+) -> Result<Vec<PathBuf>, ScaffoldError> {
+  let impafile = dir.join("impafile.toml");
+  if impafile.exists() {
+    return Err(ScaffoldError::ImpafileExists { path: impafile });
+  }
+
+  let name = match name {
+    Some(name) => name.to_owned(),
+    None => dir
+      .canonicalize()
+      .unwrap_or_else(|_| dir.to_path_buf())
+      .file_name()
+      .map(|n| n.to_string_lossy().into_owned())
+      .ok_or_else(|| ScaffoldError::MissingName {
+        path: dir.to_path_buf(),
+      })?,
+  };
+
+  let (command, script_name, script) = language.template(&component_type);
+  let script_path = dir.join(script_name);
+  if script_path.exists() {
+    return Err(ScaffoldError::FileExists { path: script_path });
+  }
+
+  fs::create_dir_all(dir).map_err(|e| ScaffoldError::CreateDir {
+    path: dir.to_path_buf(),
+    source: e,
+  })?;
+
+  let write = |path: &Path, contents: &str| {
+    fs::write(path, contents).map_err(|e| ScaffoldError::WriteFile {
+      path: path.to_path_buf(),
+      source: e,
+    })
+  };
+  write(
+    &impafile,
+    &render_impafile(&name, &component_type, command, script_name),
+  )?;
+  write(&script_path, script)?;
+
+  tracing::info!(name, dir = %dir.display(), "Scaffolded component");
+  Ok(vec![impafile, script_path])
+}
@@ -0,0 +1,251 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! SQLite result output (requires the `sqlite` cargo feature).
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use rusqlite::Connection;
+use rusqlite::params;
+
+use crate::benchmark::BufferedRows;
+use crate::error::BenchmarkError;
+use crate::output::Sorted;
+
+/// Schema of the `results` table. Seeds are stored as text because SQLite
+/// integers cannot hold every `u64`.
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS results (
+  run_id TEXT NOT NULL,
+  task_index INTEGER NOT NULL,
+  executor TEXT NOT NULL,
+  args TEXT NOT NULL,
+  rep_index INTEGER NOT NULL,
+  case_id TEXT,
+  size INTEGER,
+  seed TEXT,
+  input_checksum TEXT,
+  orchestrator_duration_ms REAL,
+  data_token TEXT NOT NULL,
+  metric REAL,
+  status TEXT NOT NULL,
+  metadata TEXT NOT NULL
+)";
+
+const INSERT_ROW: &str = "INSERT INTO results (
+  run_id, task_index, executor, args, rep_index, case_id, size, seed, input_checksum,
+  orchestrator_duration_ms, data_token, metric, status, metadata
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)";
+
+/// Appends result rows to the `results` table of an SQLite database, creating
+/// the table if the database does not have one yet.
+///
+/// Each row carries the `run_id` of the run that wrote it, so that several runs
+/// can share a database. The rows of a pipeline are inserted in a single
+/// transaction.
+pub struct SqliteSink {
+  path: PathBuf,
+  conn: Connection,
+  run_id: String,
+}
+
+impl SqliteSink {
+  pub fn create(path: &Path) -> Result<Self, BenchmarkError> {
+    let conn = Connection::open(path)
+      .and_then(|conn| conn.execute_batch(CREATE_TABLE).map(|()| conn))
+      .map_err(|e| write_err(path, e))?;
+    let run_id = humantime::format_rfc3339_micros(SystemTime::now()).to_string();
+    tracing::info!(%run_id, path = %path.display(), "Appending results to SQLite database");
+    Ok(Self {
+      path: path.to_path_buf(),
+      conn,
+      run_id,
+    })
+  }
+
+  /// Identifies the rows written by this sink: the time it was opened, in RFC
+  /// 3339 format with microseconds.
+  pub fn run_id(&self) -> &str {
+    &self.run_id
+  }
+
+  pub(crate) fn write(
+    &mut self,
+    BufferedRows { meta, rows }: &BufferedRows,
+  ) -> Result<(), BenchmarkError> {
+    if rows.is_empty() {
+      return Ok(());
+    }
+
+    let args = serde_json::to_string(&meta.task_args).map_err(BenchmarkError::SerializeResult)?;
+    let seed = meta.seed.map(|seed| seed.to_string());
+    let tx = self
+      .conn
+      .transaction()
+      .map_err(|e| write_err(&self.path, e))?;
+    {
+      let mut insert = tx
+        .prepare_cached(INSERT_ROW)
+        .map_err(|e| write_err(&self.path, e))?;
+      for row in rows {
+        // Attributes, metadata and extra metrics share a single JSON column.
+        let mut metadata = serde_json::Map::new();
+        if !meta.attributes.is_empty() {
+          metadata.insert(
+            "attributes".to_string(),
+            serde_json::Value::Object(meta.attributes.clone()),
+          );
+        }
+        if let Some(gen_meta) = &row.gen_meta {
+          metadata.insert("gen_meta".to_string(), gen_meta.clone());
+        }
+        if let Some(exec_meta) = &row.exec_meta {
+          metadata.insert("exec_meta".to_string(), exec_meta.clone());
+        }
+        if !row.metrics.is_empty() {
+          metadata.insert(
+            "metrics".to_string(),
+            serde_json::to_value(&row.metrics).map_err(BenchmarkError::SerializeResult)?,
+          );
+        }
+        let metadata = serde_json::to_string(&Sorted(&serde_json::Value::Object(metadata)))
+          .map_err(BenchmarkError::SerializeResult)?;
+
+        insert
+          .execute(params![
+            self.run_id,
+            meta.task_index as i64,
+            meta.executor,
+            args,
+            meta.rep_index as i64,
+            meta.case_id,
+            meta.size.map(|size| size as i64),
+            seed,
+            meta.input_checksum,
+            meta.orchestrator_duration_ms,
+            row.data_token,
+            row.metric.as_ref().and_then(serde_json::Number::as_f64),
+            row.status.as_str(),
+            metadata,
+          ])
+          .map_err(|e| write_err(&self.path, e))?;
+      }
+    }
+    tx.commit().map_err(|e| write_err(&self.path, e))
+  }
+
+  /// Closes the database.
+  pub fn finish(self) -> Result<(), BenchmarkError> {
+    self.conn.close().map_err(|(_, e)| write_err(&self.path, e))
+  }
+}
+
+fn write_err(path: &Path, source: rusqlite::Error) -> BenchmarkError {
+  BenchmarkError::WriteSqlite {
+    path: path.to_path_buf(),
+    source,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::benchmark::BenchmarkMeta;
+  use crate::benchmark::BufferedRow;
+  use crate::benchmark::ResultStatus;
+  use serde_json::json;
+  use std::collections::BTreeMap;
+
+  fn rows(rep_index: usize) -> BufferedRows {
+    let mut attributes = serde_json::Map::new();
+    attributes.insert("tier".to_string(), json!("high"));
+    BufferedRows {
+      meta: BenchmarkMeta {
+        task_index: 1,
+        executor: "py-sort".to_string(),
+        task_args: vec!["quick".to_string()],
+        rep_index,
+        case_id: Some("alpha".to_string()),
+        size: Some(100),
+        seed: Some(u64::MAX),
+        attributes,
+        input_checksum: None,
+        orchestrator_duration_ms: None,
+      },
+      rows: vec![
+        BufferedRow {
+          data_token: "a".to_string(),
+          gen_meta: None,
+          exec_meta: Some(json!({"k": 1})),
+          metric: serde_json::Number::from_f64(10.5),
+          metrics: BTreeMap::from([("comparisons".to_string(), 3.0)]),
+          status: ResultStatus::Ok,
+        },
+        BufferedRow::failed(ResultStatus::Crashed),
+      ],
+    }
+  }
+
+  #[test]
+  fn test_runs_append_to_results_table() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("results.db");
+
+    let mut first = SqliteSink::create(&path).unwrap();
+    let first_run = first.run_id().to_owned();
+    first.write(&rows(0)).unwrap();
+    first.write(&rows(1)).unwrap();
+    first.finish().unwrap();
+
+    let mut second = SqliteSink::create(&path).unwrap();
+    let second_run = second.run_id().to_owned();
+    second.write(&rows(0)).unwrap();
+    second.finish().unwrap();
+    assert_ne!(first_run, second_run);
+
+    let conn = Connection::open(&path).unwrap();
+    let counts: Vec<(String, i64)> = conn
+      .prepare("SELECT run_id, COUNT(*) FROM results GROUP BY run_id ORDER BY run_id")
+      .unwrap()
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+      .unwrap()
+      .map(Result::unwrap)
+      .collect();
+    assert_eq!(counts, [(first_run.clone(), 4), (second_run, 2)]);
+
+    let (args, seed, metric, metadata): (String, String, f64, String) = conn
+      .query_row(
+        "SELECT args, seed, metric, metadata FROM results
+         WHERE run_id = ?1 AND rep_index = 1 AND status = 'ok'",
+        [&first_run],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+      )
+      .unwrap();
+    assert_eq!(args, r#"["quick"]"#);
+    assert_eq!(seed, u64::MAX.to_string());
+    assert_eq!(metric, 10.5);
+    assert_eq!(
+      metadata,
+      r#"{"attributes":{"tier":"high"},"exec_meta":{"k":1},"metrics":{"comparisons":3.0}}"#
+    );
+
+    let failed_metric: Option<f64> = conn
+      .query_row(
+        "SELECT metric FROM results WHERE status = 'crashed' LIMIT 1",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap();
+    assert_eq!(failed_metric, None);
+  }
+}
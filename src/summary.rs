@@ -0,0 +1,454 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+
+use crate::cli::LineEnding;
+use crate::config::ResolvedTask;
+
+/// Maximum number of metrics kept per task for computing percentiles.
+pub const RESERVOIR_CAPACITY: usize = 10_000;
+
+/// Descriptive statistics over the metrics collected for one task.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricStats {
+  pub count: usize,
+  pub min: f64,
+  pub median: f64,
+  pub mean: f64,
+  pub p90: f64,
+  pub p95: f64,
+  pub p99: f64,
+  pub max: f64,
+  /// Sample standard deviation (`n - 1` denominator). Zero for a single sample.
+  pub stddev: f64,
+}
+
+impl MetricStats {
+  /// Computes statistics over `samples`, returning `None` when there are none.
+  pub fn from_samples(samples: &[f64]) -> Option<Self> {
+    if samples.is_empty() {
+      return None;
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let count = sorted.len();
+    let mean = sorted.iter().sum::<f64>() / count as f64;
+    let stddev = if count > 1 {
+      let var = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+      var.sqrt()
+    } else {
+      0.0
+    };
+
+    Some(Self {
+      count,
+      min: sorted[0],
+      median: percentile(&sorted, 50.0),
+      mean,
+      p90: percentile(&sorted, 90.0),
+      p95: percentile(&sorted, 95.0),
+      p99: percentile(&sorted, 99.0),
+      max: sorted[count - 1],
+      stddev,
+    })
+  }
+}
+
+/// Linearly interpolated percentile of an ascending, non-empty slice.
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+  let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+  let lo = rank.floor() as usize;
+  let hi = rank.ceil() as usize;
+  sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Items processed and the time they took, summed over result rows for
+/// `--report-throughput`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Throughput {
+  pub items: f64,
+  pub secs: f64,
+}
+
+impl Throughput {
+  /// The throughput of a row that processed `items` in `metric_ns` nanoseconds,
+  /// or `None` when the duration is not positive.
+  pub fn from_row(items: u64, metric_ns: f64) -> Option<Self> {
+    (metric_ns.is_finite() && metric_ns > 0.0).then(|| Self {
+      items: items as f64,
+      secs: metric_ns / 1e9,
+    })
+  }
+
+  /// Items per second, or `None` when no time was recorded.
+  pub fn items_per_sec(&self) -> Option<f64> {
+    (self.secs > 0.0).then(|| self.items / self.secs)
+  }
+}
+
+impl std::ops::AddAssign for Throughput {
+  fn add_assign(&mut self, other: Self) {
+    self.items += other.items;
+    self.secs += other.secs;
+  }
+}
+
+/// Running statistics over one task's metrics, in bounded memory.
+///
+/// The count, mean, standard deviation, minimum and maximum are exact. Percentiles
+/// are computed over a uniform reservoir sample of at most [`RESERVOIR_CAPACITY`]
+/// metrics, so they are exact until a task produces more metrics than that.
+#[derive(Debug, Clone)]
+struct Samples {
+  count: usize,
+  mean: f64,
+  /// Sum of squared deviations from the mean (Welford's algorithm).
+  m2: f64,
+  min: f64,
+  max: f64,
+  reservoir: Vec<f64>,
+  /// Fixed-seed so that the sampled percentiles are reproducible.
+  rng: StdRng,
+}
+
+impl Default for Samples {
+  fn default() -> Self {
+    Self {
+      count: 0,
+      mean: 0.0,
+      m2: 0.0,
+      min: f64::INFINITY,
+      max: f64::NEG_INFINITY,
+      reservoir: Vec::new(),
+      rng: StdRng::seed_from_u64(0),
+    }
+  }
+}
+
+impl Samples {
+  fn push(&mut self, x: f64) {
+    self.count += 1;
+    let delta = x - self.mean;
+    self.mean += delta / self.count as f64;
+    self.m2 += delta * (x - self.mean);
+    self.min = self.min.min(x);
+    self.max = self.max.max(x);
+
+    if self.reservoir.len() < RESERVOIR_CAPACITY {
+      self.reservoir.push(x);
+    } else {
+      let slot = self.rng.random_range(0..self.count);
+      if slot < RESERVOIR_CAPACITY {
+        self.reservoir[slot] = x;
+      }
+    }
+  }
+
+  fn stats(&self) -> Option<MetricStats> {
+    if self.count == 0 {
+      return None;
+    }
+
+    let mut sorted = self.reservoir.clone();
+    sorted.sort_by(f64::total_cmp);
+    let stddev = if self.count > 1 {
+      (self.m2 / (self.count - 1) as f64).sqrt()
+    } else {
+      0.0
+    };
+
+    Some(MetricStats {
+      count: self.count,
+      min: self.min,
+      median: percentile(&sorted, 50.0),
+      mean: self.mean,
+      p90: percentile(&sorted, 90.0),
+      p95: percentile(&sorted, 95.0),
+      p99: percentile(&sorted, 99.0),
+      max: self.max,
+      stddev,
+    })
+  }
+}
+
+/// Accumulates metrics per task for end-of-run aggregation.
+#[derive(Debug, Default)]
+pub struct Summary {
+  samples: BTreeMap<usize, Samples>,
+  throughput: BTreeMap<usize, Throughput>,
+}
+
+/// One task's statistics as emitted by `--only-summary`.
+#[derive(Debug, Serialize)]
+pub struct SummaryRecord<'a> {
+  #[serde(rename = "type")]
+  kind: &'static str,
+  pub task_index: usize,
+  pub executor: &'a str,
+  #[serde(skip_serializing_if = "<[String]>::is_empty")]
+  pub args: &'a [String],
+  #[serde(flatten)]
+  pub stats: MetricStats,
+  /// Total items over total time of the task's rows (`--report-throughput`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub items_per_sec: Option<f64>,
+}
+
+impl Summary {
+  pub fn record(&mut self, task_index: usize, metrics: impl IntoIterator<Item = f64>) {
+    let samples = self.samples.entry(task_index).or_default();
+    for metric in metrics {
+      samples.push(metric);
+    }
+  }
+
+  /// Adds to a task's total items and time, from which its `items_per_sec` is
+  /// computed.
+  pub fn record_throughput(&mut self, task_index: usize, throughput: Throughput) {
+    *self.throughput.entry(task_index).or_default() += throughput;
+  }
+
+  /// Returns the statistics for each task that produced at least one metric,
+  /// ordered by task index.
+  pub fn stats(&self) -> impl Iterator<Item = (usize, MetricStats)> + '_ {
+    self
+      .samples
+      .iter()
+      .filter_map(|(&idx, samples)| samples.stats().map(|s| (idx, s)))
+  }
+
+  /// Returns one record per task that produced at least one metric.
+  pub fn records<'a>(
+    &'a self,
+    tasks: &'a [ResolvedTask],
+  ) -> impl Iterator<Item = SummaryRecord<'a>> + 'a {
+    self.stats().map(move |(idx, stats)| {
+      let (executor, args) = tasks
+        .get(idx)
+        .map(|t| (t.executor.as_str(), t.args.as_slice()))
+        .unwrap_or_default();
+      SummaryRecord {
+        kind: "summary",
+        task_index: idx,
+        executor,
+        args,
+        stats,
+        items_per_sec: self
+          .throughput
+          .get(&idx)
+          .and_then(Throughput::items_per_sec),
+      }
+    })
+  }
+
+  /// Renders the summary as CSV, one row per task.
+  pub fn to_csv(&self, tasks: &[ResolvedTask], line_ending: LineEnding) -> String {
+    let mut out =
+      String::from("task_index,executor,args,count,min,median,mean,p90,p95,p99,max,stddev");
+    out.push_str(line_ending.as_str());
+    for (idx, s) in self.stats() {
+      let (executor, args) = tasks
+        .get(idx)
+        .map(|t| (t.executor.as_str(), t.args.join(" ")))
+        .unwrap_or_default();
+      // Writing to a `String` cannot fail.
+      let _ = write!(
+        out,
+        "{},{},{},{},{},{},{},{},{},{},{},{}{}",
+        idx,
+        csv_field(executor),
+        csv_field(&args),
+        s.count,
+        s.min,
+        s.median,
+        s.mean,
+        s.p90,
+        s.p95,
+        s.p99,
+        s.max,
+        s.stddev,
+        line_ending.as_str()
+      );
+    }
+    out
+  }
+
+  /// Renders the summary as an aligned, human-readable table, one row per task.
+  pub fn to_table(&self, tasks: &[ResolvedTask]) -> String {
+    let header = [
+      "task", "executor", "count", "min", "median", "mean", "p90", "p95", "p99", "max", "stddev",
+    ];
+    let mut rows = vec![header.map(String::from).to_vec()];
+    for (idx, s) in self.stats() {
+      let executor = tasks
+        .get(idx)
+        .map(|t| {
+          std::iter::once(t.executor.as_str())
+            .chain(t.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+        })
+        .unwrap_or_default();
+      let stats = [
+        s.min, s.median, s.mean, s.p90, s.p95, s.p99, s.max, s.stddev,
+      ];
+      rows.push(
+        [idx.to_string(), executor, s.count.to_string()]
+          .into_iter()
+          .chain(stats.iter().map(|x| format!("{:.2}", x)))
+          .collect(),
+      );
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+      .map(|col| rows.iter().map(|row| row[col].len()).max().unwrap_or(0))
+      .collect();
+    let mut out = String::new();
+    for row in &rows {
+      for (col, (cell, width)) in row.iter().zip(&widths).enumerate() {
+        // The executor column is left-aligned, numbers are right-aligned.
+        let _ = match col {
+          0 => write!(out, "{:>width$}", cell),
+          1 => write!(out, "  {:<width$}", cell),
+          _ => write!(out, "  {:>width$}", cell),
+        };
+      }
+      out.truncate(out.trim_end().len());
+      out.push('\n');
+    }
+    out
+  }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote or line break.
+pub(crate) fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_owned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_metric_stats_from_samples() {
+    let stats = MetricStats::from_samples(&[5.0, 1.0, 3.0, 2.0, 4.0]).unwrap();
+    assert_eq!(stats.count, 5);
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.median, 3.0);
+    assert_eq!(stats.mean, 3.0);
+    assert_eq!(stats.max, 5.0);
+    assert!((stats.p90 - 4.6).abs() < 1e-9);
+    assert!((stats.p95 - 4.8).abs() < 1e-9);
+    assert!((stats.stddev - 2.5f64.sqrt()).abs() < 1e-9);
+
+    assert!(MetricStats::from_samples(&[]).is_none());
+  }
+
+  #[test]
+  fn test_metric_stats_single_sample() {
+    let stats = MetricStats::from_samples(&[7.0]).unwrap();
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.stddev, 0.0);
+    for value in [stats.min, stats.median, stats.p90, stats.p99, stats.max] {
+      assert_eq!(value, 7.0);
+    }
+  }
+
+  #[test]
+  fn test_percentile_interpolation() {
+    let sorted = [10.0, 20.0, 30.0, 40.0];
+    assert_eq!(percentile(&sorted, 0.0), 10.0);
+    assert_eq!(percentile(&sorted, 50.0), 25.0);
+    assert!((percentile(&sorted, 90.0) - 37.0).abs() < 1e-9);
+    assert_eq!(percentile(&sorted, 100.0), 40.0);
+  }
+
+  #[test]
+  fn test_summary_table_skips_empty_tasks() {
+    let mut summary = Summary::default();
+    summary.record(1, [3.0, 1.0]);
+    summary.record(2, []);
+
+    let table = summary.to_table(&[]);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("task  executor  count"));
+    assert_eq!(
+      lines[1].split_whitespace().collect::<Vec<_>>(),
+      [
+        "1", "2", "1.00", "2.00", "2.00", "2.80", "2.90", "2.98", "3.00", "1.41"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_summary_bounded_percentiles() {
+    let mut summary = Summary::default();
+    let n = 5 * RESERVOIR_CAPACITY;
+    summary.record(0, (1..=n).map(|x| x as f64));
+
+    let (_, stats) = summary.stats().next().unwrap();
+    assert_eq!(stats.count, n);
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.max, n as f64);
+    assert!((stats.mean - (n + 1) as f64 / 2.0).abs() < 1e-6);
+    assert_eq!(summary.samples[&0].reservoir.len(), RESERVOIR_CAPACITY);
+    for (got, pct) in [(stats.median, 0.5), (stats.p95, 0.95), (stats.p99, 0.99)] {
+      let exact = pct * n as f64;
+      assert!(
+        (got - exact).abs() < 0.02 * n as f64,
+        "{} vs {}",
+        got,
+        exact
+      );
+    }
+  }
+
+  #[test]
+  fn test_throughput() {
+    // 1000 items in 2ms.
+    let row = Throughput::from_row(1000, 2_000_000.0).unwrap();
+    assert_eq!(row.items_per_sec(), Some(500_000.0));
+    assert_eq!(Throughput::from_row(1000, 0.0), None);
+    assert_eq!(Throughput::from_row(1000, -1.0), None);
+    assert_eq!(Throughput::default().items_per_sec(), None);
+
+    // Totals weigh each row by its duration rather than averaging rates.
+    let mut summary = Summary::default();
+    summary.record(0, [2_000_000.0, 8_000_000.0]);
+    summary.record_throughput(0, row);
+    summary.record_throughput(0, Throughput::from_row(1000, 8_000_000.0).unwrap());
+    let record = summary.records(&[]).next().unwrap();
+    assert_eq!(record.items_per_sec, Some(200_000.0));
+  }
+
+  #[test]
+  fn test_csv_field_quoting() {
+    assert_eq!(csv_field("plain"), "plain");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+  }
+}
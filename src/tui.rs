@@ -0,0 +1,320 @@
+// Copyright 2025 Chisomo Makombo Sakala
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Live terminal progress view for `impa run --tui`.
+//!
+//! The view is drawn on `stderr` so that result rows on `stdout` can still be
+//! redirected to a file while the run is in flight.
+use std::io;
+use std::time::Duration;
+use std::time::Instant;
+
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::backend::Backend;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::cursor;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::widgets::Block;
+use ratatui::widgets::Gauge;
+use ratatui::widgets::Row;
+use ratatui::widgets::Table;
+
+use crate::progress::ProgressEvent;
+use crate::progress::ProgressReceiver;
+
+/// How often the view is redrawn while no events arrive, so the elapsed time keeps ticking.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Progress of a single task.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskProgress {
+  pub label: String,
+  pub pipelines: usize,
+  pub finished: usize,
+  pub running: bool,
+  /// Fraction of the running pipeline reported by its executor, if any.
+  pub progress: Option<f64>,
+  pub rows: u64,
+}
+
+/// Aggregated state rendered by the view.
+#[derive(Debug, Clone)]
+pub struct ProgressState {
+  pub tasks: Vec<TaskProgress>,
+  pub rows: u64,
+  started: Instant,
+}
+
+impl Default for ProgressState {
+  fn default() -> Self {
+    Self {
+      tasks: Vec::new(),
+      rows: 0,
+      started: Instant::now(),
+    }
+  }
+}
+
+impl ProgressState {
+  pub fn apply(&mut self, event: ProgressEvent) {
+    match event {
+      ProgressEvent::RunStarted { tasks, pipelines } => {
+        self.tasks = tasks
+          .into_iter()
+          .zip(pipelines)
+          .map(|(label, pipelines)| TaskProgress {
+            label,
+            pipelines,
+            ..Default::default()
+          })
+          .collect();
+        self.started = Instant::now();
+      }
+      ProgressEvent::PipelineStarted { task_index, .. } => {
+        if let Some(task) = self.tasks.get_mut(task_index) {
+          task.running = true;
+          task.progress = None;
+        }
+      }
+      ProgressEvent::ResultRow { task_index } => {
+        self.rows += 1;
+        if let Some(task) = self.tasks.get_mut(task_index) {
+          task.rows += 1;
+        }
+      }
+      ProgressEvent::PipelineProgress {
+        task_index,
+        fraction,
+        ..
+      } => {
+        if let Some(task) = self.tasks.get_mut(task_index) {
+          task.progress = Some(fraction);
+        }
+      }
+      ProgressEvent::PipelineFinished { task_index, .. } => {
+        if let Some(task) = self.tasks.get_mut(task_index) {
+          task.finished += 1;
+          task.running = false;
+          task.progress = None;
+        }
+      }
+    }
+  }
+
+  pub fn elapsed(&self) -> Duration {
+    self.started.elapsed()
+  }
+
+  /// Result rows per second since the run started.
+  pub fn throughput(&self) -> f64 {
+    let secs = self.elapsed().as_secs_f64();
+    if secs > 0.0 {
+      self.rows as f64 / secs
+    } else {
+      0.0
+    }
+  }
+
+  fn ratio(&self) -> f64 {
+    let total: usize = self.tasks.iter().map(|t| t.pipelines).sum();
+    // Running pipelines that report progress count fractionally.
+    let finished: f64 = self
+      .tasks
+      .iter()
+      .map(|t| t.finished as f64 + t.progress.unwrap_or(0.0))
+      .sum();
+    if total == 0 {
+      0.0
+    } else {
+      (finished / total as f64).clamp(0.0, 1.0)
+    }
+  }
+}
+
+/// Renders `state` into `frame`.
+pub fn draw(frame: &mut Frame, state: &ProgressState) {
+  let [header, table] =
+    Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(frame.area());
+
+  let title = format!(
+    " impa run | elapsed {} | {} rows | {:.1} rows/s ",
+    humantime::format_duration(Duration::from_secs(state.elapsed().as_secs())),
+    state.rows,
+    state.throughput()
+  );
+  frame.render_widget(
+    Gauge::default()
+      .block(Block::bordered().title(title))
+      .gauge_style(Style::new().green())
+      .ratio(state.ratio()),
+    header,
+  );
+
+  let rows = state.tasks.iter().map(|t| {
+    Row::new(vec![
+      t.label.clone(),
+      format!("{}/{}", t.finished, t.pipelines),
+      t.rows.to_string(),
+      if t.finished == t.pipelines {
+        "done".to_string()
+      } else if let Some(fraction) = t.progress {
+        format!("{:.0}%", fraction * 100.0)
+      } else if t.running {
+        "running".to_string()
+      } else {
+        "pending".to_string()
+      },
+    ])
+  });
+  frame.render_widget(
+    Table::new(
+      rows,
+      [
+        Constraint::Fill(1),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(8),
+      ],
+    )
+    .header(Row::new(vec!["task", "pipelines", "rows", "status"]).bold())
+    .block(Block::bordered().title(" tasks ")),
+    table,
+  );
+}
+
+/// Consumes progress events and redraws `terminal` until the channel is closed.
+///
+/// Returns the final state once the run has finished.
+pub async fn drive<B: Backend>(
+  terminal: &mut Terminal<B>,
+  mut events: ProgressReceiver,
+) -> io::Result<ProgressState> {
+  let mut state = ProgressState::default();
+  let mut ticker = tokio::time::interval(TICK);
+
+  loop {
+    tokio::select! {
+      event = events.recv() => match event {
+        Some(event) => state.apply(event),
+        None => break,
+      },
+      _ = ticker.tick() => {}
+    }
+    terminal.draw(|frame| draw(frame, &state))?;
+  }
+
+  terminal.draw(|frame| draw(frame, &state))?;
+  Ok(state)
+}
+
+/// Runs the progress view on `stderr` until the run finishes.
+///
+/// The alternate screen is entered on start and always left again, even if
+/// drawing fails.
+pub async fn run(events: ProgressReceiver) -> io::Result<()> {
+  let mut stderr = io::stderr();
+  execute!(stderr, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+  let result = match Terminal::new(CrosstermBackend::new(io::stderr())) {
+    Ok(mut terminal) => drive(&mut terminal, events).await.map(|_| ()),
+    Err(e) => Err(e),
+  };
+
+  execute!(stderr, cursor::Show, terminal::LeaveAlternateScreen)?;
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::progress;
+  use ratatui::backend::TestBackend;
+
+  #[tokio::test]
+  async fn test_tui_initializes_and_tears_down() {
+    let (tx, rx) = progress::channel();
+    let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+
+    tx.send(ProgressEvent::RunStarted {
+      tasks: vec!["rust-sort quick".to_string(), "py-sort".to_string()],
+      pipelines: vec![2, 2],
+    })
+    .unwrap();
+    for rep_index in 0..2 {
+      tx.send(ProgressEvent::PipelineStarted {
+        task_index: 0,
+        rep_index,
+      })
+      .unwrap();
+      tx.send(ProgressEvent::ResultRow { task_index: 0 }).unwrap();
+      tx.send(ProgressEvent::PipelineFinished {
+        task_index: 0,
+        rep_index,
+      })
+      .unwrap();
+    }
+    drop(tx);
+
+    let state = tokio::time::timeout(Duration::from_secs(5), drive(&mut terminal, rx))
+      .await
+      .expect("view should stop once the channel closes")
+      .unwrap();
+
+    assert_eq!(state.rows, 2);
+    assert_eq!(state.tasks[0].finished, 2);
+    assert_eq!(state.tasks[1].finished, 0);
+
+    let screen: String = terminal
+      .backend()
+      .buffer()
+      .content()
+      .iter()
+      .map(|cell| cell.symbol())
+      .collect();
+    assert!(screen.contains("rust-sort quick"));
+    assert!(screen.contains("done"));
+    assert!(screen.contains("pending"));
+  }
+
+  #[test]
+  fn test_pipeline_progress_counts_fractionally() {
+    let mut state = ProgressState::default();
+    state.apply(ProgressEvent::RunStarted {
+      tasks: vec!["slow".to_string()],
+      pipelines: vec![2],
+    });
+    state.apply(ProgressEvent::PipelineStarted {
+      task_index: 0,
+      rep_index: 0,
+    });
+    state.apply(ProgressEvent::PipelineProgress {
+      task_index: 0,
+      rep_index: 0,
+      fraction: 0.5,
+    });
+    assert_eq!(state.ratio(), 0.25);
+
+    state.apply(ProgressEvent::PipelineFinished {
+      task_index: 0,
+      rep_index: 0,
+    });
+    assert_eq!(state.tasks[0].progress, None);
+    assert_eq!(state.ratio(), 0.5);
+  }
+}
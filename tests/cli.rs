@@ -157,13 +157,71 @@ fn test_build_and_run_e2e() {
     .assert()
     .success()
     .stdout(
-      predicate::str::contains(r#"{"task_index":0,"executor":"python-e2e","args":["test_func_1"],"rep_index":0,"data_token":"test_case_1","metric":1234}"#)
+      predicate::str::contains(r#"{"task_index":0,"executor":"python-e2e","args":["test_func_1"],"rep_index":0,"seed":42,"data_token":"test_case_1","metric":1234,"status":"ok"}"#)
     )
     .stdout(
-      predicate::str::contains(r#"{"task_index":1,"executor":"python-e2e","args":["test_func_2","--foo=true","--bars=-100"],"rep_index":0,"data_token":"test_case_1","metric":12}"#)
+      predicate::str::contains(r#"{"task_index":1,"executor":"python-e2e","args":["test_func_2","--foo=true","--bars=-100"],"rep_index":0,"seed":42,"data_token":"test_case_1","metric":12,"status":"ok"}"#)
     );
 }
 
+#[test]
+fn test_build_and_run_with_profiles() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  copy("tests/fixtures", temp.path(), &CopyOptions::new()).unwrap();
+  fs::rename(temp.path().join("fixtures"), &components_dir).unwrap();
+
+  for profile in ["debug", "release"] {
+    let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+    build_cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .args(["--profile", profile])
+      .env("NO_COLOR", "1");
+    build_cmd.assert().success();
+  }
+  assert!(temp.path().join("impa_manifest.debug.json").exists());
+  assert!(temp.path().join("impa_manifest.release.json").exists());
+  assert!(!temp.path().join("impa_manifest.json").exists());
+
+  // Dropping one profile's manifest leaves the other usable.
+  fs::remove_file(temp.path().join("impa_manifest.release.json")).unwrap();
+
+  let run = |profile: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .args(["--profile", profile])
+      .args(["--set", "generator.name=py-gen-e2e", "--config", "-"])
+      .write_stdin(r#"{"tasks": [{"executor": "python-e2e", "args": ["test_func_1"]}]}"#)
+      .env("NO_COLOR", "1");
+    run_cmd.assert()
+  };
+  run("debug")
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"test_case_1""#));
+  run("release").failure().stderr(predicate::str::contains(
+    "Component 'python-e2e' not found in manifest",
+  ));
+
+  let mut invalid_cmd = Command::new(cargo::cargo_bin!("impa"));
+  invalid_cmd
+    .arg("build")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .args(["--profile", "../escape"])
+    .env("NO_COLOR", "1");
+  invalid_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("invalid profile '../escape'"));
+}
+
 #[test]
 fn test_build_and_run_e2e_stdin_config() {
   // Setup: Create temp dir and copy fixtures
@@ -242,10 +300,10 @@ fn test_build_and_run_e2e_stdin_config() {
     .assert()
     .success()
     .stdout(
-      predicate::str::contains(r#"{"task_index":0,"executor":"python-e2e","args":["test_func_1"],"rep_index":0,"data_token":"test_case_1","metric":1234}"#)
+      predicate::str::contains(r#"{"task_index":0,"executor":"python-e2e","args":["test_func_1"],"rep_index":0,"seed":42,"data_token":"test_case_1","metric":1234,"status":"ok"}"#)
     )
     .stdout(
-      predicate::str::contains(r#"{"task_index":1,"executor":"python-e2e","args":["test_func_2","--foo=true","--bars=-100"],"rep_index":0,"data_token":"test_case_1","metric":12}"#)
+      predicate::str::contains(r#"{"task_index":1,"executor":"python-e2e","args":["test_func_2","--foo=true","--bars=-100"],"rep_index":0,"seed":42,"data_token":"test_case_1","metric":12,"status":"ok"}"#)
     );
 }
 
@@ -560,3 +618,5195 @@ fn test_generator_seed_env_var_validation() {
   let assert_valid = cmd_valid.assert();
   assert_valid.success();
 }
+
+#[test]
+fn test_run_with_env_passthrough() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "env-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '1|%s-%s\n' \"${IMPA_KEEP:-unset}\" \"${IMPA_DROP:-unset}\""]
+      }
+    },
+    "tasks": [
+      {"executor": "env-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--env-passthrough")
+    .arg("PATH,IMPA_KEEP")
+    .env("IMPA_KEEP", "kept")
+    .env("IMPA_DROP", "dropped")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"kept-unset""#));
+}
+
+#[test]
+fn test_run_with_seed_from_id() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"$IMPALAB_CASE_ID:$IMPALAB_SEED\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen"},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--seed-from-id")
+      .arg("--ids")
+      .arg("alpha,beta")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+
+  let first = run();
+  assert!(first.contains(
+    r#""case_id":"alpha","seed":9999721509958787115,"data_token":"alpha:9999721509958787115""#
+  ));
+  assert!(first.contains(
+    r#""case_id":"beta","seed":8513880941419438247,"data_token":"beta:8513880941419438247""#
+  ));
+  assert_eq!(first, run());
+
+  let ids_file = temp.path().join("ids.txt");
+  fs::write(&ids_file, "\n  \n").unwrap();
+  for ids_arg in [["--ids", ""], ["--ids-file", ids_file.to_str().unwrap()]] {
+    let mut empty_cmd = Command::new(cargo::cargo_bin!("impa"));
+    empty_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--seed-from-id")
+      .args(ids_arg)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    empty_cmd
+      .assert()
+      .failure()
+      .stdout(predicate::str::is_empty())
+      .stderr(predicate::str::contains(
+        "`--seed-from-id` was given no case ids",
+      ));
+  }
+}
+
+#[test]
+fn test_run_with_sizes() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "sized-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo x >> invocations; echo \"$IMPALAB_CASE_ID:$IMPALAB_SIZE:$IMPALAB_SEED\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "sized-gen"},
+    "tasks": [
+      {"executor": "echo-exec", "args": ["a"]},
+      {"executor": "echo-exec", "args": ["b"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .current_dir(temp.path())
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--seed-from-id")
+    .arg("--ids")
+    .arg("alpha,beta,gamma")
+    .arg("--sizes")
+    .arg("10,1000")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let rows: Vec<Value> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|l| serde_json::from_str(l).unwrap())
+    .collect();
+
+  // Every task runs once per (case id, size) pair.
+  assert_eq!(rows.len(), 2 * 3 * 2);
+  let invocations = fs::read_to_string(temp.path().join("invocations")).unwrap();
+  assert_eq!(invocations.lines().count(), 2 * 3 * 2);
+  for row in &rows {
+    let token = row["data_token"].as_str().unwrap();
+    let (case_id, rest) = token.split_once(':').unwrap();
+    let (size, _seed) = rest.split_once(':').unwrap();
+    assert_eq!(row["case_id"], case_id);
+    assert_eq!(row["size"], size.parse::<u64>().unwrap());
+  }
+  let pairs: std::collections::BTreeSet<(String, u64)> = rows
+    .iter()
+    .map(|r| {
+      (
+        r["case_id"].as_str().unwrap().to_owned(),
+        r["size"].as_u64().unwrap(),
+      )
+    })
+    .collect();
+  assert_eq!(pairs.len(), 3 * 2);
+}
+
+#[test]
+fn test_run_with_seed_count() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {"type": "generator", "command": "bash", "args": ["-c", "echo $IMPALAB_SEED"]},
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen"},
+    "tasks": [{"executor": "echo-exec", "args": ["a"]}, {"executor": "echo-exec", "args": ["b"]}]
+  }"#;
+
+  let run = |seed: Option<&str>| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--seed-count")
+      .arg("3")
+      .env("NO_COLOR", "1")
+      .env_remove("IMPA_SEED")
+      .write_stdin(config_str);
+    if let Some(seed) = seed {
+      run_cmd.arg("--seed").arg(seed);
+    }
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|l| serde_json::from_str::<Value>(l).unwrap())
+      .collect::<Vec<_>>()
+  };
+
+  for rows in [run(None), run(Some("42"))] {
+    assert_eq!(rows.len(), 2 * 3);
+    // Each row records the seed its generator was given.
+    for row in &rows {
+      assert_eq!(
+        row["seed"].as_u64().unwrap().to_string(),
+        row["data_token"].as_str().unwrap()
+      );
+    }
+    let seeds: std::collections::BTreeSet<u64> =
+      rows.iter().map(|r| r["seed"].as_u64().unwrap()).collect();
+    assert_eq!(seeds.len(), 3);
+  }
+
+  // A pinned seed is the first of a reproducible set.
+  let pinned = run(Some("42"));
+  assert_eq!(pinned[0]["seed"], 42);
+  assert_eq!(pinned, run(Some("42")));
+}
+
+#[test]
+fn test_run_with_summary_csv() {
+  let temp = tempdir().unwrap();
+  let summary_path = temp.path().join("summary.csv");
+
+  let config_str = r#"{
+    "components": {
+      "fixed-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a\n40|b\n20|c\n30|d\n'"]
+      }
+    },
+    "tasks": [
+      {"executor": "fixed-exec", "args": ["fn,with,commas"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--summary-csv")
+    .arg(&summary_path)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd.assert().success();
+
+  let csv = fs::read_to_string(&summary_path).unwrap();
+  let mut lines = csv.lines();
+  let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+  assert_eq!(
+    header,
+    [
+      "task_index",
+      "executor",
+      "args",
+      "count",
+      "min",
+      "median",
+      "mean",
+      "p90",
+      "p95",
+      "p99",
+      "max",
+      "stddev"
+    ]
+  );
+
+  let row = lines.next().unwrap();
+  assert!(row.starts_with(r#"0,fixed-exec,"fn,with,commas","#));
+  let stats: Vec<&str> = row.rsplitn(10, ',').collect();
+  let median_col = header.iter().position(|h| *h == "median").unwrap();
+  let median: f64 = stats[header.len() - 1 - median_col].parse().unwrap();
+  assert_eq!(median, 25.0);
+  assert!(lines.next().is_none());
+}
+
+#[test]
+fn test_run_with_summary_json_and_table() {
+  let temp = tempdir().unwrap();
+  let summary_path = temp.path().join("summary.json");
+
+  let config_str = r#"{
+    "components": {
+      "fixed-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a\n40|b\n20|c\n30|d\n'"]
+      },
+      "silent-exec": {
+        "type": "executor",
+        "command": "true"
+      }
+    },
+    "tasks": [
+      {"executor": "fixed-exec", "args": ["quick"]},
+      {"executor": "silent-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--summary-json")
+    .arg(&summary_path)
+    .arg("--print-summary")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let assert = run_cmd.assert().success();
+  let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+  assert!(stderr.contains("task  executor          count"));
+  assert!(stderr.contains("   0  fixed-exec quick      4  10.00   25.00"));
+
+  // Tasks without any results are skipped.
+  let summary: Vec<Value> =
+    serde_json::from_str(&fs::read_to_string(&summary_path).unwrap()).unwrap();
+  assert_eq!(summary.len(), 1);
+  assert_eq!(summary[0]["executor"], "fixed-exec");
+  assert_eq!(summary[0]["count"], 4);
+  assert_eq!(summary[0]["median"], 25.0);
+  assert_eq!(summary[0]["p90"], 37.0);
+}
+
+#[test]
+fn test_run_with_stdin_header_args_delivery() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "case-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo case_1; echo case_2"]
+      },
+      "header-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r header; while read -r l; do echo \"1|$#:$header:$l\"; done"],
+        "args_delivery": "stdin_header"
+      }
+    },
+    "generator": {"name": "case-gen"},
+    "tasks": [
+      {"executor": "header-exec", "args": ["fn_a", "fn_b"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--seed")
+    .arg("1")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      r#""args":["fn_a","fn_b"],"rep_index":0,"seed":1,"data_token":"0:[\"fn_a\",\"fn_b\"]:case_1""#,
+    ))
+    .stdout(predicate::str::contains(
+      r#""data_token":"0:[\"fn_a\",\"fn_b\"]:case_2""#,
+    ));
+}
+
+#[test]
+fn test_build_diff_only() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  fs::create_dir_all(&components_dir).unwrap();
+
+  let options = CopyOptions::new();
+  copy("tests/fixtures", temp.path(), &options).unwrap();
+  fs::rename(temp.path().join("fixtures"), &components_dir).unwrap();
+
+  let build = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--manifest-filename")
+      .arg("manifest.json")
+      .env("NO_COLOR", "1");
+    cmd
+  };
+
+  build().assert().success();
+  let manifest_path = temp.path().join("manifest.json");
+  let original = fs::read_to_string(&manifest_path).unwrap();
+
+  let impafile = components_dir.join("py-gen-e2e/impafile.toml");
+  let content = fs::read_to_string(&impafile)
+    .unwrap()
+    .replace(r#"command = "python3""#, r#"command = "python3.12""#);
+  fs::write(&impafile, content).unwrap();
+
+  build()
+    .arg("--diff-only")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("~ py-gen-e2e"))
+    .stdout(predicate::str::contains(
+      r#"command: "python3" -> "python3.12""#,
+    ))
+    .stdout(predicate::str::contains("~ python-e2e").not());
+
+  assert_eq!(fs::read_to_string(&manifest_path).unwrap(), original);
+
+  build()
+    .arg("--diff")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("~ py-gen-e2e"));
+
+  assert_ne!(fs::read_to_string(&manifest_path).unwrap(), original);
+}
+
+#[test]
+fn test_run_with_executor_timeout() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "slow-exec": {
+        "type": "executor",
+        "command": "sleep",
+        "args": ["5"]
+      }
+    },
+    "tasks": [
+      {"executor": "slow-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--timeout")
+    .arg("200ms")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .timeout(std::time::Duration::from_secs(4))
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Executor process timed out after 200ms",
+    ));
+
+  let mut zero_cmd = Command::new(cargo::cargo_bin!("impa"));
+  zero_cmd
+    .arg("run")
+    .arg("--timeout")
+    .arg("0s")
+    .env("NO_COLOR", "1");
+
+  zero_cmd.assert().failure().stderr(predicate::str::contains(
+    "duration must be greater than zero",
+  ));
+}
+
+#[test]
+fn test_run_with_line_timeout() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "stalling-exec": {
+        "type": "executor",
+        "command": "sh",
+        "args": ["-c", "echo '1|first'; sleep 5; echo '2|second'"]
+      }
+    },
+    "tasks": [
+      {"executor": "stalling-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--line-timeout")
+    .arg("300ms")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .timeout(std::time::Duration::from_secs(4))
+    .assert()
+    .failure()
+    .stdout(predicate::str::contains(r#""data_token":"first""#))
+    .stderr(predicate::str::contains(
+      "Executor produced no output line for 300ms",
+    ));
+}
+
+#[test]
+fn test_run_with_invalid_utf8_output() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "binary-exec": {
+        "type": "executor",
+        "command": "printf",
+        "args": ["1|a\\n2|b\\377\\n3|c\\n"]
+      }
+    },
+    "tasks": [
+      {"executor": "binary-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().clone();
+  let tokens: Vec<String> = String::from_utf8(output.stdout)
+    .unwrap()
+    .lines()
+    .map(|line| {
+      let row: Value = serde_json::from_str(line).unwrap();
+      row["data_token"].as_str().unwrap().to_owned()
+    })
+    .collect();
+  assert_eq!(tokens, ["a", "b\u{FFFD}", "c"]);
+  assert!(String::from_utf8_lossy(&output.stderr).contains("not valid UTF-8"));
+}
+
+#[test]
+fn test_run_with_crlf_output() {
+  let temp = tempdir().unwrap();
+
+  // As written by e.g. a PowerShell executor.
+  let config_str = r#"{
+    "components": {
+      "crlf-exec": {
+        "type": "executor",
+        "command": "printf",
+        "args": ["1234|case1\\r\\n56|case2|{\"ok\":true}\\r\\n"]
+      }
+    },
+    "tasks": [
+      {"executor": "crlf-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      r#""data_token":"case1","metric":1234,"status":"ok"}"#,
+    ))
+    .stdout(predicate::str::contains(
+      r#""data_token":"case2","exec_meta":{"ok":true},"metric":56,"status":"ok"}"#,
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_with_kill_grace() {
+  let temp = tempdir().unwrap();
+  let marker = temp.path().join("terminated");
+
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "idle-gen": {{
+        "type": "generator",
+        "command": "sleep",
+        "args": ["30"]
+      }},
+      "trapping-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "trap 'touch \"{}\"; kill $!; exit 0' TERM; sleep 5 & wait"]
+      }}
+    }},
+    "generator": {{"name": "idle-gen"}},
+    "tasks": [
+      {{"executor": "trapping-exec"}}
+    ]
+  }}"#,
+    marker.display()
+  );
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--timeout")
+    .arg("200ms")
+    .arg("--kill-grace")
+    .arg("2s")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  // The idle generator is killed along with the timed-out executor.
+  run_cmd
+    .timeout(std::time::Duration::from_secs(6))
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Executor process timed out after 200ms",
+    ));
+  assert!(marker.exists(), "executor should have handled SIGTERM");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_with_generator_memory_limit() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "hungry-gen": {
+        "type": "generator",
+        "command": "python3",
+        "args": ["-c", "x = bytearray(512 * 1024 * 1024); print(len(x))"]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "hungry-gen"},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--generator-max-mem")
+    .arg("64M")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd.assert().failure().stderr(predicate::str::contains(
+    "Generator process was terminated under resource limits (max_mem=67108864 bytes)",
+  ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_with_generator_limits_classifies_failures() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "spin-gen": {
+        "type": "generator",
+        "command": "sh",
+        "args": ["-c", "while :; do :; done"]
+      },
+      "failing-gen": {
+        "type": "generator",
+        "command": "sh",
+        "args": ["-c", "echo 1; exit 3"]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "spin-gen"},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let run = |generator: &str, extra: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--set")
+      .arg(format!("generator.name={}", generator))
+      .args(["--generator-max-cpu-secs", "1", "--generator-max-mem", "1G"])
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd.timeout(std::time::Duration::from_secs(10)).assert()
+  };
+
+  run("spin-gen", &[]).failure().stderr(predicate::str::contains(
+    "Generator process was terminated under resource limits (max_mem=1073741824 bytes, max_cpu=1s)",
+  ));
+
+  // A plain non-zero exit is an ordinary failure, even with limits set.
+  run("failing-gen", &[])
+    .failure()
+    .stderr(predicate::str::contains(
+      "Generator process failed with exit code: Some(3)",
+    ))
+    .stderr(predicate::str::contains("resource limits").not());
+  run("failing-gen", &["--no-fail-on-error"])
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"1""#));
+}
+
+#[test]
+fn test_run_with_min_metric() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "mixed-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '0.2|noise\n5|small\n1200|large\n0|empty\n'"]
+      }
+    },
+    "tasks": [
+      {"executor": "mixed-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--min-metric")
+    .arg("5")
+    .env("RUST_LOG", "info")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"small""#))
+    .stdout(predicate::str::contains(r#""data_token":"large""#))
+    .stdout(predicate::str::contains(r#""data_token":"noise""#).not())
+    .stdout(predicate::str::contains(r#""data_token":"empty""#).not())
+    .stderr(predicate::str::contains("dropped=2"));
+}
+
+#[test]
+fn test_run_with_config_template() {
+  let temp = tempdir().unwrap();
+  fs::write(
+    temp.path().join("algo.tmpl"),
+    "task={task_index};args={args}",
+  )
+  .unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "config-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "f=${1#--config=}; printf '1|%s@%s\n' \"$(cat \"$f\")\" \"$f\"", "_", "--config={config_path}"],
+        "working_dir": ".",
+        "config_template": "algo.tmpl"
+      }
+    },
+    "tasks": [
+      {"executor": "config-exec", "args": ["fn_a"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let result: Value = serde_json::from_slice(&output).unwrap();
+  let (rendered, config_path) = result["data_token"]
+    .as_str()
+    .unwrap()
+    .split_once('@')
+    .unwrap();
+
+  assert_eq!(rendered, r#"task=0;args=["fn_a"]"#);
+  assert!(!std::path::Path::new(config_path).exists());
+}
+
+#[test]
+fn test_run_output_is_byte_stable() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "meta-order-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '7|t|{\"zeta\":1,\"alpha\":{\"y\":2,\"b\":3}}\n'"]
+      }
+    },
+    "attributes": {"zone": "eu", "arch": "x86_64", "mid": {"z": 1, "a": 2}},
+    "tasks": [
+      {"executor": "meta-order-exec", "attributes": {"build": "release"}}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd.assert().success().get_output().stdout.clone()
+  };
+
+  let first = run();
+  assert_eq!(first, run());
+  assert_eq!(
+    String::from_utf8(first).unwrap().trim_end(),
+    r#"{"task_index":0,"executor":"meta-order-exec","rep_index":0,"attributes":{"arch":"x86_64","build":"release","mid":{"a":2,"z":1},"zone":"eu"},"data_token":"t","exec_meta":{"alpha":{"b":3,"y":2},"zeta":1},"metric":7,"status":"ok"}"#
+  );
+}
+
+#[test]
+fn test_run_resume_after_interruption() {
+  let temp = tempdir().unwrap();
+  let checkpoint = temp.path().join("checkpoint.jsonl");
+  let unblock = temp.path().join("unblock");
+
+  // The second repetition hangs until the `unblock` marker exists, so the first
+  // run is killed after completing only rep 0.
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "flaky-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo \"1|rep$IMPALAB_REP_INDEX\"; if [ \"$IMPALAB_REP_INDEX\" = 1 ] && [ ! -e '{}' ]; then exec sleep 30 >/dev/null 2>&1; fi"]
+      }}
+    }},
+    "reps": 3,
+    "tasks": [
+      {{"executor": "flaky-exec"}}
+    ]
+  }}"#,
+    unblock.display()
+  );
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--resume")
+      .arg(&checkpoint)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str.clone());
+    run_cmd
+  };
+
+  run()
+    .timeout(std::time::Duration::from_secs(2))
+    .assert()
+    .failure();
+
+  let recorded = fs::read_to_string(&checkpoint).unwrap();
+  assert_eq!(recorded.lines().count(), 1);
+  assert!(recorded.contains(r#""rep_index":0"#));
+
+  fs::write(&unblock, "").unwrap();
+  let output = run().assert().success().get_output().stdout.clone();
+  let rep_indices: Vec<u64> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|l| {
+      serde_json::from_str::<Value>(l).unwrap()["rep_index"]
+        .as_u64()
+        .unwrap()
+    })
+    .collect();
+  assert_eq!(rep_indices, vec![1, 2]);
+
+  let recorded = fs::read_to_string(&checkpoint).unwrap();
+  assert_eq!(recorded.lines().count(), 3);
+}
+
+#[test]
+fn test_run_applies_run_env_from_impafile() {
+  let temp = tempdir().unwrap();
+  let cmp_dir = temp.path().join("env_exec");
+  fs::create_dir(&cmp_dir).unwrap();
+  fs::write(
+    cmp_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "env-exec"
+type = "executor"
+
+[components.run]
+command = "bash"
+args = ["-c", "echo \"1|$LIB_FLAVOR\""]
+
+[components.run.env]
+LIB_FLAVOR = "shared"
+"#,
+  )
+  .unwrap();
+
+  let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+  build_cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(temp.path())
+    .arg("--root-dir")
+    .arg(temp.path())
+    .env("NO_COLOR", "1");
+  build_cmd.assert().success();
+
+  let manifest = fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap();
+  assert!(manifest.contains(r#""run_env""#));
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(r#"{"tasks": [{"executor": "env-exec"}]}"#);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"shared""#));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_run_with_parquet_output() {
+  use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+  let temp = tempdir().unwrap();
+  let output = temp.path().join("results.parquet");
+
+  let config_str = r#"{
+    "components": {
+      "multi-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a\n20|b|{\"k\":1}\n30|c\n'"]
+      }
+    },
+    "reps": 2,
+    "tasks": [
+      {"executor": "multi-exec", "args": ["quick"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--output-format")
+    .arg("parquet")
+    .arg("--output-file")
+    .arg(&output)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::is_empty());
+
+  let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&output).unwrap())
+    .unwrap()
+    .build()
+    .unwrap();
+  let batches: Vec<_> = reader.map(Result::unwrap).collect();
+  let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+  assert_eq!(rows, 6);
+
+  let schema = batches[0].schema();
+  let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+  assert_eq!(
+    names,
+    [
+      "task_index",
+      "executor",
+      "args",
+      "rep_index",
+      "case_id",
+      "size",
+      "seed",
+      "attributes",
+      "input_checksum",
+      "orchestrator_duration_ms",
+      "data_token",
+      "gen_meta",
+      "exec_meta",
+      "metric",
+      "metrics",
+      "status"
+    ]
+  );
+
+  let exec_meta_nulls: usize = batches
+    .iter()
+    .map(|b| b.column_by_name("exec_meta").unwrap().null_count())
+    .sum();
+  assert_eq!(exec_meta_nulls, 4);
+}
+
+#[test]
+fn test_run_with_csv_and_pretty_output() {
+  let temp = tempdir().unwrap();
+  let config_str = r#"{
+    "components": {
+      "exec-a": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a|{\"k\":\"x,y\"}\n'"]
+      },
+      "exec-b": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '20|b\n'"]
+      }
+    },
+    "tasks": [
+      {"executor": "exec-a", "args": ["quick"]},
+      {"executor": "exec-b"}
+    ]
+  }"#;
+
+  let run = |format: &str| {
+    let output = Command::new(cargo::cargo_bin!("impa"))
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--output-format")
+      .arg(format)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str)
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+  };
+
+  // One header for the whole run, however many pipelines produced rows.
+  assert_eq!(
+    run("csv"),
+    "task_index,executor,args,rep_index,case_id,size,seed,attributes,\
+input_checksum,orchestrator_duration_ms,data_token,gen_meta,exec_meta,metric,metrics,status\n\
+0,exec-a,quick,0,,,,,,,a,,\"{\"\"k\"\":\"\"x,y\"\"}\",10,,ok\n\
+1,exec-b,,0,,,,,,,b,,,20,,ok\n"
+  );
+
+  let pretty = run("pretty");
+  assert!(pretty.starts_with("{\n  \"task_index\": 0,\n"));
+  let rows: Vec<serde_json::Value> = serde_json::Deserializer::from_str(&pretty)
+    .into_iter()
+    .map(Result::unwrap)
+    .collect();
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[1]["metric"], 20);
+}
+
+#[test]
+fn test_compare_runs() {
+  let temp = tempdir().unwrap();
+  let old = temp.path().join("old.jsonl");
+  let new = temp.path().join("new.jsonl");
+
+  fs::write(
+    &old,
+    r#"{"task_index":0,"executor":"sort","args":["quick"],"rep_index":0,"data_token":"small","metric":100}
+{"task_index":0,"executor":"sort","args":["quick"],"rep_index":1,"data_token":"small","metric":102}
+{"task_index":0,"executor":"sort","args":["quick"],"rep_index":0,"data_token":"large","metric":1000}
+{"task_index":1,"executor":"search","rep_index":0,"data_token":"small","metric":50}
+{"task_index":1,"executor":"search","rep_index":0,"data_token":"retired","metric":7}
+{"task_index":1,"executor":"search","rep_index":1,"data_token":"","status":"crashed"}
+"#,
+  )
+  .unwrap();
+  fs::write(
+    &new,
+    r#"{"task_index":0,"executor":"sort","args":["quick"],"rep_index":0,"data_token":"small","metric":150}
+{"task_index":0,"executor":"sort","args":["quick"],"rep_index":1,"data_token":"small","metric":152}
+{"task_index":0,"executor":"sort","args":["quick"],"rep_index":0,"data_token":"large","metric":500}
+{"task_index":1,"executor":"search","rep_index":0,"data_token":"small","metric":51}
+{"task_index":1,"executor":"search","rep_index":0,"data_token":"fresh","metric":9}
+"#,
+  )
+  .unwrap();
+
+  let mut json_cmd = Command::new(cargo::cargo_bin!("impa"));
+  json_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--json")
+    .env("NO_COLOR", "1");
+  // A regression fails the comparison, which still prints its report.
+  let output = json_cmd.assert().failure().get_output().stdout.clone();
+  let report: Value = serde_json::from_slice(&output).unwrap();
+
+  assert_eq!(
+    report["summary"],
+    serde_json::json!({
+      "improvements": 1,
+      "regressions": 1,
+      "unchanged": 1,
+      "only_old": 1,
+      "only_new": 1
+    })
+  );
+  let verdict_of = |token: &str, executor: &str| {
+    report["entries"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|e| e["data_token"] == token && e["executor"] == executor)
+      .map(|e| e["verdict"].as_str().unwrap().to_owned())
+  };
+  assert_eq!(verdict_of("small", "sort").as_deref(), Some("regression"));
+  assert_eq!(verdict_of("large", "sort").as_deref(), Some("improvement"));
+  assert_eq!(verdict_of("small", "search").as_deref(), Some("unchanged"));
+  assert_eq!(report["only_old"][0]["data_token"], "retired");
+  assert_eq!(report["only_new"][0]["data_token"], "fresh");
+
+  let mut table_cmd = Command::new(cargo::cargo_bin!("impa"));
+  table_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--no-fail-on-regression")
+    .env("NO_COLOR", "1");
+  table_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("REGRESSION"))
+    .stdout(predicate::str::contains(
+      "1 improvements, 1 regressions, 1 unchanged (threshold 5%), 1 only in old, 1 only in new",
+    ));
+}
+
+#[test]
+fn test_compare_skips_non_result_objects_and_zero_baselines() {
+  let temp = tempdir().unwrap();
+  let old = temp.path().join("old.jsonl");
+  let new = temp.path().join("new.jsonl");
+
+  fs::write(
+    &old,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"data_token":"small","metric":100}
+{"type":"process_status","task_index":0,"executor":"sort","rep_index":0,"exec_code":0,"gen_code":null}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"empty","metric":0}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"noop","metric":0}
+{"type":"summary","task_index":0,"executor":"sort","count":3,"median":0}
+"#,
+  )
+  .unwrap();
+  fs::write(
+    &new,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"data_token":"small","metric":101}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"empty","metric":3}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"noop","metric":0}
+{"type":"process_status","task_index":0,"executor":"sort","rep_index":0,"exec_code":0,"gen_code":null}
+"#,
+  )
+  .unwrap();
+
+  let mut json_cmd = Command::new(cargo::cargo_bin!("impa"));
+  json_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--json")
+    .env("NO_COLOR", "1");
+  let output = json_cmd.assert().failure().get_output().stdout.clone();
+  let report: Value = serde_json::from_slice(&output).unwrap();
+
+  assert_eq!(
+    report["summary"],
+    serde_json::json!({
+      "improvements": 0,
+      "regressions": 1,
+      "unchanged": 2,
+      "only_old": 0,
+      "only_new": 0
+    })
+  );
+  let entry = |token: &str| {
+    report["entries"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|e| e["data_token"] == token)
+      .unwrap()
+      .clone()
+  };
+  // Any growth from a zero baseline is a regression without a finite change.
+  assert_eq!(entry("empty")["verdict"], "regression");
+  assert_eq!(entry("empty")["change_pct"], Value::Null);
+  assert_eq!(entry("noop")["verdict"], "unchanged");
+  assert_eq!(entry("noop")["speedup"], Value::Null);
+
+  let mut table_cmd = Command::new(cargo::cargo_bin!("impa"));
+  table_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--no-fail-on-regression")
+    .env("NO_COLOR", "1");
+  table_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("n/a"));
+}
+
+#[test]
+fn test_compare_keys_rows_by_size() {
+  let temp = tempdir().unwrap();
+  let old = temp.path().join("old.jsonl");
+  let new = temp.path().join("new.jsonl");
+
+  fs::write(
+    &old,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"size":100,"data_token":"sorted","metric":10}
+{"task_index":0,"executor":"sort","rep_index":0,"size":1000,"data_token":"sorted","metric":1000}
+"#,
+  )
+  .unwrap();
+  fs::write(
+    &new,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"size":100,"data_token":"sorted","metric":10}
+{"task_index":0,"executor":"sort","rep_index":0,"size":1000,"data_token":"sorted","metric":2000}
+"#,
+  )
+  .unwrap();
+
+  // Rows of each size are compared with their own baseline, not pooled.
+  let mut json_cmd = Command::new(cargo::cargo_bin!("impa"));
+  json_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--json")
+    .env("NO_COLOR", "1");
+  let output = json_cmd.assert().failure().get_output().stdout.clone();
+  let report: Value = serde_json::from_slice(&output).unwrap();
+  let entries = report["entries"].as_array().unwrap();
+  assert_eq!(entries.len(), 2);
+  assert_eq!(entries[0]["size"], 100);
+  assert_eq!(entries[0]["verdict"], "unchanged");
+  assert_eq!(entries[1]["size"], 1000);
+  assert_eq!(entries[1]["verdict"], "regression");
+
+  let mut table_cmd = Command::new(cargo::cargo_bin!("impa"));
+  table_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .arg("--no-fail-on-regression")
+    .env("NO_COLOR", "1");
+  table_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("sort size=100 @ sorted"))
+    .stdout(predicate::str::contains("sort size=1000 @ sorted"));
+}
+
+#[test]
+fn test_compare_absolute_threshold_and_regression_exit_status() {
+  let temp = tempdir().unwrap();
+  let old = temp.path().join("old.jsonl");
+  let new = temp.path().join("new.jsonl");
+
+  fs::write(
+    &old,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"data_token":"small","metric":100}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"large","metric":1000}
+"#,
+  )
+  .unwrap();
+  fs::write(
+    &new,
+    r#"{"task_index":0,"executor":"sort","rep_index":0,"data_token":"small","metric":150}
+{"task_index":0,"executor":"sort","rep_index":0,"data_token":"large","metric":1040}
+"#,
+  )
+  .unwrap();
+
+  // +50% and +4%, but only the +40ns change reaches an absolute 40ns threshold.
+  let mut absolute_cmd = Command::new(cargo::cargo_bin!("impa"));
+  absolute_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .args(["--threshold", "40ns", "--no-fail-on-regression"])
+    .env("NO_COLOR", "1");
+  absolute_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      "0 improvements, 2 regressions, 0 unchanged (threshold 40ns)",
+    ));
+
+  let mut lenient_cmd = Command::new(cargo::cargo_bin!("impa"));
+  lenient_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .args(["--threshold", "1us"])
+    .env("NO_COLOR", "1");
+  lenient_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("2 unchanged (threshold 1us)"));
+
+  let mut failing_cmd = Command::new(cargo::cargo_bin!("impa"));
+  failing_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .args(["--threshold", "10%"])
+    .env("NO_COLOR", "1");
+  failing_cmd
+    .assert()
+    .failure()
+    .stdout(predicate::str::contains(
+      "1 regressions, 1 unchanged (threshold 10%)",
+    ))
+    .stderr(predicate::str::contains(
+      "impa compare found 1 regression(s)",
+    ));
+
+  let mut invalid_cmd = Command::new(cargo::cargo_bin!("impa"));
+  invalid_cmd
+    .arg("compare")
+    .arg(&old)
+    .arg(&new)
+    .args(["--threshold", "fast"])
+    .env("NO_COLOR", "1");
+  invalid_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("invalid threshold 'fast'"));
+}
+
+#[test]
+fn test_init_scaffolds_buildable_components() {
+  let temp = tempdir().unwrap();
+
+  for (dir, component_type) in [("my-gen", "generator"), ("my-exec", "executor")] {
+    let mut init_cmd = Command::new(cargo::cargo_bin!("impa"));
+    init_cmd
+      .arg("init")
+      .arg(temp.path().join(dir))
+      .arg("--type")
+      .arg(component_type)
+      .arg("--language")
+      .arg("bash")
+      .env("NO_COLOR", "1");
+    init_cmd
+      .assert()
+      .success()
+      .stdout(predicate::str::contains("impafile.toml"));
+  }
+
+  let mut again_cmd = Command::new(cargo::cargo_bin!("impa"));
+  again_cmd
+    .arg("init")
+    .arg(temp.path().join("my-exec"))
+    .arg("--type")
+    .arg("executor")
+    .arg("--language")
+    .arg("python")
+    .env("NO_COLOR", "1");
+  again_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("An impafile already exists"));
+
+  let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+  build_cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(temp.path())
+    .arg("--root-dir")
+    .arg(temp.path())
+    .env("NO_COLOR", "1");
+  build_cmd.assert().success();
+
+  let manifest: Value =
+    serde_json::from_str(&fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap())
+      .unwrap();
+  assert_eq!(manifest["components"]["my-gen"]["type"], "generator");
+  assert_eq!(manifest["components"]["my-exec"]["type"], "executor");
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(
+      r#"{"generator": {"name": "my-gen", "seed": 1}, "tasks": [{"executor": "my-exec"}]}"#,
+    );
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  assert_eq!(String::from_utf8(output).unwrap().lines().count(), 10);
+}
+
+#[test]
+fn test_run_emits_process_status() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seq-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo case_1"]
+      },
+      "failing-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r l; do echo \"1|$l\"; done; exit 3"]
+      }
+    },
+    "generator": {"name": "seq-gen"},
+    "tasks": [
+      {"executor": "failing-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--emit-process-status")
+    .args(["--output-format", "json"])
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().failure().get_output().clone();
+  // Status lines stay out of the result array on stdout.
+  let rows: Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(rows.as_array().unwrap().len(), 2);
+  assert!(rows[0].get("type").is_none());
+  assert_eq!(rows[1]["status"], "nonzero_exit");
+  let status: Value = String::from_utf8(output.stderr)
+    .unwrap()
+    .lines()
+    .find(|l| l.starts_with(r#"{"type":"process_status""#))
+    .map(|l| serde_json::from_str(l).unwrap())
+    .expect("a process_status line should be written to stderr");
+
+  assert_eq!(status["executor"], "failing-exec");
+  assert_eq!(status["exec_code"], 3);
+  assert_eq!(status["gen_code"], 0);
+
+  let status_file = temp.path().join("status.jsonl");
+  let mut file_cmd = Command::new(cargo::cargo_bin!("impa"));
+  file_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--process-status-file")
+    .arg(&status_file)
+    .args(["--output-format", "csv"])
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+  file_cmd
+    .assert()
+    .failure()
+    .stdout(predicate::str::contains("process_status").not());
+
+  let statuses: Vec<Value> = fs::read_to_string(&status_file)
+    .unwrap()
+    .lines()
+    .map(|l| serde_json::from_str(l).unwrap())
+    .collect();
+  assert_eq!(statuses.len(), 1);
+  assert_eq!(statuses[0]["exec_code"], 3);
+}
+
+#[test]
+fn test_run_retries_only_matching_stderr() {
+  let temp = tempdir().unwrap();
+
+  // Fails on the first attempt with `message` on stderr, succeeds afterwards.
+  let run = |message: &str| {
+    let attempts = temp.path().join(format!("attempts-{}", message.len()));
+    let config_str = format!(
+      r#"{{
+      "components": {{
+        "flaky-exec": {{
+          "type": "executor",
+          "command": "bash",
+          "args": ["-c", "echo x >> '{attempts}'; if [ $(wc -l < '{attempts}') -eq 1 ]; then echo '{message}' >&2; exit 1; fi; echo '1|ok'"]
+        }}
+      }},
+      "tasks": [
+        {{"executor": "flaky-exec"}}
+      ]
+    }}"#,
+      attempts = attempts.display(),
+    );
+
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--retries")
+      .arg("2")
+      .arg("--retry-if-stderr-matches")
+      .arg("(?i)resource temporarily unavailable")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    let assert = run_cmd.assert();
+    let count = fs::read_to_string(&attempts).unwrap().lines().count();
+    (assert, count)
+  };
+
+  let (transient, attempts) = run("fork: Resource temporarily unavailable");
+  transient
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"ok""#));
+  assert_eq!(attempts, 2);
+
+  let (permanent, attempts) = run("segmentation fault");
+  permanent.failure();
+  assert_eq!(attempts, 1);
+}
+
+#[test]
+fn test_run_retried_pipeline_writes_final_attempt_rows_only() {
+  let temp = tempdir().unwrap();
+  let attempts = temp.path().join("attempts");
+
+  // Prints a row on every attempt, but only the second one succeeds.
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "flaky-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo x >> '{attempts}'; n=$(wc -l < '{attempts}'); echo \"1|attempt-$n\"; [ $n -gt 1 ]"]
+      }}
+    }},
+    "tasks": [
+      {{"executor": "flaky-exec"}}
+    ]
+  }}"#,
+    attempts = attempts.display(),
+  );
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--retries")
+    .arg("1")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let rows: Vec<Value> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|l| serde_json::from_str(l).unwrap())
+    .collect();
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0]["data_token"], "attempt-2");
+}
+
+#[test]
+fn test_run_regenerate_per_rep() {
+  let temp = tempdir().unwrap();
+  let invocations = temp.path().join("invocations");
+
+  // The generator's output differs on every invocation unless it is replayed.
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "nonce-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo x >> '{}'; echo \"seed$IMPALAB_SEED-$(cat /proc/sys/kernel/random/uuid)\""]
+      }},
+      "echo-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }}
+    }},
+    "generator": {{"name": "nonce-gen", "seed": 10}},
+    "reps": 3,
+    "tasks": [
+      {{"executor": "echo-exec"}}
+    ]
+  }}"#,
+    invocations.display()
+  );
+
+  let tokens = |regenerate: &str| {
+    let _ = fs::remove_file(&invocations);
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--regenerate-per-rep")
+      .arg(regenerate)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str.clone());
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    let tokens: Vec<String> = String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|l| {
+        serde_json::from_str::<Value>(l).unwrap()["data_token"]
+          .as_str()
+          .unwrap()
+          .to_owned()
+      })
+      .collect();
+    let generated = fs::read_to_string(&invocations).unwrap().lines().count();
+    (tokens, generated)
+  };
+
+  let (cached, generated) = tokens("false");
+  assert_eq!(cached.len(), 3);
+  assert!(cached.iter().all(|t| t == &cached[0]));
+  assert_eq!(generated, 1);
+
+  let (fresh, generated) = tokens("true");
+  assert_eq!(fresh.len(), 3);
+  for (rep, token) in fresh.iter().enumerate() {
+    assert!(token.starts_with(&format!("seed{}-", 10 + rep)));
+  }
+  assert_eq!(generated, 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_classifies_truncated_final_line() {
+  let temp = tempdir().unwrap();
+
+  let config = |script: &str| {
+    format!(
+      r#"{{
+    "components": {{
+      "partial-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "{}"]
+      }}
+    }},
+    "tasks": [
+      {{"executor": "partial-exec"}}
+    ]
+  }}"#,
+      script
+    )
+  };
+
+  let mut killed_cmd = Command::new(cargo::cargo_bin!("impa"));
+  killed_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--timeout")
+    .arg("300ms")
+    .env("NO_COLOR", "1")
+    .write_stdin(config("printf '1|ok\\\\n12'; exec sleep 5"));
+
+  killed_cmd
+    .timeout(std::time::Duration::from_secs(4))
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Executor was timed out after 300ms mid-write; final output line is truncated: 12",
+    ));
+
+  let mut exited_cmd = Command::new(cargo::cargo_bin!("impa"));
+  exited_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config("printf '1|ok\\\\n12'"));
+
+  exited_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Malformed output line from executor: 12",
+    ))
+    .stderr(predicate::str::contains("truncated").not());
+}
+
+#[test]
+fn test_build_dry_run() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  fs::create_dir_all(&components_dir).unwrap();
+
+  let options = CopyOptions::new();
+  copy("tests/fixtures", temp.path(), &options).unwrap();
+  fs::rename(temp.path().join("fixtures"), &components_dir).unwrap();
+
+  let built_dir = components_dir.join("built-exec");
+  fs::create_dir_all(&built_dir).unwrap();
+  fs::write(
+    built_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "built-exec"
+type = "executor"
+build = { command = "touch", args = ["built.marker"] }
+run = { command = "true" }
+"#,
+  )
+  .unwrap();
+
+  let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+  cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(&components_dir)
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--manifest-filename")
+    .arg("manifest.json")
+    .arg("--dry-run")
+    .env("NO_COLOR", "1");
+
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      "build built-exec (Executor): touch built.marker",
+    ))
+    .stdout(predicate::str::contains("skip  python-e2e (no build step)"))
+    .stdout(predicate::str::contains("skip  py-gen-e2e (no build step)"))
+    .stdout(predicate::str::contains(r#""meta-gen": {"#));
+
+  assert!(!built_dir.join("built.marker").exists());
+  assert!(!temp.path().join("manifest.json").exists());
+}
+
+#[test]
+fn test_run_reads_seed_run_id_and_labels_from_env() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"seed:$IMPALAB_SEED\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen", "seed": 1},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .env("IMPA_SEED", "42")
+      .env("IMPA_RUN_ID", "job-7")
+      .env("IMPA_LABELS", "zone=eu,shard=3")
+      .write_stdin(config_str);
+    run_cmd
+  };
+
+  run()
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"seed:42""#))
+    .stdout(predicate::str::contains(
+      r#""attributes":{"run_id":"job-7","shard":"3","zone":"eu"}"#,
+    ));
+
+  // CLI flags take precedence over the environment.
+  run()
+    .arg("--seed")
+    .arg("7")
+    .arg("--run-id")
+    .arg("job-8")
+    .arg("--labels")
+    .arg("zone=us")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"seed:7""#))
+    .stdout(predicate::str::contains(
+      r#""attributes":{"run_id":"job-8","zone":"us"}"#,
+    ));
+
+  run()
+    .env("IMPA_LABELS", "not-a-label")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "invalid label 'not-a-label': expected KEY=VALUE",
+    ));
+}
+
+#[test]
+fn test_run_progress_is_disabled_without_terminal() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|ok'"]
+      }
+    },
+    "tasks": [
+      {"executor": "echo-exec", "reps": 3}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--progress")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().clone();
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert_eq!(stdout.lines().count(), 3);
+  assert!(!stderr.contains('\x1b'));
+  assert!(!stderr.contains("pipelines"));
+}
+
+#[test]
+fn test_run_streams_json_array() {
+  let temp = tempdir().unwrap();
+
+  let config = |reps: usize| {
+    format!(
+      r#"{{
+    "components": {{
+      "echo-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|a'; echo '2|b'"]
+      }}
+    }},
+    "tasks": [
+      {{"executor": "echo-exec", "reps": {}}}
+    ]
+  }}"#,
+      reps
+    )
+  };
+
+  let run = |reps: usize| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--output-format")
+      .arg("json")
+      .env("NO_COLOR", "1")
+      .write_stdin(config(reps));
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    serde_json::from_slice::<Value>(&output).unwrap()
+  };
+
+  let rows = run(3);
+  let rows = rows.as_array().unwrap();
+  assert_eq!(rows.len(), 6);
+  assert_eq!(rows[5]["data_token"], "b");
+  assert_eq!(rows[5]["rep_index"], 2);
+
+  assert_eq!(run(0), serde_json::json!([]));
+}
+
+#[test]
+fn test_run_fail_if_empty() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "silent-exec": {
+        "type": "executor",
+        "command": "true"
+      }
+    },
+    "tasks": [
+      {"executor": "silent-exec"}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd
+  };
+
+  run().assert().success();
+
+  run()
+    .arg("--fail-if-empty")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Benchmark run produced no result rows",
+    ));
+}
+
+#[test]
+fn test_build_verifies_declared_artifacts() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let component_dir = components_dir.join("c-exec");
+  fs::create_dir_all(&component_dir).unwrap();
+
+  let impafile = |produces: &str| {
+    format!(
+      r#"
+[[components]]
+name = "c-exec"
+type = "executor"
+build = {{ command = "touch", args = ["bench.o"] }}
+produces = [{}]
+run = {{ command = "./bench" }}
+"#,
+      produces
+    )
+  };
+
+  let build = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    cmd
+  };
+
+  fs::write(
+    component_dir.join("impafile.toml"),
+    impafile(r#""bench.o", "bench""#),
+  )
+  .unwrap();
+  build().assert().failure().stderr(predicate::str::contains(
+    "Build step for c-exec succeeded but did not produce artifact: bench",
+  ));
+  assert!(!temp.path().join("impa_manifest.json").exists());
+
+  fs::write(
+    component_dir.join("impafile.toml"),
+    impafile(r#""bench.o""#),
+  )
+  .unwrap();
+  build().assert().success();
+  assert!(temp.path().join("impa_manifest.json").exists());
+}
+
+#[test]
+fn test_build_and_run_with_tags() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let component_dir = components_dir.join("execs");
+  fs::create_dir_all(&component_dir).unwrap();
+
+  fs::write(
+    component_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "fast-exec"
+type = "executor"
+tags = ["fast", "stable"]
+build = { command = "touch", args = ["built-fast"] }
+run = { command = "bash", args = ["-c", "echo '1|fast'"] }
+
+[[components]]
+name = "new-exec"
+type = "executor"
+tags = ["experimental"]
+build = { command = "touch", args = ["built-new"] }
+run = { command = "bash", args = ["-c", "echo '2|new'"] }
+"#,
+  )
+  .unwrap();
+
+  let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+  build_cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(&components_dir)
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--tag")
+    .arg("fast")
+    .env("NO_COLOR", "1");
+  build_cmd.assert().success();
+
+  assert!(component_dir.join("built-fast").exists());
+  assert!(!component_dir.join("built-new").exists());
+  let manifest: Value =
+    serde_json::from_str(&fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap())
+      .unwrap();
+  assert_eq!(
+    manifest["components"]["fast-exec"]["tags"],
+    serde_json::json!(["fast", "stable"])
+  );
+  assert_eq!(
+    manifest["components"]["new-exec"]["tags"],
+    serde_json::json!(["experimental"])
+  );
+
+  let run = |tags: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--tag")
+      .arg(tags)
+      .env("NO_COLOR", "1")
+      .write_stdin(r#"{"tasks": [{"executor": "fast-exec"}, {"executor": "new-exec"}]}"#);
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+
+  let stdout = run("experimental");
+  assert_eq!(stdout.lines().count(), 1);
+  assert!(stdout.contains(r#""data_token":"new""#));
+  assert_eq!(run("stable,experimental").lines().count(), 2);
+}
+
+#[test]
+fn test_list_manifest() {
+  let temp = tempdir().unwrap();
+  let component_dir = temp.path().join("components").join("comps");
+  fs::create_dir_all(&component_dir).unwrap();
+
+  let list = || {
+    let mut list_cmd = Command::new(cargo::cargo_bin!("impa"));
+    list_cmd
+      .arg("list")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    list_cmd
+  };
+
+  list()
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(format!(
+      "Build manifest not found at {}. Run `impa build` first",
+      temp.path().join("impa_manifest.json").display()
+    )));
+
+  fs::write(
+    component_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "seq-gen"
+type = "generator"
+default = true
+run = { command = "seq", args = ["10"] }
+
+[[components]]
+name = "cat-exec"
+type = "executor"
+run = { command = "bash", args = ["-c", "cat -n"] }
+"#,
+  )
+  .unwrap();
+  let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+  build_cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(temp.path().join("components"))
+    .arg("--root-dir")
+    .arg(temp.path())
+    .env("NO_COLOR", "1");
+  build_cmd.assert().success();
+
+  let output = list().assert().success().get_output().stdout.clone();
+  assert_eq!(
+    String::from_utf8(output).unwrap(),
+    "Generators:\n  name      command\n  seq-gen   seq 10  (default)\n\
+     Executors:\n  name      command\n  cat-exec  bash -c 'cat -n'\n\
+     2 component(s).\n"
+  );
+
+  let output = list()
+    .arg("--json")
+    .assert()
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+  let manifest: Value = serde_json::from_slice(&output).unwrap();
+  assert_eq!(manifest["components"]["cat-exec"]["type"], "executor");
+  assert_eq!(manifest["default_generator"], "seq-gen");
+}
+
+#[test]
+fn test_validate_manifest() {
+  let temp = tempdir().unwrap();
+  fs::create_dir_all(temp.path().join("scripts")).unwrap();
+  fs::write(temp.path().join("scripts").join("gen.sh"), "seq 3\n").unwrap();
+  let manifest_path = temp.path().join("impa_manifest.json");
+  fs::write(
+    &manifest_path,
+    r#"{
+    "components": {
+      "seq-gen": {"type": "generator", "command": "bash", "args": ["scripts/gen.sh"]},
+      "echo-exec": {"type": "executor", "command": "bash", "args": ["scripts/run.sh"]},
+      "missing-exec": {"type": "executor", "command": "./no-such-binary"}
+    }
+  }"#,
+  )
+  .unwrap();
+
+  let validate = |strict: bool| {
+    let mut validate_cmd = Command::new(cargo::cargo_bin!("impa"));
+    validate_cmd
+      .arg("validate")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    if strict {
+      validate_cmd.arg("--strict");
+    }
+    validate_cmd.assert()
+  };
+
+  validate(false)
+    .failure()
+    .stdout("missing-exec (run): program not found or not executable: ./no-such-binary\n")
+    .stderr(predicate::str::contains("impa validate found 1 problem(s)"));
+  // Every problem is reported, not just the first.
+  validate(true)
+    .failure()
+    .stdout(predicate::str::contains(
+      "echo-exec (run): argument path does not exist: scripts/run.sh",
+    ))
+    .stdout(predicate::str::contains("missing-exec (run)"))
+    .stdout(predicate::str::contains("scripts/gen.sh").not())
+    .stderr(predicate::str::contains("impa validate found 2 problem(s)"));
+
+  fs::write(temp.path().join("scripts").join("run.sh"), "echo '1|a'\n").unwrap();
+  fs::write(
+    &manifest_path,
+    fs::read_to_string(&manifest_path)
+      .unwrap()
+      .replace("./no-such-binary", "bash"),
+  )
+  .unwrap();
+  validate(true)
+    .success()
+    .stdout(predicate::str::contains("Manifest is valid"));
+}
+
+#[test]
+fn test_build_captures_warnings() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let component_dir = components_dir.join("c-exec");
+  fs::create_dir_all(&component_dir).unwrap();
+
+  fs::write(
+    component_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "c-exec"
+type = "executor"
+build = { command = "bash", args = ["-c", "echo 'warning: unused x'; echo ok; echo 'warning: unused y' >&2; echo 'lint: shadowed z' >&2"] }
+run = { command = "./bench" }
+"#,
+  )
+  .unwrap();
+
+  let build = |extra: &[&str]| {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .args(extra)
+      .env("NO_COLOR", "1");
+    cmd.assert().success();
+    let manifest = fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap();
+    serde_json::from_str::<Value>(&manifest).unwrap()["components"]["c-exec"].clone()
+  };
+
+  assert!(build(&[]).get("build_warnings").is_none());
+
+  let component = build(&["--capture-build-warnings"]);
+  assert_eq!(component["build_warnings"]["count"], 2);
+  assert_eq!(
+    component["build_warnings"]["sample"],
+    serde_json::json!(["warning: unused x", "warning: unused y"])
+  );
+
+  let component = build(&[
+    "--capture-build-warnings",
+    "--build-warning-pattern",
+    "^(warning|lint):",
+  ]);
+  assert_eq!(component["build_warnings"]["count"], 3);
+}
+
+#[test]
+fn test_run_with_emit_rate() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "burst-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "for i in $(seq 1 11); do echo \"$i|row$i\"; done"]
+      }
+    },
+    "tasks": [
+      {"executor": "burst-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--emit-rate")
+    .arg("20")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  // At 20 lines per second, the first line is written immediately and each of
+  // the remaining ten waits 50ms.
+  let start = std::time::Instant::now();
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let elapsed = start.elapsed();
+
+  assert_eq!(String::from_utf8(output).unwrap().lines().count(), 11);
+  assert!(
+    elapsed >= std::time::Duration::from_millis(480),
+    "11 lines at 20/s were emitted in {:?}",
+    elapsed
+  );
+}
+
+#[test]
+fn test_run_with_input_files() {
+  let temp = tempdir().unwrap();
+  let data_dir = temp.path().join("data");
+  fs::create_dir_all(&data_dir).unwrap();
+  fs::write(data_dir.join("a.bin"), "3 1 2\n").unwrap();
+  fs::write(data_dir.join("b.bin"), "5 4\n6 7\n").unwrap();
+  fs::write(data_dir.join("notes.txt"), "ignored\n").unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "never-gen": {
+        "type": "generator",
+        "command": "false"
+      },
+      "count-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo \"$(wc -l)|lines\""]
+      },
+      "words-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo \"$(wc -w)|words\""]
+      }
+    },
+    "generator": {"name": "never-gen"},
+    "tasks": [
+      {"executor": "count-exec"},
+      {"executor": "words-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--input-files")
+    .arg(data_dir.join("*.bin"))
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let rows: Vec<Value> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect();
+  let results: Vec<(String, String, u64)> = rows
+    .iter()
+    .map(|row| {
+      let case_id = row["case_id"].as_str().unwrap();
+      let file = std::path::Path::new(case_id)
+        .file_name()
+        .unwrap()
+        .to_string_lossy();
+      (
+        file.into_owned(),
+        row["data_token"].as_str().unwrap().to_string(),
+        row["metric"].as_u64().unwrap(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    results,
+    [
+      ("a.bin".to_string(), "lines".to_string(), 1),
+      ("b.bin".to_string(), "lines".to_string(), 2),
+      ("a.bin".to_string(), "words".to_string(), 3),
+      ("b.bin".to_string(), "words".to_string(), 4),
+    ]
+  );
+
+  let mut empty_cmd = Command::new(cargo::cargo_bin!("impa"));
+  empty_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--input-files")
+    .arg(data_dir.join("*.csv"))
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  empty_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("matched no files"));
+}
+
+#[test]
+fn test_run_with_input_file_from_stdin() {
+  let temp = tempdir().unwrap();
+  let config = temp.path().join("plan.json");
+  fs::write(
+    &config,
+    r#"{
+      "components": {
+        "count-exec": {
+          "type": "executor",
+          "command": "bash",
+          "args": ["-c", "echo \"$(wc -l)|lines\""]
+        }
+      },
+      "reps": 2,
+      "tasks": [
+        {"executor": "count-exec"}
+      ]
+    }"#,
+  )
+  .unwrap();
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg(&config)
+    .arg("--input-file")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin("a\nb\nc\n");
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let rows: Vec<Value> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect();
+  assert_eq!(rows.len(), 2);
+  for row in &rows {
+    assert_eq!(row["case_id"], "-");
+    assert_eq!(row["metric"], 3);
+  }
+
+  let mut conflict_cmd = Command::new(cargo::cargo_bin!("impa"));
+  conflict_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--input-file")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin("{}");
+  conflict_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "`--input-files -` and `--config -` cannot both read from stdin",
+    ));
+}
+
+#[test]
+fn test_run_with_no_seed() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"seed:${IMPALAB_SEED-unset}\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen", "seed": 42},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--no-seed")
+    .arg("--seed")
+    .arg("7")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let assert = run_cmd.assert().success();
+  let output = assert.get_output();
+  let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+  assert!(stdout.contains(r#""data_token":"seed:unset""#));
+  // The generator seeds itself, so no seed is recorded.
+  assert!(!stdout.contains(r#""seed":"#));
+  assert!(String::from_utf8_lossy(&output.stderr).contains("--seed has no effect with --no-seed"));
+}
+
+#[test]
+fn test_run_with_env_generator_name() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "gen-a": {"type": "generator", "command": "echo", "args": ["from-a"]},
+      "gen-b": {"type": "generator", "command": "echo", "args": ["from-b"]},
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "${GEN_CHOICE}"},
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let run = |choice: Option<&str>| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .env_remove("GEN_CHOICE")
+      .write_stdin(config_str);
+    if let Some(choice) = choice {
+      run_cmd.env("GEN_CHOICE", choice);
+    }
+    run_cmd.assert()
+  };
+
+  run(Some("gen-b"))
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"from-b""#));
+  run(None).failure().stderr(predicate::str::contains(
+    "Environment variable 'GEN_CHOICE' referenced by component name '${GEN_CHOICE}' is not set",
+  ));
+}
+
+#[test]
+fn test_run_with_env_overrides_run_env() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "env-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"$THREADS\""]
+      },
+      "env-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r line; echo \"1|$line-$MODE-$THREADS\""],
+        "run_env": {"MODE": "manifest", "THREADS": "1"}
+      }
+    },
+    "generator": {"name": "env-gen"},
+    "tasks": [
+      {"executor": "env-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--env")
+    .arg("THREADS=4")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"4-manifest-4""#));
+}
+
+#[test]
+fn test_run_with_meta_file() {
+  let temp = tempdir().unwrap();
+  let meta_file = temp.path().join("run.meta.json");
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"seed:$IMPALAB_SEED\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen"},
+    "reps": 2,
+    "tasks": [
+      {"executor": "echo-exec", "args": ["quick"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--seed")
+    .arg("42")
+    .arg("--labels")
+    .arg("env=ci")
+    .arg("--meta-file")
+    .arg(&meta_file)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd.assert().success();
+
+  let meta: serde_json::Value =
+    serde_json::from_str(&fs::read_to_string(&meta_file).unwrap()).unwrap();
+  assert_eq!(meta["impalab_version"], env!("CARGO_PKG_VERSION"));
+  assert_eq!(meta["generator"]["name"], "seed-gen");
+  assert_eq!(meta["generator"]["seed"], 42);
+  assert_eq!(meta["tasks"][0]["executor"], "echo-exec");
+  assert_eq!(meta["tasks"][0]["reps"], 2);
+  assert_eq!(meta["tasks"][0]["attributes"]["env"], "ci");
+  assert!(meta["started_at"].is_string());
+  assert!(meta["finished_at"].is_string());
+  assert!(meta["host"]["os"].is_string());
+}
+
+#[test]
+fn test_run_consumes_progress_lines() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "slow-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '#progress=0.25\n#progress=0.5\n10|a\n#progress=1\n'"]
+      }
+    },
+    "tasks": [
+      {"executor": "slow-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--progress")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let assert = run_cmd.assert().success();
+  let output = assert.get_output();
+  let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+  assert_eq!(stdout.lines().count(), 1);
+  assert!(stdout.contains(r#""data_token":"a""#));
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(!stderr.contains("WARN"), "{}", stderr);
+  assert!(!stderr.contains("ERROR"), "{}", stderr);
+}
+
+#[test]
+fn test_run_with_cache_generator() {
+  let temp = tempdir().unwrap();
+  let runs_log = temp.path().join("runs.log");
+  let cache_dir = temp.path().join("cache");
+
+  let config_str = r#"{
+    "components": {
+      "counting-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo run >> \"$RUNS_LOG\"; echo \"data:$RANDOM\""]
+      },
+      "exec-a": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r line; echo \"1|$line\""]
+      },
+      "exec-b": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r line; echo \"2|$line\""]
+      }
+    },
+    "generator": {"name": "counting-gen", "seed": 9},
+    "tasks": [
+      {"executor": "exec-a"},
+      {"executor": "exec-b"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--cache-generator")
+    .arg("--cache-generator-path")
+    .arg(&cache_dir)
+    .env("RUNS_LOG", &runs_log)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let tokens: Vec<String> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|line| {
+      let row: Value = serde_json::from_str(line).unwrap();
+      row["data_token"].as_str().unwrap().to_string()
+    })
+    .collect();
+  assert_eq!(tokens.len(), 2);
+  assert_eq!(tokens[0], tokens[1]);
+  assert_eq!(fs::read_to_string(&runs_log).unwrap().lines().count(), 1);
+  assert_eq!(
+    fs::read_to_string(cache_dir.join("9.bin")).unwrap(),
+    format!("{}\n", tokens[0])
+  );
+}
+
+#[test]
+fn test_run_preserves_integer_and_float_metrics() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "mixed-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '12|int\n12.5|float\n1e3|exp\n'"]
+      }
+    },
+    "tasks": [
+      {"executor": "mixed-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let stdout = String::from_utf8(output).unwrap();
+  let lines: Vec<&str> = stdout.lines().collect();
+  assert!(lines[0].ends_with(r#""data_token":"int","metric":12,"status":"ok"}"#));
+  assert!(lines[1].ends_with(r#""data_token":"float","metric":12.5,"status":"ok"}"#));
+  assert!(lines[2].ends_with(r#""data_token":"exp","metric":1000.0,"status":"ok"}"#));
+}
+
+#[test]
+fn test_run_with_dump_effective_env() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "echo-exec": {
+        "type": "executor",
+        "command": "/bin/sh",
+        "args": ["-c", "echo '1|a'"]
+      }
+    },
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--env-passthrough")
+    .arg("KEEP_ME")
+    .arg("--env")
+    .arg("EXTRA=1")
+    .arg("--dump-effective-env")
+    .env("KEEP_ME", "kept")
+    .env("DROP_ME", "dropped")
+    .env("RUST_LOG", "impalab=debug")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stderr.clone();
+  let stderr = String::from_utf8_lossy(&output);
+  let dump = stderr
+    .lines()
+    .find(|line| line.contains("Effective environment"))
+    .expect("effective environment should be logged");
+  assert!(dump.contains("component=\"echo-exec\""));
+  assert!(dump.contains("KEEP_ME=kept"));
+  assert!(dump.contains("EXTRA=1"));
+  assert!(dump.contains("IMPALAB_COMPONENT_NAME=echo-exec"));
+  assert!(!dump.contains("DROP_ME"));
+}
+
+#[test]
+fn test_run_with_jobs() {
+  let temp = tempdir().unwrap();
+
+  // `waiter` only succeeds if `signaller` runs while it is still waiting.
+  let config_str = r#"{
+    "components": {
+      "waiter": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "for i in $(seq 100); do [ -f \"$SIGNAL\" ] && break; sleep 0.02; done; [ -f \"$SIGNAL\" ] && echo '1|waited'"]
+      },
+      "signaller": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "touch \"$SIGNAL\"; echo '2|signalled'"]
+      },
+      "failer": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "for i in $(seq 100); do [ -f \"$SIGNAL\" ] && break; sleep 0.02; done; exit 3"]
+      },
+      "slow": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "touch \"$SIGNAL\"; sleep 0.3; echo '3|slow'"]
+      }
+    },
+    "tasks": [
+      {"executor": "waiter"},
+      {"executor": "signaller"}
+    ]
+  }"#;
+
+  let run = |config: &str, signal: &std::path::Path| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--jobs")
+      .arg("2")
+      .env("SIGNAL", signal)
+      .env("NO_COLOR", "1")
+      .write_stdin(config.to_string());
+    run_cmd.assert()
+  };
+
+  let output = run(config_str, &temp.path().join("signal"))
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+  let stdout = String::from_utf8(output).unwrap();
+  assert!(stdout.contains(r#""data_token":"waited""#));
+  assert!(stdout.contains(r#""data_token":"signalled""#));
+
+  // A failing pipeline does not cancel one that is already running. `failer`
+  // waits for `slow` to start so the two overlap.
+  let failing = config_str.replace(
+    r#"{"executor": "waiter"},
+      {"executor": "signaller"}"#,
+    r#"{"executor": "failer"},
+      {"executor": "slow"}"#,
+  );
+  run(&failing, &temp.path().join("started"))
+    .failure()
+    .stdout(predicate::str::contains(r#""data_token":"slow""#))
+    .stderr(predicate::str::contains("exit code: Some(3)"));
+}
+
+#[test]
+fn test_run_with_rep_concurrency() {
+  let temp = tempdir().unwrap();
+  let signal = temp.path().join("signal");
+
+  // The first repetition to start only emits its row if another one runs
+  // while it is still waiting.
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {"type": "generator", "command": "bash", "args": ["-c", "echo $IMPALAB_SEED"]},
+      "pair-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r line; if mkdir \"$SIGNAL.lock\" 2>/dev/null; then for i in $(seq 100); do [ -f \"$SIGNAL\" ] && break; sleep 0.02; done; [ -f \"$SIGNAL\" ] && echo \"1|$line\"; else touch \"$SIGNAL\"; echo \"2|$line\"; fi"]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen", "seed": 100},
+    "tasks": [{"executor": "pair-exec"}]
+  }"#;
+
+  let run = |config: &str, args: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--regenerate-per-rep")
+      .arg("true")
+      .args(args)
+      .env("SIGNAL", &signal)
+      .env("NO_COLOR", "1")
+      .write_stdin(config.to_string());
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+  let rep_tokens = |stdout: &str| {
+    let mut rows: Vec<(u64, String)> = stdout
+      .lines()
+      .map(|l| {
+        let row: Value = serde_json::from_str(l).unwrap();
+        (
+          row["rep_index"].as_u64().unwrap(),
+          row["data_token"].as_str().unwrap().to_owned(),
+        )
+      })
+      .collect();
+    rows.sort();
+    rows
+  };
+
+  let stdout = run(
+    config_str,
+    &["--repetitions", "2", "--rep-concurrency", "2"],
+  );
+  assert_eq!(
+    rep_tokens(&stdout),
+    [(0, "100".to_string()), (1, "101".to_string())]
+  );
+
+  // Every repetition is run once, with its own generator seed.
+  let echo = config_str.replace(
+    r#"{"executor": "pair-exec"}"#,
+    r#"{"executor": "echo-exec"}"#,
+  );
+  let stdout = run(&echo, &["--repetitions", "5", "--rep-concurrency", "3"]);
+  assert_eq!(
+    rep_tokens(&stdout),
+    (0..5)
+      .map(|i| (i, (100 + i).to_string()))
+      .collect::<Vec<_>>()
+  );
+
+  // One repetition at a time is the default.
+  assert_eq!(
+    run(&echo, &["--repetitions", "3", "--rep-concurrency", "1"]),
+    run(&echo, &["--repetitions", "3"])
+  );
+}
+
+#[test]
+fn test_run_with_ordered_per_key() {
+  let temp = tempdir().unwrap();
+
+  // Later repetitions finish first, so their rows would arrive out of order.
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {"type": "generator", "command": "bash", "args": ["-c", "echo $IMPALAB_SEED"]},
+      "reverse-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r line; sleep 0.$(( (IMPALAB_REPS - IMPALAB_REP_INDEX - 1) * 3 )); echo \"1|$line\""]
+      }
+    },
+    "generator": {"name": "seed-gen", "seed": 100},
+    "tasks": [{"executor": "reverse-exec"}, {"executor": "reverse-exec", "args": ["b"]}]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--regenerate-per-rep")
+    .arg("true")
+    .arg("--repetitions")
+    .arg("3")
+    .arg("--rep-concurrency")
+    .arg("3")
+    .arg("--jobs")
+    .arg("2")
+    .arg("--ordered-per-key")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+
+  let mut reps: [Vec<u64>; 2] = Default::default();
+  for line in String::from_utf8(output).unwrap().lines() {
+    let row: Value = serde_json::from_str(line).unwrap();
+    reps[row["task_index"].as_u64().unwrap() as usize].push(row["rep_index"].as_u64().unwrap());
+  }
+  assert_eq!(reps, [[0, 1, 2], [0, 1, 2]]);
+}
+
+#[test]
+fn test_run_with_empty_manifest() {
+  let temp = tempdir().unwrap();
+  let manifest_path = temp.path().join("impa_manifest.json");
+
+  let run = |config: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(config.to_string());
+    run_cmd.assert()
+  };
+  let tasks = r#"{"tasks": [{"executor": "missing-exec"}]}"#;
+  let empty_manifest = format!(
+    "Build manifest at {} is empty or lists no components",
+    manifest_path.display()
+  );
+
+  fs::write(&manifest_path, "{}").unwrap();
+  run(tasks)
+    .failure()
+    .stderr(predicate::str::contains(empty_manifest.as_str()));
+
+  // Inline components do not need the manifest.
+  run(
+    r#"{
+    "components": {
+      "inline-exec": {"type": "executor", "command": "bash", "args": ["-c", "echo '1|a'"]}
+    },
+    "tasks": [{"executor": "inline-exec"}]
+  }"#,
+  )
+  .success();
+
+  fs::write(&manifest_path, "").unwrap();
+  run(tasks)
+    .failure()
+    .stderr(predicate::str::contains(empty_manifest.as_str()));
+
+  // Malformed JSON is still reported as such.
+  fs::write(&manifest_path, "{").unwrap();
+  run(tasks)
+    .failure()
+    .stderr(predicate::str::contains("empty or lists no components").not());
+}
+
+#[test]
+fn test_run_with_stdin_prelude() {
+  let temp = tempdir().unwrap();
+  fs::write(temp.path().join("prelude.txt"), "10\n").unwrap();
+
+  // `scale-exec` reads a factor from its first stdin line, then the data.
+  let config_str = r#"{
+    "components": {
+      "seq-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "seq $IMPALAB_SEED"]
+      },
+      "scale-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r n; while read -r line; do echo \"$((line * n))|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seq-gen", "seed": 2},
+    "tasks": [
+      {"executor": "scale-exec", "stdin_prelude": "3\n"},
+      {"executor": "scale-exec", "stdin_prelude": "@prelude.txt"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let metrics: Vec<(u64, String)> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str::<Value>(line).unwrap())
+    .map(|row| {
+      (
+        row["metric"].as_u64().unwrap(),
+        row["data_token"].as_str().unwrap().to_owned(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    metrics,
+    [
+      (3, "1".to_string()),
+      (6, "2".to_string()),
+      (10, "1".to_string()),
+      (20, "2".to_string())
+    ]
+  );
+}
+
+#[test]
+fn test_run_with_strict_manifest() {
+  let temp = tempdir().unwrap();
+  let manifest_path = temp.path().join("impa_manifest.json");
+  fs::write(
+    &manifest_path,
+    r#"{
+    "components": {
+      "echo-exec": {"type": "executor", "command": "bash", "args": ["-c", "echo '1|a'"]}
+    },
+    "default_generatr": "seq-gen"
+  }"#,
+  )
+  .unwrap();
+
+  let run = |strict: bool| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(r#"{"tasks": [{"executor": "echo-exec"}]}"#);
+    if strict {
+      run_cmd.arg("--strict-manifest");
+    }
+    run_cmd.assert()
+  };
+
+  // The misspelled key is ignored by default.
+  run(false).success();
+  run(true).failure().stderr(predicate::str::contains(format!(
+    r#"Build manifest at {} has unknown fields: ["default_generatr"]"#,
+    manifest_path.display()
+  )));
+}
+
+#[test]
+fn test_run_suggests_misspelled_component() {
+  let temp = tempdir().unwrap();
+
+  let run = |executor: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(format!(
+        r#"{{
+    "components": {{
+      "quicksort": {{"type": "executor", "command": "bash", "args": ["-c", "echo '1|a'"]}},
+      "mergesort": {{"type": "executor", "command": "bash", "args": ["-c", "echo '2|a'"]}}
+    }},
+    "tasks": [{{"executor": "{}"}}]
+  }}"#,
+        executor
+      ));
+    run_cmd.assert()
+  };
+
+  run("qucksort").failure().stderr(predicate::str::contains(
+    "Component 'qucksort' not found in manifest. Did you mean 'quicksort'?",
+  ));
+  // Without a close match, every available name is listed.
+  run("bubble").failure().stderr(predicate::str::contains(
+    r#"Component 'bubble' not found in manifest. Available: ["mergesort", "quicksort"]."#,
+  ));
+}
+
+#[test]
+fn test_run_with_echo_seed() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seed-gen": {"type": "generator", "command": "bash", "args": ["-c", "echo $IMPALAB_SEED"]},
+      "cat-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "seed-gen"},
+    "tasks": [{"executor": "cat-exec"}]
+  }"#;
+
+  let run = |seed: Option<&str>| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--echo-seed")
+      .env("NO_COLOR", "1")
+      .env_remove("IMPA_SEED")
+      .write_stdin(config_str);
+    if let Some(seed) = seed {
+      run_cmd.arg("--seed").arg(seed);
+    }
+    let output = run_cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row: Value = serde_json::from_str(stdout.lines().next().unwrap()).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let echoed: Vec<String> = stderr
+      .lines()
+      .filter_map(|line| line.strip_prefix("IMPA_SEED="))
+      .map(str::to_owned)
+      .collect();
+    (echoed, row["data_token"].as_str().unwrap().to_owned())
+  };
+
+  // A randomly chosen seed is echoed as the one the generator received.
+  let (echoed, token) = run(None);
+  assert_eq!(echoed, [token.as_str()]);
+
+  let (echoed, token) = run(Some("42"));
+  assert_eq!(echoed, ["42"]);
+  assert_eq!(token, "42");
+}
+
+#[test]
+fn test_run_warmup_uses_warmup_seed() {
+  let temp = tempdir().unwrap();
+  let seeds_log = temp.path().join("seeds.log");
+
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "seed-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo $IMPALAB_SEED >> '{}'; echo \"seed:$IMPALAB_SEED\""]
+      }},
+      "echo-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }}
+    }},
+    "generator": {{"name": "seed-gen", "seed": 42}},
+    "tasks": [
+      {{"executor": "echo-exec"}}
+    ]
+  }}"#,
+    seeds_log.display()
+  );
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--warmup")
+    .arg("2")
+    .arg("--warmup-seed")
+    .arg("7")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let stdout = String::from_utf8(output).unwrap();
+
+  // Warmup output is discarded; only the measured pipeline is reported.
+  assert_eq!(stdout.lines().count(), 1);
+  assert!(stdout.contains(r#""data_token":"seed:42""#));
+  assert_eq!(fs::read_to_string(&seeds_log).unwrap(), "7\n7\n42\n");
+}
+
+#[test]
+fn test_run_warmup_tolerates_malformed_output() {
+  let temp = tempdir().unwrap();
+  let marker = temp.path().join("warmed");
+
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "echo-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo hello"]
+      }},
+      "flaky-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "if [ -e '{0}' ]; then while read -r line; do echo \"1|$line\"; done; else touch '{0}'; cat >/dev/null; echo garbage; fi"]
+      }}
+    }},
+    "generator": {{"name": "echo-gen"}},
+    "tasks": [
+      {{"executor": "flaky-exec"}}
+    ]
+  }}"#,
+    marker.display()
+  );
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--warmup")
+    .arg("1")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let assert = run_cmd.assert().success();
+  let output = assert.get_output();
+  let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+  let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+
+  assert_eq!(stdout.lines().count(), 1);
+  assert!(stdout.contains(r#""data_token":"hello""#));
+  assert!(stderr.contains("Malformed output from warmup pipeline"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_classifies_spawn_errors() {
+  use std::os::unix::fs::PermissionsExt;
+
+  let temp = tempdir().unwrap();
+  let write_program = |name: &str, contents: &[u8], mode: u32| {
+    let path = temp.path().join(name);
+    fs::write(&path, contents).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    path
+  };
+  let no_exec = write_program("no-exec.sh", b"#!/bin/sh\necho '1|x'\n", 0o644);
+  let bad_format = write_program("bad-format", b"\x7fELF not really", 0o755);
+
+  let run = |command: &std::path::Path| {
+    let config_str = format!(
+      r#"{{
+    "components": {{
+      "exec": {{ "type": "executor", "command": "{}" }}
+    }},
+    "tasks": [ {{"executor": "exec"}} ]
+  }}"#,
+      command.display()
+    );
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd.assert().failure()
+  };
+
+  run(&temp.path().join("missing")).stderr(predicate::str::contains(
+    "Program for component 'exec' not found",
+  ));
+  run(&no_exec).stderr(predicate::str::contains(format!(
+    "Make it executable with `chmod +x {}`",
+    no_exec.display()
+  )));
+  run(&bad_format).stderr(predicate::str::contains("is not an executable format"));
+}
+
+#[test]
+fn test_build_records_default_generator() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let gen_dir = components_dir.join("const-gen");
+  let exec_dir = components_dir.join("echo-exec");
+  fs::create_dir_all(&gen_dir).unwrap();
+  fs::create_dir_all(&exec_dir).unwrap();
+
+  fs::write(
+    gen_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "const-gen"
+type = "generator"
+default = true
+run = { command = "echo", args = ["from-default"] }
+"#,
+  )
+  .unwrap();
+  fs::write(
+    exec_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "echo-exec"
+type = "executor"
+run = { command = "bash", args = ["-c", "while read -r line; do echo \"1|$line\"; done"] }
+"#,
+  )
+  .unwrap();
+
+  let mut build_cmd = Command::new(cargo::cargo_bin!("impa"));
+  build_cmd
+    .arg("build")
+    .arg("--components-dir")
+    .arg(&components_dir)
+    .arg("--root-dir")
+    .arg(temp.path())
+    .env("NO_COLOR", "1");
+  build_cmd.assert().success();
+
+  let manifest: Value =
+    serde_json::from_str(&fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap())
+      .unwrap();
+  assert_eq!(manifest["default_generator"], "const-gen");
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(r#"{"tasks": [{"executor": "echo-exec"}]}"#);
+    run_cmd
+  };
+
+  run()
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"from-default""#));
+
+  // An empty name opts out of the default generator.
+  run()
+    .arg("--set")
+    .arg("default_generator=")
+    .assert()
+    .success()
+    .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_build_from_multiple_components_dirs() {
+  let temp = tempdir().unwrap();
+  let write_component = |dir: &str, name: &str| {
+    let component_dir = temp.path().join(dir).join(name);
+    fs::create_dir_all(&component_dir).unwrap();
+    fs::write(
+      component_dir.join("impafile.toml"),
+      format!(
+        "[[components]]\nname = \"{}\"\ntype = \"executor\"\nrun = {{ command = \"true\" }}\n",
+        name
+      ),
+    )
+    .unwrap();
+  };
+  write_component("team-a", "exec-a");
+  write_component("team-b", "exec-b");
+
+  let build = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(temp.path().join("team-a"))
+      .arg("--components-dir")
+      .arg(temp.path().join("team-b"))
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    cmd
+  };
+
+  build().assert().success();
+  let manifest: Value =
+    serde_json::from_str(&fs::read_to_string(temp.path().join("impa_manifest.json")).unwrap())
+      .unwrap();
+  assert_eq!(
+    manifest["components"]["exec-a"]["working_dir"],
+    "team-a/exec-a"
+  );
+  assert_eq!(
+    manifest["components"]["exec-b"]["working_dir"],
+    "team-b/exec-b"
+  );
+
+  // Duplicate names are detected across directories.
+  write_component("team-b", "exec-a");
+  build().assert().failure().stderr(predicate::str::contains(
+    "Build failed for component: exec-a",
+  ));
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_run_with_msgpack_output() {
+  let temp = tempdir().unwrap();
+  let output = temp.path().join("results.msgpack");
+
+  let config_str = r#"{
+    "components": {
+      "multi-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a\n20|b|{\"k\":1}\n'"]
+      }
+    },
+    "reps": 2,
+    "tasks": [
+      {"executor": "multi-exec"}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--output-format")
+    .arg("msgpack")
+    .arg("--output-file")
+    .arg(&output)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::is_empty());
+
+  let records = impalab::msgpack::read_records(fs::File::open(&output).unwrap()).unwrap();
+  assert_eq!(records.len(), 4);
+  assert_eq!(records[1].data_token, "b");
+  assert_eq!(records[1].exec_meta, Some(serde_json::json!({"k": 1})));
+  assert_eq!(records[3].rep_index, 1);
+  assert_eq!(records[3].metric, Some(20.0));
+}
+
+#[cfg(feature = "jsonschema")]
+#[test]
+fn test_run_validates_generator_output_against_schema() {
+  let temp = tempdir().unwrap();
+  let schema = temp.path().join("schema.json");
+  fs::write(
+    &schema,
+    r#"{"type": "object", "properties": {"n": {"type": "integer"}}, "required": ["n"]}"#,
+  )
+  .unwrap();
+
+  let run = |gen_script: &str| {
+    let config_str = format!(
+      r#"{{
+      "components": {{
+        "json-gen": {{"type": "generator", "command": "bash", "args": ["-c", "{gen_script}"]}},
+        "echo-exec": {{
+          "type": "executor",
+          "command": "bash",
+          "args": ["-c", "while read -r line; do echo \"1|ok\"; done"]
+        }}
+      }},
+      "generator": {{"name": "json-gen"}},
+      "tasks": [{{"executor": "echo-exec"}}]
+    }}"#
+    );
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--validate-input-schema")
+      .arg(&schema)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd.assert()
+  };
+
+  run(r#"printf '{\\\"n\\\":1}\\n{\\\"n\\\":2}\\n'"#)
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"ok""#).count(2));
+
+  // The executor never sees input that fails validation.
+  run(r#"printf '{\\\"n\\\":1}\\n{\\\"n\\\":\\\"two\\\"}\\n'"#)
+    .failure()
+    .stdout(predicate::str::is_empty())
+    .stderr(predicate::str::contains(
+      r#"Generator output line 2 does not match the input schema"#,
+    ))
+    .stderr(predicate::str::contains(r#"{"n":"two"}"#));
+
+  run("echo not-json")
+    .failure()
+    .stderr(predicate::str::contains("line 1").and(predicate::str::contains("not valid JSON")));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_run_with_sqlite_output() {
+  let temp = tempdir().unwrap();
+  let output = temp.path().join("results.db");
+
+  let config_str = r#"{
+    "components": {
+      "multi-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "printf '10|a\n20|b|{\"k\":1}\n'"]
+      }
+    },
+    "reps": 2,
+    "tasks": [
+      {"executor": "multi-exec", "attributes": {"tier": "high"}}
+    ]
+  }"#;
+
+  // A second run appends to the same database.
+  for _ in 0..2 {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--output-format")
+      .arg("sqlite")
+      .arg("--output-file")
+      .arg(&output)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd
+      .assert()
+      .success()
+      .stdout(predicate::str::is_empty());
+  }
+
+  let conn = rusqlite::Connection::open(&output).unwrap();
+  let counts: Vec<i64> = conn
+    .prepare("SELECT COUNT(*) FROM results GROUP BY run_id")
+    .unwrap()
+    .query_map([], |row| row.get(0))
+    .unwrap()
+    .map(Result::unwrap)
+    .collect();
+  assert_eq!(counts, [4, 4]);
+
+  let (executor, rep_index, metric, status, metadata): (String, i64, f64, String, String) = conn
+    .query_row(
+      "SELECT executor, rep_index, metric, status, metadata FROM results
+       WHERE data_token = 'b' ORDER BY rowid DESC LIMIT 1",
+      [],
+      |row| {
+        Ok((
+          row.get(0)?,
+          row.get(1)?,
+          row.get(2)?,
+          row.get(3)?,
+          row.get(4)?,
+        ))
+      },
+    )
+    .unwrap();
+  assert_eq!(executor, "multi-exec");
+  assert_eq!(rep_index, 1);
+  assert_eq!(metric, 20.0);
+  assert_eq!(status, "ok");
+  assert_eq!(
+    metadata,
+    r#"{"attributes":{"tier":"high"},"exec_meta":{"k":1}}"#
+  );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_run_with_sqlite_output_records_crashed_executor() {
+  let temp = tempdir().unwrap();
+  let output = temp.path().join("results.db");
+
+  let config_str = r#"{
+    "components": {
+      "crashing-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|a'; kill -SEGV $$"]
+      }
+    },
+    "tasks": [{"executor": "crashing-exec"}]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--output-format")
+    .arg("sqlite")
+    .arg("--output-file")
+    .arg(&output)
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+  run_cmd.assert().failure();
+
+  // The failed run still records the row printed before the crash, followed
+  // by the one reporting it.
+  let conn = rusqlite::Connection::open(&output).unwrap();
+  let rows: Vec<(String, String)> = conn
+    .prepare("SELECT data_token, status FROM results ORDER BY rowid")
+    .unwrap()
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+    .unwrap()
+    .map(Result::unwrap)
+    .collect();
+  assert_eq!(
+    rows,
+    [
+      ("a".to_string(), "ok".to_string()),
+      (String::new(), "crashed".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn test_run_executor_setup_runs_once() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let gen_dir = components_dir.join("const-gen");
+  let exec_dir = components_dir.join("primed-exec");
+  fs::create_dir_all(&gen_dir).unwrap();
+  fs::create_dir_all(&exec_dir).unwrap();
+  fs::write(
+    gen_dir.join("impafile.toml"),
+    r#"
+[[components]]
+name = "const-gen"
+type = "generator"
+run = { command = "echo", args = ["token"] }
+"#,
+  )
+  .unwrap();
+
+  let impafile = |setup: &str| {
+    format!(
+      r#"
+[[components]]
+name = "primed-exec"
+type = "executor"
+run = {{ command = "bash", args = ["-c", "read -r line; echo \"$(cat primed.txt)|$line\""] }}
+
+[components.setup]
+command = "bash"
+args = ["-c", "{}"]
+"#,
+      setup
+    )
+  };
+
+  let build = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    cmd
+  };
+  let run = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(
+        r#"{"generator": {"name": "const-gen"}, "reps": 3, "tasks": [{"executor": "primed-exec"}]}"#,
+      );
+    cmd
+  };
+
+  fs::write(
+    exec_dir.join("impafile.toml"),
+    impafile("echo 7 > primed.txt; echo ran >> setup.log; echo setup-noise"),
+  )
+  .unwrap();
+  build().assert().success();
+
+  let output = run().assert().success().get_output().clone();
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  assert_eq!(stdout.lines().count(), 3);
+  assert!(stdout.lines().all(|l| l.contains(r#""metric":7"#)));
+  assert!(!stdout.contains("setup-noise"));
+  assert_eq!(
+    fs::read_to_string(exec_dir.join("setup.log")).unwrap(),
+    "ran\n"
+  );
+
+  fs::write(exec_dir.join("impafile.toml"), impafile("exit 3")).unwrap();
+  build().assert().success();
+  run().assert().failure().stderr(predicate::str::contains(
+    "Setup command for executor 'primed-exec' failed with exit code: Some(3)",
+  ));
+}
+
+#[test]
+fn test_run_executor_teardown_runs_after_failure() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let exec_dir = components_dir.join("flaky-exec");
+  fs::create_dir_all(&exec_dir).unwrap();
+
+  let impafile = |run: &str, teardown: &str| {
+    format!(
+      r#"
+[[components]]
+name = "flaky-exec"
+type = "executor"
+run = {{ command = "bash", args = ["-c", "{}"] }}
+
+[components.teardown]
+command = "bash"
+args = ["-c", "{}"]
+"#,
+      run, teardown
+    )
+  };
+
+  let build = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("build")
+      .arg("--components-dir")
+      .arg(&components_dir)
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1");
+    cmd
+  };
+  let run = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(r#"{"tasks": [{"executor": "flaky-exec"}]}"#);
+    cmd
+  };
+
+  // The measured run fails, but teardown still runs.
+  fs::write(
+    exec_dir.join("impafile.toml"),
+    impafile("exit 1", "echo done > torn_down.txt"),
+  )
+  .unwrap();
+  build().assert().success();
+  run().assert().failure();
+  assert_eq!(
+    fs::read_to_string(exec_dir.join("torn_down.txt")).unwrap(),
+    "done\n"
+  );
+
+  // A failing teardown is only fatal under --strict.
+  fs::write(
+    exec_dir.join("impafile.toml"),
+    impafile("echo '1|ok'", "exit 4"),
+  )
+  .unwrap();
+  build().assert().success();
+  run()
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"ok""#));
+  run()
+    .arg("--strict")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Teardown command for executor 'flaky-exec' failed with exit code: Some(4)",
+    ));
+}
+
+#[test]
+fn test_run_deterministic() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "sum-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r -a nums; sum=0; for n in \"${nums[@]}\"; do sum=$((sum + n)); done; echo \"${#nums[@]}|$sum\""]
+      }
+    },
+    "generator": {"name": "builtin:ints", "args": ["25"]},
+    "reps": 3,
+    "tasks": [
+      {"executor": "sum-exec", "args": ["a"]},
+      {"executor": "sum-exec", "args": ["b"]}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--deterministic")
+      .args(["--regenerate-per-rep", "true"])
+      .env_remove("IMPA_SEED")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+
+  let first = run();
+  assert_eq!(first.lines().count(), 6);
+  // Repetitions regenerate their input from the pinned seed plus their index.
+  assert!(first.lines().next().unwrap().contains(r#""seed":0,"#));
+  assert_eq!(first, run());
+
+  let mut jobs_cmd = Command::new(cargo::cargo_bin!("impa"));
+  jobs_cmd
+    .arg("run")
+    .arg("--deterministic")
+    .args(["--jobs", "2"])
+    .env("NO_COLOR", "1");
+  jobs_cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_run_with_builtin_generator() {
+  let temp = tempdir().unwrap();
+
+  // The executor reports how many integers it received, keyed by their sum.
+  let config_str = r#"{
+    "components": {
+      "sum-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "read -r -a nums; sum=0; for n in \"${nums[@]}\"; do sum=$((sum + n)); done; echo \"${#nums[@]}|$sum\""]
+      }
+    },
+    "generator": {"name": "builtin:ints", "args": ["25"]},
+    "tasks": [
+      {"executor": "sum-exec"}
+    ]
+  }"#;
+
+  let run = |seed: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--seed")
+      .arg(seed)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+
+  let first = run("7");
+  assert!(first.contains(r#""metric":25"#));
+  assert_eq!(first, run("7"));
+  assert_ne!(first, run("8"));
+
+  let mut unknown = Command::new(cargo::cargo_bin!("impa"));
+  unknown
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--set")
+    .arg("generator.name=builtin:floats")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str)
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      r#"Unknown builtin generator 'builtin:floats'. Available: ["builtin:ints"]."#,
+    ));
+}
+
+#[test]
+fn test_run_with_checksum_input() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seq-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "seq $IMPALAB_SEED"]
+      },
+      "sh-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      },
+      "awk-exec": {
+        "type": "executor",
+        "command": "awk",
+        "args": ["{ print \"2|\" $0 }"]
+      }
+    },
+    "generator": {"name": "seq-gen"},
+    "tasks": [
+      {"executor": "sh-exec"},
+      {"executor": "awk-exec"}
+    ]
+  }"#;
+
+  let run = |seed: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--checksum-input")
+      .arg("--seed")
+      .arg(seed)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str::<Value>(line).unwrap())
+      .collect::<Vec<_>>()
+  };
+
+  let rows = run("3");
+  assert_eq!(rows.len(), 6);
+  let checksum = rows[0]["input_checksum"].as_str().unwrap().to_owned();
+  assert_eq!(checksum.len(), 16);
+  // Both executors ran on the same input, so every row carries the same checksum.
+  assert!(
+    rows
+      .iter()
+      .all(|row| row["input_checksum"] == checksum.as_str())
+  );
+  assert_eq!(rows[3]["executor"], "awk-exec");
+
+  let other = run("4");
+  assert_ne!(other[0]["input_checksum"], checksum.as_str());
+}
+
+#[test]
+fn test_run_with_verify_input_identical() {
+  let temp = tempdir().unwrap();
+  let counter = temp.path().join("counter");
+
+  // `count-gen` emits how often it has run, so it is not seed-deterministic.
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "seq-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "seq $IMPALAB_SEED"]
+      }},
+      "count-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo x >> '{}'; wc -l < '{}'"]
+      }},
+      "sh-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }},
+      "awk-exec": {{
+        "type": "executor",
+        "command": "awk",
+        "args": ["{{ print \"2|\" $0 }}"]
+      }}
+    }},
+    "generator": {{"name": "GENERATOR"}},
+    "tasks": [
+      {{"executor": "sh-exec"}},
+      {{"executor": "awk-exec"}}
+    ]
+  }}"#,
+    counter.display(),
+    counter.display()
+  );
+
+  let run = |generator: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--verify-input-identical")
+      .arg("--seed")
+      .arg("3")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str.replace("GENERATOR", generator));
+    run_cmd.assert()
+  };
+
+  let output = run("seq-gen").success().get_output().stdout.clone();
+  let stdout = String::from_utf8(output).unwrap();
+  assert_eq!(stdout.lines().count(), 6);
+  // Checksums are compared, not added to the rows.
+  assert!(!stdout.contains("input_checksum"));
+
+  run("count-gen").failure().stderr(predicate::str::contains(
+    r#"Executors ["sh-exec", "awk-exec"] received different generator input for seed 3"#,
+  ));
+}
+
+#[test]
+fn test_run_with_args_separator() {
+  let temp = tempdir().unwrap();
+
+  // The executor reports how many arguments it received and the first of them.
+  let config_str = r#"{
+    "components": {
+      "argv-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo \"$#|$1\"", "argv-exec"]
+      }
+    },
+    "tasks": [
+      {"executor": "argv-exec", "args": ["sort", "search", "--size=10"]}
+    ]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd
+  };
+
+  run().assert().success().stdout(predicate::str::contains(
+    r#""data_token":"sort","metric":3"#,
+  ));
+  run()
+    .arg("--args-separator")
+    .arg(":")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+      r#""args":["sort","search","--size=10"],"rep_index":0,"data_token":"sort:search:--size=10","metric":1"#,
+    ));
+  run()
+    .arg("--args-separator")
+    .arg("")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("separator must not be empty"));
+}
+
+#[test]
+fn test_doctor_flags_missing_executable() {
+  let temp = tempdir().unwrap();
+  let manifest_path = temp.path().join("impa_manifest.json");
+
+  let doctor = || {
+    let mut cmd = Command::new(cargo::cargo_bin!("impa"));
+    cmd
+      .arg("doctor")
+      .arg("--components-dir")
+      .arg(temp.path())
+      .arg("--root-dir")
+      .arg(temp.path())
+      .env("NO_COLOR", "1")
+      .env("IMPA_SEED", "42");
+    cmd
+  };
+
+  doctor()
+    .assert()
+    .failure()
+    .stdout(predicate::str::contains("[FAIL] Manifest not found"));
+
+  fs::write(
+    &manifest_path,
+    r#"{"components": {
+      "ok-exec": {"type": "executor", "command": "bash"},
+      "missing-exec": {"type": "executor", "command": "no-such-program-for-impa"}
+    }}"#,
+  )
+  .unwrap();
+  doctor()
+    .assert()
+    .failure()
+    .stdout(predicate::str::contains(
+      "[FAIL] missing-exec (run): program not found or not executable: no-such-program-for-impa",
+    ))
+    .stdout(predicate::str::contains(
+      "[ok  ] ok-exec (run): bash resolves to",
+    ))
+    .stdout(predicate::str::contains("IMPA_SEED=42"))
+    .stdout(predicate::str::contains("1 problem(s) found."))
+    .stderr(predicate::str::contains("impa doctor found 1 problem(s)"));
+
+  fs::write(
+    &manifest_path,
+    r#"{"components": {"ok-exec": {"type": "executor", "command": "bash"}}}"#,
+  )
+  .unwrap();
+  doctor()
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("No problems found."));
+}
+
+#[test]
+fn test_run_with_generator_mix() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "gen-a": {"type": "generator", "command": "echo", "args": ["a"]},
+      "gen-b": {"type": "generator", "command": "echo", "args": ["b"]},
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "reps": 100,
+    "tasks": [{"executor": "echo-exec"}]
+  }"#;
+
+  let run = || {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--generator-mix")
+      .arg("gen-a:3,gen-b:1")
+      .arg("--seed")
+      .arg("11")
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str::<Value>(line).unwrap())
+      .map(|row| {
+        // The recorded pick must match the generator that produced the input.
+        let generator = row["attributes"]["generator"].as_str().unwrap().to_owned();
+        assert_eq!(
+          format!("gen-{}", row["data_token"].as_str().unwrap()),
+          generator
+        );
+        generator
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let picks = run();
+  assert_eq!(picks.len(), 100);
+  let a = picks.iter().filter(|g| *g == "gen-a").count();
+  assert!(
+    (55..=95).contains(&a),
+    "gen-a picked {} times out of 100",
+    a
+  );
+  assert_eq!(picks, run());
+
+  let mut invalid = Command::new(cargo::cargo_bin!("impa"));
+  invalid
+    .arg("run")
+    .arg("--generator-mix")
+    .arg("gen-a:0")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("expected a positive integer"));
+}
+
+#[test]
+fn test_run_with_orchestrator_duration() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "slow-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '5|first'; sleep 0.2; echo '5|second'"]
+      }
+    },
+    "tasks": [{"executor": "slow-exec"}]
+  }"#;
+
+  let run = |extra: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str::<Value>(line).unwrap())
+      .collect::<Vec<_>>()
+  };
+
+  let rows = run(&["--orchestrator-duration"]);
+  assert_eq!(rows.len(), 2);
+  let duration = rows[0]["orchestrator_duration_ms"].as_f64().unwrap();
+  assert!(duration >= 200.0, "duration was {}", duration);
+  // Both rows come from the same executor process, and keep their own metric.
+  assert_eq!(
+    rows[1]["orchestrator_duration_ms"],
+    rows[0]["orchestrator_duration_ms"]
+  );
+  assert_eq!(rows[1]["metric"], 5);
+
+  let rows = run(&[]);
+  assert!(rows[0].get("orchestrator_duration_ms").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_output_flush_on_signal() {
+  use std::io::Write;
+  use std::process::Stdio;
+
+  let temp = tempdir().unwrap();
+  let started = temp.path().join("started");
+
+  // The second repetition never finishes, so the run only ends on SIGTERM.
+  let config_str = format!(
+    r#"{{
+    "components": {{
+      "hanging-exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo \"1|rep$IMPALAB_REP_INDEX\"; if [ \"$IMPALAB_REP_INDEX\" = 1 ]; then touch '{}'; exec sleep 30 >/dev/null 2>&1; fi"]
+      }}
+    }},
+    "reps": 2,
+    "tasks": [
+      {{"executor": "hanging-exec"}}
+    ]
+  }}"#,
+    started.display()
+  );
+
+  let mut child = std::process::Command::new(cargo::cargo_bin!("impa"))
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--output-format")
+    .arg("json")
+    .arg("--output-flush-on-signal")
+    .env("NO_COLOR", "1")
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap();
+  child
+    .stdin
+    .take()
+    .unwrap()
+    .write_all(config_str.as_bytes())
+    .unwrap();
+
+  let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+  while !started.exists() {
+    assert!(
+      std::time::Instant::now() < deadline,
+      "executor never started"
+    );
+    std::thread::sleep(std::time::Duration::from_millis(20));
+  }
+  std::thread::sleep(std::time::Duration::from_millis(200));
+  assert_eq!(unsafe { libc::kill(child.id() as i32, libc::SIGTERM) }, 0);
+
+  let output = child.wait_with_output().unwrap();
+  assert!(!output.status.success());
+  assert!(String::from_utf8_lossy(&output.stderr).contains("terminated by SIGTERM"));
+
+  // The JSON array is closed, and holds the rows of both repetitions.
+  let rows: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[0]["data_token"], "rep0");
+  assert_eq!(rows[1]["data_token"], "rep1");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_warns_when_executor_ignores_input() {
+  let temp = tempdir().unwrap();
+
+  let config = |script: &str| {
+    format!(
+      r#"{{
+    "components": {{
+      "big-gen": {{
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "yes | head -n 200000"]
+      }},
+      "exec": {{
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "{}"]
+      }}
+    }},
+    "generator": {{"name": "big-gen"}},
+    "tasks": [
+      {{"executor": "exec"}}
+    ]
+  }}"#,
+      script
+    )
+  };
+
+  let run = |script: &str| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--batch-records")
+      .arg("1000")
+      .env("NO_COLOR", "1")
+      .write_stdin(config(script));
+    run_cmd
+  };
+
+  // The generator is drained, so it still exits successfully.
+  run("exec 0<&-; sleep 0.2; echo '1|done'")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(r#""data_token":"done""#))
+    .stderr(predicate::str::contains(
+      "Executor 'exec' exited without reading most of its input",
+    ));
+
+  run("cat > /dev/null; echo '1|done'")
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("without reading").not());
+}
+
+#[test]
+fn test_run_with_max_line_length() {
+  let temp = tempdir().unwrap();
+
+  // An endless line without a newline.
+  let config_str = r#"{
+    "components": {
+      "runaway-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|short'; yes x | tr -d '\\n'"]
+      }
+    },
+    "tasks": [{"executor": "runaway-exec"}]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--max-line-length")
+    .arg("1M")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  run_cmd
+    .timeout(std::time::Duration::from_secs(10))
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+      "Executor output line exceeds the maximum length of 1048576 bytes",
+    ));
+}
+
+#[test]
+fn test_run_with_kv_exec_output_format() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "kv-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo 'func=sort data_token=a metric=12 note=\"two words\"'"]
+      },
+      "bad-kv-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo 'func=sort metric=12'"]
+      }
+    },
+    "tasks": [{"executor": "kv-exec"}]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--exec-output-format")
+    .arg("kv")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let row: Value = serde_json::from_slice(&output).unwrap();
+  assert_eq!(row["data_token"], "a");
+  assert_eq!(row["metric"], 12);
+  assert_eq!(row["exec_meta"]["func"], "sort");
+  assert_eq!(row["exec_meta"]["note"], "two words");
+
+  let mut bad_cmd = Command::new(cargo::cargo_bin!("impa"));
+  bad_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--exec-output-format")
+    .arg("kv")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str.replace(
+      r#"{"executor": "kv-exec"}"#,
+      r#"{"executor": "bad-kv-exec"}"#,
+    ));
+
+  bad_cmd.assert().failure().stderr(predicate::str::contains(
+    "Missing required key 'data_token'",
+  ));
+}
+
+#[test]
+fn test_run_with_extra_metrics() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "counting-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1234|case1|comparisons=5012|bytes=4096'; echo '99|case2'"]
+      }
+    },
+    "tasks": [{"executor": "counting-exec"}]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let stdout = String::from_utf8(output).unwrap();
+  let lines: Vec<&str> = stdout.lines().collect();
+  assert!(
+    lines[0]
+      .ends_with(r#""metric":1234,"metrics":{"bytes":4096.0,"comparisons":5012.0},"status":"ok"}"#)
+  );
+  assert!(lines[1].ends_with(r#""metric":99,"status":"ok"}"#));
+}
+
+#[test]
+fn test_run_reports_failed_executors_in_status_rows() {
+  let temp = tempdir().unwrap();
+
+  let run = |script: &str, extra: &[&str]| {
+    let config_str = format!(
+      r#"{{
+      "components": {{
+        "failing-exec": {{"type": "executor", "command": "bash", "args": ["-c", "{script}"]}}
+      }},
+      "tasks": [{{"executor": "failing-exec"}}]
+    }}"#
+    );
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    let output = run_cmd.assert().failure().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|l| serde_json::from_str(l).unwrap())
+      .collect::<Vec<Value>>()
+  };
+
+  // Rows printed before a segmentation fault are followed by one reporting it.
+  let rows = run("echo '1|a'; kill -SEGV $$", &[]);
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[0]["status"], "ok");
+  assert_eq!(rows[1]["status"], "crashed");
+  assert_eq!(rows[1]["executor"], "failing-exec");
+  assert!(rows[1].get("metric").is_none());
+
+  let rows = run("sleep 5", &["--timeout", "200ms"]);
+  assert_eq!(rows.len(), 1);
+  assert_eq!(rows[0]["status"], "timeout");
+  assert_eq!(rows[0]["rep_index"], 0);
+}
+
+#[test]
+fn test_run_with_no_fail_on_error() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "crashing-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|a'; exit 3"]
+      }
+    },
+    "tasks": [{"executor": "crashing-exec"}]
+  }"#;
+
+  let run = |extra: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    run_cmd.assert()
+  };
+
+  run(&[]).failure().code(1).stderr(predicate::str::contains(
+    "Executor process failed with exit code: Some(3)",
+  ));
+
+  // The row printed before the failure is followed by one reporting it.
+  let output = run(&["--no-fail-on-error"]).success().get_output().clone();
+  let rows: Vec<Value> = String::from_utf8(output.stdout)
+    .unwrap()
+    .lines()
+    .map(|l| serde_json::from_str(l).unwrap())
+    .collect();
+  assert_eq!(rows.len(), 2);
+  assert_eq!(rows[0]["status"], "ok");
+  assert_eq!(rows[1]["status"], "nonzero_exit");
+  assert!(rows[1].get("metric").is_none());
+  assert!(String::from_utf8_lossy(&output.stderr).contains("Executor process failed"));
+}
+
+#[test]
+fn test_run_with_crlf_line_ending() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "two-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '1|a'; echo '2|b'"]
+      }
+    },
+    "tasks": [{"executor": "two-exec"}]
+  }"#;
+
+  let run = |extra: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+  };
+
+  let stdout = run(&["--line-ending", "crlf"]);
+  let records: Vec<&str> = stdout.split_terminator("\r\n").collect();
+  assert_eq!(records.len(), 2);
+  assert!(records.iter().all(|r| !r.contains('\n')));
+  assert_eq!(
+    serde_json::from_str::<Value>(records[1]).unwrap()["data_token"],
+    "b"
+  );
+
+  let stdout = run(&["--line-ending", "crlf", "--output-format", "json"]);
+  assert!(stdout.starts_with("[\r\n{"));
+  assert!(stdout.ends_with("}\r\n]\r\n"));
+  assert_eq!(
+    serde_json::from_str::<Vec<Value>>(&stdout).unwrap().len(),
+    2
+  );
+
+  // Every line of a pretty-printed row ends the same way.
+  let stdout = run(&["--line-ending", "crlf", "--output-format", "pretty"]);
+  assert!(stdout.ends_with("}\r\n"));
+  assert_eq!(stdout.matches('\n').count(), stdout.matches("\r\n").count());
+  assert_eq!(stdout.matches("}\r\n").count(), 2);
+
+  let stdout = run(&[]);
+  assert!(!stdout.contains('\r'));
+  assert_eq!(stdout.lines().count(), 2);
+}
+
+#[test]
+fn test_run_with_only_summary() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "seq-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "for i in $(seq 1 1000); do echo \"$i|t$i\"; done"]
+      }
+    },
+    "tasks": [
+      {"executor": "seq-exec", "args": ["a"]},
+      {"executor": "seq-exec", "args": ["b"]}
+    ]
+  }"#;
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--root-dir")
+    .arg(temp.path())
+    .arg("--config")
+    .arg("-")
+    .arg("--only-summary")
+    .env("NO_COLOR", "1")
+    .write_stdin(config_str);
+
+  let output = run_cmd.assert().success().get_output().stdout.clone();
+  let records: Vec<Value> = String::from_utf8(output)
+    .unwrap()
+    .lines()
+    .map(|l| serde_json::from_str(l).unwrap())
+    .collect();
+  assert_eq!(records.len(), 2);
+  assert!(records.iter().all(|r| r["type"] == "summary"));
+  assert!(records.iter().all(|r| r.get("data_token").is_none()));
+
+  let summary = &records[1];
+  assert_eq!(summary["task_index"], 1);
+  assert_eq!(summary["args"], serde_json::json!(["b"]));
+  assert_eq!(summary["count"], 1000);
+  assert_eq!(summary["min"], 1.0);
+  assert_eq!(summary["max"], 1000.0);
+  let p95 = summary["p95"].as_f64().unwrap();
+  assert!((p95 - 950.0).abs() < 10.0, "p95 was {}", p95);
+}
+
+#[test]
+fn test_run_with_report_throughput() {
+  let temp = tempdir().unwrap();
+
+  // Durations are in nanoseconds: 1000 items in 2ms, then a zero duration,
+  // then 500 items in 1ms after a `#count=` hint.
+  let config_str = r#"{
+    "components": {
+      "timed-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "echo '2000000|a'; echo '0|b'; echo '#count=500'; echo '1000000|c'"]
+      }
+    },
+    "tasks": [{"executor": "timed-exec"}]
+  }"#;
+
+  let run = |args: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .arg("--report-throughput")
+      .arg("--item-count")
+      .arg("1000")
+      .args(args)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|l| serde_json::from_str::<Value>(l).unwrap())
+      .collect::<Vec<_>>()
+  };
+
+  let rows = run(&[]);
+  assert_eq!(rows.len(), 3);
+  assert_eq!(rows[0]["metrics"]["items_per_sec"], 500_000.0);
+  assert!(rows[1].get("metrics").is_none());
+  assert_eq!(rows[2]["metrics"]["items_per_sec"], 500_000.0);
+
+  // 1500 items over 3ms; the zero-duration row does not count.
+  let summary = run(&["--only-summary"]);
+  assert_eq!(summary.len(), 1);
+  assert_eq!(summary[0]["items_per_sec"], 500_000.0);
+
+  let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+  run_cmd
+    .arg("run")
+    .arg("--item-count")
+    .arg("1000")
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("--report-throughput"));
+}
+
+#[test]
+fn test_run_with_repetitions() {
+  let temp = tempdir().unwrap();
+
+  let config_str = r#"{
+    "components": {
+      "nonce-gen": {
+        "type": "generator",
+        "command": "bash",
+        "args": ["-c", "echo \"seed$IMPALAB_SEED-$(cat /proc/sys/kernel/random/uuid)\""]
+      },
+      "echo-exec": {
+        "type": "executor",
+        "command": "bash",
+        "args": ["-c", "while read -r line; do echo \"1|$line\"; done"]
+      }
+    },
+    "generator": {"name": "nonce-gen"},
+    "reps": 5,
+    "tasks": [
+      {"executor": "echo-exec"}
+    ]
+  }"#;
+
+  let run = |extra: &[&str]| {
+    let mut run_cmd = Command::new(cargo::cargo_bin!("impa"));
+    run_cmd
+      .arg("run")
+      .arg("--root-dir")
+      .arg(temp.path())
+      .arg("--config")
+      .arg("-")
+      .args(extra)
+      .env("NO_COLOR", "1")
+      .write_stdin(config_str);
+    let output = run_cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output)
+      .unwrap()
+      .lines()
+      .map(|l| serde_json::from_str::<Value>(l).unwrap())
+      .collect::<Vec<_>>()
+  };
+
+  // Every repetition sees the identical input and is told apart by `rep_index`.
+  let rows = run(&["--repetitions", "3"]);
+  assert_eq!(rows.len(), 3);
+  for (rep, row) in rows.iter().enumerate() {
+    assert_eq!(row["rep_index"], rep);
+    assert_eq!(row["data_token"], rows[0]["data_token"]);
+  }
+
+  let rows = run(&["--repetitions", "3", "--set", "reps=1"]);
+  assert_eq!(rows.len(), 1);
+}
+
+#[test]
+fn test_build_watch_rebuilds_changed_component() {
+  let temp = tempdir().unwrap();
+  let components_dir = temp.path().join("components");
+  let manifest_path = temp.path().join("impa_manifest.json");
+
+  // Build logs live outside the watched directory.
+  let add_component = |name: &str| {
+    let dir = components_dir.join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+      dir.join("impafile.toml"),
+      format!(
+        r#"[[components]]
+name = "{name}"
+type = "executor"
+build = {{ command = "bash", args = ["-c", "echo built >> '{log}'"] }}
+run = {{ command = "true" }}
+"#,
+        name = name,
+        log = temp.path().join(format!("{}.log", name)).display()
+      ),
+    )
+    .unwrap();
+  };
+  let builds = |name: &str| {
+    fs::read_to_string(temp.path().join(format!("{}.log", name)))
+      .map(|log| log.lines().count())
+      .unwrap_or(0)
+  };
+  let components = || -> Vec<String> {
+    fs::read_to_string(&manifest_path)
+      .ok()
+      .and_then(|m| serde_json::from_str::<Value>(&m).ok())
+      .and_then(|m| {
+        m["components"]
+          .as_object()
+          .map(|c| c.keys().cloned().collect())
+      })
+      .unwrap_or_default()
+  };
+  let wait_for = |what: &str, cond: &dyn Fn() -> bool| {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while !cond() {
+      assert!(
+        std::time::Instant::now() < deadline,
+        "timed out waiting for {}",
+        what
+      );
+      std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+  };
+
+  add_component("comp-a");
+  add_component("comp-b");
+
+  let mut child = std::process::Command::new(cargo::cargo_bin!("impa"))
+    .arg("build")
+    .arg("--watch")
+    .arg("--debounce")
+    .arg("100ms")
+    .arg("--components-dir")
+    .arg(&components_dir)
+    .arg("--root-dir")
+    .arg(temp.path())
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .spawn()
+    .unwrap();
+
+  wait_for("the initial build", &|| components().len() == 2);
+  assert_eq!((builds("comp-a"), builds("comp-b")), (1, 1));
+  // Let the watcher start.
+  std::thread::sleep(std::time::Duration::from_millis(500));
+
+  fs::write(components_dir.join("comp-a").join("main.py"), "edited").unwrap();
+  wait_for("comp-a to be rebuilt", &|| builds("comp-a") == 2);
+  std::thread::sleep(std::time::Duration::from_millis(500));
+  assert_eq!((builds("comp-a"), builds("comp-b")), (2, 1));
+
+  add_component("comp-c");
+  wait_for("comp-c to be registered", &|| components().len() == 3);
+  fs::remove_dir_all(components_dir.join("comp-b")).unwrap();
+  wait_for("comp-b to be pruned", &|| {
+    components() == ["comp-a".to_string(), "comp-c".to_string()]
+  });
+  assert_eq!(builds("comp-c"), 1);
+
+  child.kill().unwrap();
+  child.wait().unwrap();
+}